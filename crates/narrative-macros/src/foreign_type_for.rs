@@ -0,0 +1,194 @@
+use quote::{format_ident, quote};
+use syn::parse::{Parse, ParseStream};
+
+mod kw {
+    syn::custom_keyword!(display);
+    syn::custom_keyword!(deserialize);
+}
+
+/// The attr like `#[foreign_type_for(MyStory, display, deserialize)]`.
+pub struct ForeignTypeAttr {
+    /// The stories that the newtype is local to.
+    pub story_names: Vec<syn::Ident>,
+    /// Whether the value is serialized by `Display` and deserialized by `FromStr`, for foreign
+    /// types without serde support.
+    pub display: Option<kw::display>,
+    /// Whether `Deserialize` is implemented too.
+    pub deserialize: Option<kw::deserialize>,
+}
+
+impl Parse for ForeignTypeAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut story_names = vec![input.parse()?];
+        let mut display = None;
+        let mut deserialize = None;
+        while !input.is_empty() {
+            input.parse::<syn::Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            let lookahead = input.lookahead1();
+            if lookahead.peek(kw::display) {
+                display = Some(input.parse()?);
+            } else if lookahead.peek(kw::deserialize) {
+                deserialize = Some(input.parse()?);
+            } else if lookahead.peek(syn::Ident) {
+                story_names.push(input.parse()?);
+            } else {
+                return Err(lookahead.error());
+            }
+        }
+        Ok(Self {
+            story_names,
+            display,
+            deserialize,
+        })
+    }
+}
+
+pub(crate) fn generate(
+    attr: &ForeignTypeAttr,
+    input_item: &syn::ItemStruct,
+) -> proc_macro2::TokenStream {
+    let foreign_type = match &input_item.fields {
+        syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1 => &fields.unnamed[0].ty,
+        _ => {
+            return syn::Error::new_spanned(
+                input_item,
+                "foreign_type_for expects a newtype of the foreign type like `struct StatusCode(http::StatusCode);`",
+            )
+            .to_compile_error();
+        }
+    };
+    if !input_item.generics.params.is_empty() {
+        return syn::Error::new_spanned(
+            &input_item.generics,
+            "foreign_type_for doesn't support generic newtypes",
+        )
+        .to_compile_error();
+    }
+    let type_name = &input_item.ident;
+    let local_type_traits = attr
+        .story_names
+        .iter()
+        .map(|story_name| format_ident!("{}LocalType", story_name));
+    let serialize = if attr.display.is_some() {
+        quote!(serializer.collect_str(&self.0))
+    } else {
+        quote!(narrative::serde::Serialize::serialize(&self.0, serializer))
+    };
+    let deserialize = attr.deserialize.map(|_| {
+        let body = if attr.display.is_some() {
+            quote! {
+                let text = <String as narrative::serde::Deserialize>::deserialize(deserializer)?;
+                text.parse().map(Self).map_err(narrative::serde::de::Error::custom)
+            }
+        } else {
+            quote!(narrative::serde::Deserialize::deserialize(deserializer).map(Self))
+        };
+        quote! {
+            impl<'de> narrative::serde::Deserialize<'de> for #type_name {
+                fn deserialize<D: narrative::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    #body
+                }
+            }
+        }
+    });
+
+    quote! {
+        #input_item
+
+        impl std::fmt::Debug for #type_name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                std::fmt::Debug::fmt(&self.0, f)
+            }
+        }
+
+        impl Clone for #type_name {
+            fn clone(&self) -> Self {
+                Self(self.0.clone())
+            }
+        }
+
+        impl narrative::serde::Serialize for #type_name {
+            fn serialize<S: narrative::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                #serialize
+            }
+        }
+
+        #deserialize
+
+        impl std::ops::Deref for #type_name {
+            type Target = #foreign_type;
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl From<#foreign_type> for #type_name {
+            fn from(value: #foreign_type) -> Self {
+                Self(value)
+            }
+        }
+
+        impl From<#type_name> for #foreign_type {
+            fn from(value: #type_name) -> Self {
+                value.0
+            }
+        }
+
+        // The foreign type is not asserted, so the newtype lets it in the story.
+        impl narrative::StoryOwnedType for #type_name {}
+
+        #(impl #local_type_traits for #type_name {})*
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_attr() {
+        let attr: ForeignTypeAttr = syn::parse_quote!(Checkout, display, Login, deserialize);
+        assert_eq!(attr.story_names, ["Checkout", "Login"]);
+        assert!(attr.display.is_some());
+        assert!(attr.deserialize.is_some());
+        assert!(syn::parse_str::<ForeignTypeAttr>("Checkout, display = true").is_err());
+    }
+
+    #[test]
+    fn test_serialize_by_display() {
+        let attr: ForeignTypeAttr = syn::parse_quote!(Checkout, display, deserialize);
+        let input: syn::ItemStruct = syn::parse_quote! {
+            pub struct StatusCode(pub http::StatusCode);
+        };
+        let actual = generate(&attr, &input).to_string();
+        assert!(actual.contains(&quote!(serializer.collect_str(&self.0)).to_string()));
+        assert!(
+            actual.contains(
+                &quote!(
+                    text.parse()
+                        .map(Self)
+                        .map_err(narrative::serde::de::Error::custom)
+                )
+                .to_string()
+            )
+        );
+        assert!(actual.contains(&quote!(impl CheckoutLocalType for StatusCode {}).to_string()));
+    }
+
+    #[test]
+    fn test_not_newtype() {
+        let attr: ForeignTypeAttr = syn::parse_quote!(Checkout);
+        let input: syn::ItemStruct = syn::parse_quote! {
+            pub struct StatusCode { code: http::StatusCode }
+        };
+        assert!(
+            generate(&attr, &input)
+                .to_string()
+                .contains("foreign_type_for expects a newtype")
+        );
+    }
+}
@@ -0,0 +1,1046 @@
+//! Generates story traits from a Gherkin feature file.
+
+use std::collections::BTreeSet;
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+/// An error at a line and a column of a feature file, both of which start from 1.
+#[derive(Debug, PartialEq)]
+pub(crate) struct FeatureError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl FeatureError {
+    fn new(line: usize, column: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    /// Reports the error at the path of the feature file, prefixed with the location in the file
+    /// like `features/shop.feature:3:5: ...`.
+    fn to_syn_error(&self, path: &syn::LitStr) -> syn::Error {
+        syn::Error::new(
+            path.span(),
+            format!(
+                "{}:{}:{}: {}",
+                path.value(),
+                self.line,
+                self.column,
+                self.message
+            ),
+        )
+    }
+}
+
+/// Reads the feature file and generates the story traits.
+pub(crate) fn expand(path: &syn::LitStr) -> syn::Result<TokenStream> {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let full_path = std::path::Path::new(&manifest_dir).join(path.value());
+    let source = std::fs::read_to_string(&full_path).map_err(|err| {
+        syn::Error::new(
+            path.span(),
+            format!(
+                "{}: failed to read `{}`: {err}",
+                path.value(),
+                full_path.display()
+            ),
+        )
+    })?;
+    let stories = parse_feature(&source)
+        .and_then(|feature| generate(&feature))
+        .map_err(|err| err.to_syn_error(path))?;
+    // Makes the crate rebuilt when the feature file changes.
+    let full_path = full_path.to_string_lossy();
+    Ok(quote! {
+        const _: &str = include_str!(#full_path);
+        #stories
+    })
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct Feature {
+    pub title: String,
+    pub background: Option<Scenario>,
+    pub scenarios: Vec<Scenario>,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct Scenario {
+    pub title: String,
+    pub line: usize,
+    pub steps: Vec<Step>,
+    /// The examples of a scenario outline, which is empty for a plain scenario.
+    pub examples: Vec<Examples>,
+    pub is_outline: bool,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct Step {
+    /// The text without the keyword like `Given`.
+    pub text: String,
+    pub line: usize,
+    pub column: usize,
+    pub doc_string: Option<String>,
+    pub table: Vec<Vec<String>>,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct Examples {
+    pub title: String,
+    pub line: usize,
+    /// The header and the rows.
+    pub table: Vec<Vec<String>>,
+}
+
+const STEP_KEYWORDS: &[&str] = &["Given ", "When ", "Then ", "And ", "But ", "* "];
+
+/// Where the next lines go.
+enum Section {
+    Feature,
+    Background,
+    Scenario,
+    Examples,
+}
+
+pub(crate) fn parse_feature(source: &str) -> Result<Feature, FeatureError> {
+    let mut feature: Option<Feature> = None;
+    let mut section = Section::Feature;
+    let mut lines = source
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line));
+    while let Some((line, raw)) = lines.next() {
+        let text = raw.trim();
+        let column = raw.len() - raw.trim_start().len() + 1;
+        if text.is_empty() || text.starts_with('#') || text.starts_with('@') {
+            continue;
+        }
+        let Some(feature) = &mut feature else {
+            let Some(title) = strip_keyword(text, &["Feature:"]) else {
+                return Err(FeatureError::new(
+                    line,
+                    column,
+                    "expected `Feature:` at the start of the feature file",
+                ));
+            };
+            feature = Some(Feature {
+                title: title.to_string(),
+                background: None,
+                scenarios: Vec::new(),
+            });
+            continue;
+        };
+        let current = match section {
+            Section::Feature => None,
+            Section::Background => feature.background.as_mut(),
+            Section::Scenario | Section::Examples => feature.scenarios.last_mut(),
+        };
+        if strip_keyword(text, &["Rule:"]).is_some() {
+            section = Section::Feature;
+        } else if strip_keyword(text, &["Background:"]).is_some() {
+            if feature.background.is_some() {
+                return Err(FeatureError::new(line, column, "duplicate `Background:`"));
+            }
+            if !feature.scenarios.is_empty() {
+                return Err(FeatureError::new(
+                    line,
+                    column,
+                    "`Background:` must come before the scenarios",
+                ));
+            }
+            feature.background = Some(Scenario::new(String::new(), line, false));
+            section = Section::Background;
+        } else if let Some(title) =
+            strip_keyword(text, &["Scenario Outline:", "Scenario Template:"])
+        {
+            feature
+                .scenarios
+                .push(Scenario::new(title.to_string(), line, true));
+            section = Section::Scenario;
+        } else if let Some(title) = strip_keyword(text, &["Scenario:", "Example:"]) {
+            feature
+                .scenarios
+                .push(Scenario::new(title.to_string(), line, false));
+            section = Section::Scenario;
+        } else if let Some(title) = strip_keyword(text, &["Examples:", "Scenarios:"]) {
+            let Some(scenario) = current.filter(|scenario| scenario.is_outline) else {
+                return Err(FeatureError::new(
+                    line,
+                    column,
+                    "`Examples:` must follow the steps of a `Scenario Outline:`",
+                ));
+            };
+            scenario.examples.push(Examples {
+                title: title.to_string(),
+                line,
+                table: Vec::new(),
+            });
+            section = Section::Examples;
+        } else if let Some(step_text) = strip_keyword(text, STEP_KEYWORDS) {
+            let (Section::Background | Section::Scenario, Some(scenario)) = (&section, current)
+            else {
+                return Err(FeatureError::new(
+                    line,
+                    column,
+                    "a step must be in a scenario or a background",
+                ));
+            };
+            let keyword_len = text.len() - text[text.find(' ').unwrap_or(0)..].trim_start().len();
+            scenario.steps.push(Step {
+                text: step_text.to_string(),
+                line,
+                column: column + keyword_len,
+                doc_string: None,
+                table: Vec::new(),
+            });
+        } else if text.starts_with('|') {
+            let row = parse_row(text, line, column)?;
+            let table = match (&section, current) {
+                (Section::Examples, Some(scenario)) => scenario
+                    .examples
+                    .last_mut()
+                    .map(|examples| &mut examples.table),
+                (Section::Background | Section::Scenario, Some(scenario)) => {
+                    scenario.steps.last_mut().map(|step| &mut step.table)
+                }
+                _ => None,
+            };
+            let Some(table) = table else {
+                return Err(FeatureError::new(
+                    line,
+                    column,
+                    "a table must follow a step or `Examples:`",
+                ));
+            };
+            if let Some(header) = table.first()
+                && header.len() != row.len()
+            {
+                return Err(FeatureError::new(
+                    line,
+                    column,
+                    format!(
+                        "the row has {} cells, but the first row has {}",
+                        row.len(),
+                        header.len()
+                    ),
+                ));
+            }
+            table.push(row);
+        } else if let Some(delimiter) = ["\"\"\"", "```"]
+            .into_iter()
+            .find(|delimiter| text.starts_with(delimiter))
+        {
+            let step = match (&section, current) {
+                (Section::Background | Section::Scenario, Some(scenario)) => scenario
+                    .steps
+                    .last_mut()
+                    .filter(|step| step.doc_string.is_none()),
+                _ => None,
+            };
+            let Some(step) = step else {
+                return Err(FeatureError::new(
+                    line,
+                    column,
+                    "a doc string must follow a step",
+                ));
+            };
+            let mut doc_string = Vec::new();
+            loop {
+                let Some((_, raw)) = lines.next() else {
+                    return Err(FeatureError::new(
+                        line,
+                        column,
+                        format!("unclosed doc string, close it with `{delimiter}`"),
+                    ));
+                };
+                if raw.trim() == delimiter {
+                    break;
+                }
+                // The indentation of the delimiter is removed from the content. Both are in bytes,
+                // since the indentation may have multi-byte whitespaces like U+3000.
+                let indent = raw
+                    .char_indices()
+                    .take_while(|(index, c)| *index < column - 1 && c.is_whitespace())
+                    .last()
+                    .map_or(0, |(index, c)| index + c.len_utf8());
+                doc_string.push(&raw[indent..]);
+            }
+            step.doc_string = Some(doc_string.join("\n"));
+        } else {
+            // Free texts are the descriptions of the feature, the scenarios, and the examples
+            // before their steps or tables.
+            let is_description = match (&section, current) {
+                (Section::Feature, _) => true,
+                (Section::Background | Section::Scenario, Some(scenario)) => {
+                    scenario.steps.is_empty()
+                }
+                (Section::Examples, Some(scenario)) => scenario
+                    .examples
+                    .last()
+                    .is_some_and(|examples| examples.table.is_empty()),
+                _ => false,
+            };
+            if !is_description {
+                return Err(FeatureError::new(
+                    line,
+                    column,
+                    format!(
+                        "expected a step like `Given ...`, `When ...`, or `Then ...`, found `{text}`"
+                    ),
+                ));
+            }
+        }
+    }
+    let Some(feature) = feature else {
+        return Err(FeatureError::new(1, 1, "the feature file is empty"));
+    };
+    if feature.scenarios.is_empty() {
+        return Err(FeatureError::new(1, 1, "the feature has no scenarios"));
+    }
+    Ok(feature)
+}
+
+impl Scenario {
+    fn new(title: String, line: usize, is_outline: bool) -> Self {
+        Self {
+            title,
+            line,
+            steps: Vec::new(),
+            examples: Vec::new(),
+            is_outline,
+        }
+    }
+}
+
+fn strip_keyword<'a>(text: &'a str, keywords: &[&str]) -> Option<&'a str> {
+    keywords
+        .iter()
+        .find_map(|keyword| text.strip_prefix(keyword))
+        .map(str::trim)
+}
+
+fn parse_row(text: &str, line: usize, column: usize) -> Result<Vec<String>, FeatureError> {
+    let Some(inner) = text
+        .strip_prefix('|')
+        .and_then(|text| text.strip_suffix('|'))
+    else {
+        return Err(FeatureError::new(line, column, "a row must end with `|`"));
+    };
+    let mut cells = vec![String::new()];
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('|') => cells.last_mut().unwrap().push('|'),
+                Some('n') => cells.last_mut().unwrap().push('\n'),
+                Some(c) => cells.last_mut().unwrap().push(c),
+                None => cells.last_mut().unwrap().push('\\'),
+            },
+            '|' => cells.push(String::new()),
+            c => cells.last_mut().unwrap().push(c),
+        }
+    }
+    Ok(cells
+        .into_iter()
+        .map(|cell| cell.trim().to_string())
+        .collect())
+}
+
+/// A part of a step text.
+#[derive(Debug, PartialEq)]
+enum TextPart {
+    Literal(String),
+    /// A number like `5` or `-1.5`.
+    Number(String),
+    /// The content of a quoted string like `"Thank you"`.
+    Quoted(String),
+    /// A column of the examples like `<user>`, which may be quoted.
+    Placeholder {
+        column: String,
+        quoted: bool,
+    },
+}
+
+fn split_text<'a>(text: &'a str, columns: &[String]) -> Vec<TextPart> {
+    let mut parts = Vec::new();
+    let mut literal = String::new();
+    let mut rest = text;
+    let placeholder = |text: &'a str| {
+        text.strip_prefix('<')
+            .and_then(|text| text.split_once('>'))
+            .filter(|(column, _)| columns.iter().any(|name| name == column))
+            .map(|(column, rest)| (column.to_string(), rest))
+    };
+    while let Some(c) = rest.chars().next() {
+        let at_word_start = !literal.ends_with(|c: char| c.is_alphanumeric() || c == '_');
+        let part = if c == '"' {
+            rest[1..].split_once('"').map(|(content, after)| {
+                match placeholder(content).filter(|(_, rest)| rest.is_empty()) {
+                    Some((column, _)) => (
+                        TextPart::Placeholder {
+                            column,
+                            quoted: true,
+                        },
+                        after,
+                    ),
+                    None => (TextPart::Quoted(content.to_string()), after),
+                }
+            })
+        } else if c == '<' {
+            placeholder(rest).map(|(column, after)| {
+                (
+                    TextPart::Placeholder {
+                        column,
+                        quoted: false,
+                    },
+                    after,
+                )
+            })
+        } else if at_word_start && (c.is_ascii_digit() || c == '-') {
+            let sign = usize::from(c == '-');
+            let digits = |text: &str| {
+                text.find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(text.len())
+            };
+            let mut end = sign + digits(&rest[sign..]);
+            if rest[end..].starts_with('.') && digits(&rest[end + 1..]) > 0 {
+                end += 1 + digits(&rest[end + 1..]);
+            }
+            let after = &rest[end..];
+            (end > sign && !after.starts_with(|c: char| c.is_alphanumeric() || c == '_'))
+                .then(|| (TextPart::Number(rest[..end].to_string()), after))
+        } else {
+            None
+        };
+        match part {
+            Some((part, after)) => {
+                if !literal.is_empty() {
+                    parts.push(TextPart::Literal(std::mem::take(&mut literal)));
+                }
+                parts.push(part);
+                rest = after;
+            }
+            None => {
+                literal.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    if !literal.is_empty() {
+        parts.push(TextPart::Literal(literal));
+    }
+    parts
+}
+
+/// Converts a text to the words of an ident in snake case, like `I have` to `i_have`.
+fn snake_case(text: &str) -> String {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+fn pascal_case(text: &str) -> String {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Makes an ident from the name, adding the prefix if the name is empty or not an ident.
+fn make_ident(name: &str, prefix: &str) -> syn::Ident {
+    let name = if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("{prefix}{name}")
+    } else {
+        name.to_string()
+    };
+    syn::parse_str(&name).unwrap_or_else(|_| format_ident!("{}_", name))
+}
+
+/// Returns the name with a number suffix if the name is already used.
+fn unique_name(name: String, used: &mut BTreeSet<String>) -> String {
+    let name = (1..)
+        .map(|n| match n {
+            1 => name.clone(),
+            n => format!("{name}_{n}"),
+        })
+        .find(|name| !used.contains(name))
+        .expect("there is an unused name");
+    used.insert(name.clone());
+    name
+}
+
+/// The type and the value of a cell, which is an integer, a float, or a string.
+fn infer_type<'a>(values: impl IntoIterator<Item = &'a str>) -> TokenStream {
+    let values: Vec<_> = values.into_iter().collect();
+    if values.iter().all(|value| is_plain_number::<i64>(value)) {
+        quote!(i64)
+    } else if values.iter().all(|value| is_plain_number::<f64>(value)) {
+        quote!(f64)
+    } else {
+        quote!(&str)
+    }
+}
+
+/// Whether the text is a decimal number that is displayed as the same text, like `42` or `-1.5`
+/// but not `01234`, `1.50`, `inf`, `NaN`, or `1e400`, which are kept as they are in strings.
+fn is_plain_number<T: std::str::FromStr + std::fmt::Display>(text: &str) -> bool {
+    text.chars()
+        .all(|c| c.is_ascii_digit() || c == '-' || c == '.')
+        && text
+            .parse::<T>()
+            .is_ok_and(|value| value.to_string() == text)
+}
+
+fn value_tokens(value: &str, ty: &TokenStream) -> TokenStream {
+    match ty.to_string().as_str() {
+        "i64" => value.parse::<i64>().map(|value| quote!(#value)).unwrap(),
+        "f64" => {
+            let value = value.parse::<f64>().unwrap();
+            quote!(#value)
+        }
+        _ => quote!(#value),
+    }
+}
+
+fn escape_braces(text: &str) -> String {
+    text.replace('{', "{{").replace('}', "}}")
+}
+
+pub(crate) fn generate(feature: &Feature) -> Result<TokenStream, FeatureError> {
+    let background = feature
+        .background
+        .as_ref()
+        .map(|background| {
+            let title = if feature.title.is_empty() {
+                "Background".to_string()
+            } else {
+                format!("{} background", feature.title)
+            };
+            let ident = format_ident!("{}Background", pascal_case(&feature.title));
+            let steps = generate_steps(background, &[])?;
+            Ok((
+                ident.clone(),
+                quote! {
+                    #[narrative::background(#title)]
+                    trait #ident {
+                        #(#steps)*
+                    }
+                },
+            ))
+        })
+        .transpose()?;
+    let background_attr = background
+        .as_ref()
+        .map(|(ident, _)| quote!(, background = #ident));
+    let mut story_names = BTreeSet::new();
+    let stories = feature
+        .scenarios
+        .iter()
+        .map(|scenario| {
+            let name = unique_name(pascal_case(&scenario.title), &mut story_names);
+            let ident = make_ident(&name, "Scenario");
+            let title = &scenario.title;
+            if !scenario.is_outline {
+                let steps = generate_steps(scenario, &[])?;
+                return Ok(quote! {
+                    #[narrative::story(#title #background_attr)]
+                    trait #ident {
+                        #(#steps)*
+                    }
+                });
+            }
+            let rows: Vec<_> = scenario
+                .examples
+                .iter()
+                .flat_map(|examples| examples.table.iter().skip(1))
+                .collect();
+            let Some(header) = scenario
+                .examples
+                .iter()
+                .find_map(|examples| examples.table.first())
+                .filter(|_| !rows.is_empty())
+            else {
+                return Err(FeatureError::new(
+                    scenario.line,
+                    1,
+                    "a scenario outline needs `Examples:` with a header and rows",
+                ));
+            };
+            for examples in &scenario.examples {
+                if examples.table.first().is_some_and(|other| other != header) {
+                    return Err(FeatureError::new(
+                        examples.line,
+                        1,
+                        "all examples of a scenario outline must have the same header",
+                    ));
+                }
+            }
+            // The columns are the consts of the story, whose values are bound by the examples.
+            let mut const_names = BTreeSet::new();
+            let consts: Vec<_> = header
+                .iter()
+                .enumerate()
+                .map(|(index, column)| {
+                    let name = unique_name(
+                        make_ident(&snake_case(column).to_uppercase(), "COLUMN_").to_string(),
+                        &mut const_names,
+                    );
+                    let ident = format_ident!("{}", name);
+                    let ty = infer_type(rows.iter().map(|row| row[index].as_str()));
+                    (ident, ty)
+                })
+                .collect();
+            let const_defs = consts.iter().zip(rows[0]).map(|((ident, ty), value)| {
+                let value = value_tokens(value, ty);
+                quote!(const #ident: #ty = #value;)
+            });
+            let steps = generate_steps(scenario, &consts_with_columns(header, &consts))?;
+            let examples_ident = format_ident!("{}Examples", ident);
+            let examples_title = format!("{title} examples");
+            let example_steps = rows.iter().enumerate().map(|(index, row)| {
+                let step_ident = format_ident!("example_{}", index + 1);
+                let bindings = consts.iter().zip(row.iter()).map(|((ident, ty), value)| {
+                    let value = value_tokens(value, ty);
+                    quote!(#ident = #value)
+                });
+                let text = escape_braces(
+                    &header
+                        .iter()
+                        .zip(row.iter())
+                        .map(|(column, value)| format!("{column} = {value}"))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                );
+                quote! {
+                    #[step(story: #ident { #(#bindings),* }, #text)]
+                    fn #step_ident();
+                }
+            });
+            Ok(quote! {
+                #[narrative::story(#title #background_attr)]
+                trait #ident {
+                    #(#const_defs)*
+                    #(#steps)*
+                }
+
+                #[narrative::story(#examples_title)]
+                trait #examples_ident {
+                    #(#example_steps)*
+                }
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let background = background.map(|(_, tokens)| tokens);
+    Ok(quote! {
+        #background
+        #(#stories)*
+    })
+}
+
+/// The columns with the consts and the types of their values.
+struct Column<'a> {
+    name: &'a str,
+    ident: &'a syn::Ident,
+    ty: &'a TokenStream,
+}
+
+fn consts_with_columns<'a>(
+    header: &'a [String],
+    consts: &'a [(syn::Ident, TokenStream)],
+) -> Vec<Column<'a>> {
+    header
+        .iter()
+        .zip(consts)
+        .map(|(name, (ident, ty))| Column { name, ident, ty })
+        .collect()
+}
+
+fn generate_steps(
+    scenario: &Scenario,
+    columns: &[Column],
+) -> Result<Vec<TokenStream>, FeatureError> {
+    let column_names: Vec<_> = columns
+        .iter()
+        .map(|column| column.name.to_string())
+        .collect();
+    let mut step_names = BTreeSet::new();
+    scenario
+        .steps
+        .iter()
+        .map(|step| {
+            let parts = split_text(&step.text, &column_names);
+            let mut arg_names = BTreeSet::new();
+            let mut text = String::new();
+            let mut words = Vec::new();
+            let mut args = Vec::new();
+            for (index, part) in parts.iter().enumerate() {
+                let (name, ty, value) = match part {
+                    TextPart::Literal(literal) => {
+                        text.push_str(&escape_braces(literal));
+                        words.push(literal.as_str());
+                        continue;
+                    }
+                    TextPart::Number(number) => {
+                        // A number is named after the word after it, like `apples` in `5 apples`.
+                        let name = match parts.get(index + 1) {
+                            Some(TextPart::Literal(next)) => next
+                                .split(|c: char| !c.is_alphanumeric())
+                                .find(|word| !word.is_empty())
+                                .map(str::to_lowercase),
+                            _ => None,
+                        };
+                        let ty = infer_type([number.as_str()]);
+                        let value = value_tokens(number, &ty);
+                        (name.unwrap_or_else(|| "number".to_string()), ty, value)
+                    }
+                    TextPart::Quoted(content) => {
+                        ("text".to_string(), quote!(&str), quote!(#content))
+                    }
+                    TextPart::Placeholder { column, .. } => {
+                        let column = columns
+                            .iter()
+                            .find(|candidate| candidate.name == column)
+                            .expect("placeholders are the columns");
+                        let ident = column.ident;
+                        (snake_case(column.name), column.ty.clone(), quote!(#ident))
+                    }
+                };
+                let name = unique_name(make_ident(&name, "arg_").to_string(), &mut arg_names);
+                let quoted = matches!(
+                    part,
+                    TextPart::Quoted(_) | TextPart::Placeholder { quoted: true, .. }
+                );
+                if quoted {
+                    text.push_str(&format!("\"{{{name}}}\""));
+                } else {
+                    text.push_str(&format!("{{{name}}}"));
+                }
+                args.push((format_ident!("{}", name), ty, value));
+            }
+            if let Some(doc_string) = &step.doc_string {
+                let name = unique_name("doc_string".to_string(), &mut arg_names);
+                args.push((format_ident!("{}", name), quote!(&str), quote!(#doc_string)));
+            }
+            if !step.table.is_empty() {
+                let name = unique_name("table".to_string(), &mut arg_names);
+                let rows = step.table.iter().map(|row| quote!(&[#(#row),*]));
+                args.push((
+                    format_ident!("{}", name),
+                    quote!(&[&[&str]]),
+                    quote!(&[#(#rows),*]),
+                ));
+            }
+            let step_name = unique_name(
+                make_ident(&snake_case(&words.concat()), "step_").to_string(),
+                &mut step_names,
+            );
+            let step_ident = format_ident!("{}", step_name);
+            let attr_args = args.iter().map(|(name, _, value)| quote!(, #name = #value));
+            let params = args.iter().map(|(name, ty, _)| quote!(#name: #ty));
+            Ok(quote! {
+                #[step(#text #(#attr_args)*)]
+                fn #step_ident(#(#params),*);
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const SHOPPING: &str = r#"
+@shop
+Feature: Shopping
+  People buy fruits.
+
+  Background:
+    Given I open the shop
+
+  Scenario: Buy apples
+    Given I have 5 apples
+    When I buy 3 apples for 1.5 dollars
+    Then I see "Thank you"
+    And the receipt is:
+      """
+      apples x3
+        total: 4.5
+      """
+    And the cart has:
+      | name  | count |
+      | apple | 3     |
+
+  Scenario Outline: Log in
+    Given I log in as <user>
+    Then I see "<message>"
+
+    Examples: Admins
+      | user  | message |
+      | admin | Welcome |
+    Examples:
+      | user  | message |
+      | guest | Hello   |
+"#;
+
+    #[test]
+    fn test_parse_feature() {
+        let feature = parse_feature(SHOPPING).unwrap();
+        assert_eq!(feature.title, "Shopping");
+        let background = feature.background.unwrap();
+        assert_eq!(background.steps[0].text, "I open the shop");
+        assert_eq!(feature.scenarios.len(), 2);
+        let steps = &feature.scenarios[0].steps;
+        assert_eq!(steps[1].text, "I buy 3 apples for 1.5 dollars");
+        assert_eq!((steps[1].line, steps[1].column), (11, 10));
+        assert_eq!(
+            steps[3].doc_string.as_deref(),
+            Some("apples x3\n  total: 4.5")
+        );
+        assert_eq!(
+            steps[4].table,
+            vec![vec!["name", "count"], vec!["apple", "3"]]
+        );
+        let outline = &feature.scenarios[1];
+        assert!(outline.is_outline);
+        assert_eq!(outline.examples.len(), 2);
+        assert_eq!(outline.examples[0].title, "Admins");
+        assert_eq!(
+            outline.examples[1].table,
+            vec![vec!["user", "message"], vec!["guest", "Hello"]]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_feature("Scenario: foo").unwrap_err(),
+            FeatureError::new(1, 1, "expected `Feature:` at the start of the feature file")
+        );
+        assert_eq!(
+            parse_feature("Feature: foo\n  Scenario: bar\n    Given baz\n    oops").unwrap_err(),
+            FeatureError::new(
+                4,
+                5,
+                "expected a step like `Given ...`, `When ...`, or `Then ...`, found `oops`"
+            )
+        );
+        assert_eq!(
+            parse_feature("Feature: foo\n  Scenario: bar\n    Given baz\n    | a |\n    | b | c |")
+                .unwrap_err(),
+            FeatureError::new(5, 5, "the row has 2 cells, but the first row has 1")
+        );
+        assert_eq!(
+            parse_feature("Feature: foo\n  Scenario: bar\n    Given baz\n    \"\"\"\n    text")
+                .unwrap_err(),
+            FeatureError::new(4, 5, "unclosed doc string, close it with `\"\"\"`")
+        );
+        assert_eq!(
+            parse_feature("Feature: foo\n  Scenario: bar\n    Given baz\n  Examples:").unwrap_err(),
+            FeatureError::new(
+                4,
+                3,
+                "`Examples:` must follow the steps of a `Scenario Outline:`"
+            )
+        );
+        assert_eq!(
+            parse_feature("Feature: foo").unwrap_err(),
+            FeatureError::new(1, 1, "the feature has no scenarios")
+        );
+    }
+
+    #[test]
+    fn test_parse_doc_string_with_wide_indent() {
+        let feature = parse_feature(
+            "Feature: foo\n  Scenario: bar\n    Given baz\n\u{3000}\u{3000}\u{3000}\u{3000}\"\"\"\n\u{3000}\u{3000}\u{3000}\u{3000}text\n\u{3000}\u{3000}\u{3000}\u{3000}\u{3000}indented\n\u{3000}\u{3000}\u{3000}\u{3000}\"\"\"",
+        )
+        .unwrap();
+        assert_eq!(
+            feature.scenarios[0].steps[0].doc_string.as_deref(),
+            Some("text\n\u{3000}indented")
+        );
+    }
+
+    #[test]
+    fn test_split_text() {
+        let columns = vec!["user".to_string()];
+        assert_eq!(
+            split_text(
+                r#"<user> buys 3 apples for -1.5 in "Fruit Shop" on day3 <other>"#,
+                &columns
+            ),
+            vec![
+                TextPart::Placeholder {
+                    column: "user".to_string(),
+                    quoted: false
+                },
+                TextPart::Literal(" buys ".to_string()),
+                TextPart::Number("3".to_string()),
+                TextPart::Literal(" apples for ".to_string()),
+                TextPart::Number("-1.5".to_string()),
+                TextPart::Literal(" in ".to_string()),
+                TextPart::Quoted("Fruit Shop".to_string()),
+                TextPart::Literal(" on day3 <other>".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_generate_scenario() {
+        let feature = parse_feature(
+            "Feature: Shopping\n  Scenario: Buy apples\n    Given I have 5 apples\n    And I have 2 apples\n    Then I see \"{total}\"\n      | a |",
+        )
+        .unwrap();
+        assert_eq!(
+            generate(&feature).unwrap().to_string(),
+            quote! {
+                #[narrative::story("Buy apples")]
+                trait BuyApples {
+                    #[step("I have {apples} apples", apples = 5i64)]
+                    fn i_have_apples(apples: i64);
+                    #[step("I have {apples} apples", apples = 2i64)]
+                    fn i_have_apples_2(apples: i64);
+                    #[step("I see \"{text}\"", text = "{total}", table = &[&["a"]])]
+                    fn i_see(text: &str, table: &[&[&str]]);
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_outline() {
+        let feature = parse_feature(
+            "Feature: Accounts\n  Background:\n    Given I open the app\n  Scenario Outline: Log in\n    Given I log in as <user> <count> times\n    Examples:\n      | user | count |\n      | admin | 1 |\n      | guest | 2 |",
+        )
+        .unwrap();
+        assert_eq!(
+            generate(&feature).unwrap().to_string(),
+            quote! {
+                #[narrative::background("Accounts background")]
+                trait AccountsBackground {
+                    #[step("I open the app")]
+                    fn i_open_the_app();
+                }
+                #[narrative::story("Log in", background = AccountsBackground)]
+                trait LogIn {
+                    const USER: &str = "admin";
+                    const COUNT: i64 = 1i64;
+                    #[step("I log in as {user} {count} times", user = USER, count = COUNT)]
+                    fn i_log_in_as_times(user: &str, count: i64);
+                }
+                #[narrative::story("Log in examples")]
+                trait LogInExamples {
+                    #[step(story: LogIn { USER = "admin", COUNT = 1i64 }, "user = admin, count = 1")]
+                    fn example_1();
+                    #[step(story: LogIn { USER = "guest", COUNT = 2i64 }, "user = guest, count = 2")]
+                    fn example_2();
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_generate_outline_with_similar_columns() {
+        let feature = parse_feature(
+            "Feature: Accounts\n  Scenario Outline: Log in\n    Given I log in as <User> <user> <a b> <a_b>\n    Examples:\n      | User | user | a b | a_b |\n      | admin | guest | x | y |",
+        )
+        .unwrap();
+        assert_eq!(
+            generate(&feature).unwrap().to_string(),
+            quote! {
+                #[narrative::story("Log in")]
+                trait LogIn {
+                    const USER: &str = "admin";
+                    const USER_2: &str = "guest";
+                    const A_B: &str = "x";
+                    const A_B_2: &str = "y";
+                    #[step("I log in as {user} {user_2} {a_b} {a_b_2}", user = USER, user_2 = USER_2, a_b = A_B, a_b_2 = A_B_2)]
+                    fn i_log_in_as(user: &str, user_2: &str, a_b: &str, a_b_2: &str);
+                }
+                #[narrative::story("Log in examples")]
+                trait LogInExamples {
+                    #[step(story: LogIn { USER = "admin", USER_2 = "guest", A_B = "x", A_B_2 = "y" }, "User = admin, user = guest, a b = x, a_b = y")]
+                    fn example_1();
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_infer_type() {
+        assert_eq!(infer_type(["1", "-20"]).to_string(), "i64");
+        assert_eq!(infer_type(["1", "-2.5"]).to_string(), "f64");
+        for cell in [
+            "NaN", "inf", "Infinity", "1e400", "01234", "1.50", "+5", "-0.0",
+        ] {
+            assert_eq!(infer_type([cell]).to_string(), "& str", "{cell}");
+        }
+    }
+
+    #[test]
+    fn test_generate_number_like_texts() {
+        let feature = parse_feature(
+            "Feature: Codes\n  Scenario Outline: Enter codes\n    Given I enter <code> and 01234\n    Examples:\n      | code |\n      | NaN |\n      | 1e400 |",
+        )
+        .unwrap();
+        assert_eq!(
+            generate(&feature).unwrap().to_string(),
+            quote! {
+                #[narrative::story("Enter codes")]
+                trait EnterCodes {
+                    const CODE: &str = "NaN";
+                    #[step("I enter {code} and {number}", code = CODE, number = "01234")]
+                    fn i_enter_and(code: &str, number: &str);
+                }
+                #[narrative::story("Enter codes examples")]
+                trait EnterCodesExamples {
+                    #[step(story: EnterCodes { CODE = "NaN" }, "code = NaN")]
+                    fn example_1();
+                    #[step(story: EnterCodes { CODE = "1e400" }, "code = 1e400")]
+                    fn example_2();
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_error_location() {
+        let path: syn::LitStr = syn::parse_quote!("features/shop.feature");
+        let err = FeatureError::new(3, 5, "a step must be in a scenario or a background");
+        assert_eq!(
+            err.to_syn_error(&path).to_string(),
+            "features/shop.feature:3:5: a step must be in a scenario or a background"
+        );
+
+        let full_path = std::env::temp_dir().join("narrative-include-feature-error.feature");
+        std::fs::write(&full_path, "Feature: Shop\n  Given I shop\n").unwrap();
+        let full_path = full_path.to_string_lossy();
+        let err = expand(&syn::LitStr::new(
+            &full_path,
+            proc_macro2::Span::call_site(),
+        ))
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("{full_path}:2:3: a step must be in a scenario or a background")
+        );
+    }
+}
@@ -1,12 +1,19 @@
 pub mod story_const;
+pub mod story_generics;
 pub mod story_item;
 pub mod story_step;
 
+use quote::{ToTokens as _, format_ident};
 use syn::{
     Token, braced,
     parse::{Parse, ParseStream},
 };
 
+use crate::{
+    combine_errors, did_you_mean, map_last_segment,
+    template::{TemplateSegment, parse_template},
+};
+
 pub use story_item::StoryItem;
 pub use story_step::StoryStep;
 
@@ -17,6 +24,10 @@ pub struct ItemStory {
     #[allow(dead_code)]
     pub trait_token: Token![trait],
     pub ident: syn::Ident,
+    /// The generic params and the where clause of the story, like `trait UserStory<T: Backend>`.
+    pub generics: syn::Generics,
+    /// The story extended by this story, like `trait Checkout: Login`.
+    pub parent: Option<StoryParent>,
     #[allow(dead_code)]
     pub brace_token: syn::token::Brace,
     pub items: Vec<StoryItem>,
@@ -27,19 +38,82 @@ impl Parse for ItemStory {
         let attrs = input.call(syn::Attribute::parse_outer)?;
         let story_token = input.parse::<Token![trait]>()?;
         let ident = input.parse()?;
+        let mut generics: syn::Generics = input.parse()?;
+        let parent = if input.peek(Token![:]) {
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        generics.where_clause = input.parse()?;
         let content;
         let brace_token = braced!(content in input);
+        // Each item is parsed as a trait item first, so all malformed items are reported at once.
         let mut items = Vec::new();
+        let mut errors = Vec::new();
         while !content.is_empty() {
-            items.push(content.parse()?);
+            match StoryItem::new(content.parse()?) {
+                Ok(item) => items.push(item),
+                Err(error) => errors.push(error),
+            }
         }
-        Ok(Self {
+        combine_errors(errors)?;
+        let story = Self {
             attrs,
             trait_token: story_token,
             ident,
+            generics,
+            parent,
             brace_token,
             items,
-        })
+        };
+        combine_errors(
+            [
+                story.validate_generics(),
+                story.validate_repeats(),
+                story.validate_placeholders(),
+                story.validate_arg_values(),
+            ]
+            .into_iter()
+            .filter_map(Result::err),
+        )?;
+        Ok(story)
+    }
+}
+
+pub struct StoryParent {
+    #[allow(dead_code)]
+    pub colon_token: Token![:],
+    pub path: syn::Path,
+}
+
+impl Parse for StoryParent {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let colon_token = input.parse()?;
+        let path = input.parse()?;
+        if input.peek(Token![+]) {
+            return Err(input.error("a story can extend only one story"));
+        }
+        Ok(Self { colon_token, path })
+    }
+}
+
+impl StoryParent {
+    pub fn async_path(&self) -> syn::Path {
+        map_last_segment(&self.path, |ident| format_ident!("Async{}", ident))
+    }
+
+    pub fn context_path(&self) -> syn::Path {
+        crate::context_path(&self.path)
+    }
+
+    /// The variant of the `Step` enum that wraps the steps of the parent story.
+    pub fn variant_ident(&self) -> &syn::Ident {
+        &self
+            .path
+            .segments
+            .last()
+            .expect("a path has at least one segment")
+            .ident
     }
 }
 
@@ -50,21 +124,198 @@ impl ItemStory {
             _ => None,
         })
     }
+    /// The consts except story values, which are Rust consts in the generated code too.
+    pub(crate) fn rust_consts(&self) -> impl Iterator<Item = &StoryConst> {
+        self.consts().filter(|item| !item.is_value())
+    }
     pub(crate) fn steps(&self) -> impl Iterator<Item = &StoryStep> {
         self.items.iter().filter_map(|item| match item {
             StoryItem::Step(step) => Some(step),
             _ => None,
         })
     }
-    pub(crate) fn find_assignments<'a>(&'a self, ident: &'a syn::Ident) -> Option<&'a syn::Expr> {
-        self.consts().find_map(|StoryConst { raw, default }| {
-            if raw.ident == *ident {
-                Some(&default.1)
-            } else {
-                None
-            }
+    /// Makes the background the parent story, so its steps come first like an extended story.
+    pub(crate) fn with_background(mut self, background: syn::Path) -> syn::Result<Self> {
+        if let Some(parent) = &self.parent {
+            return Err(syn::Error::new_spanned(
+                &parent.path,
+                "a story cannot both extend another story and have a background",
+            ));
+        }
+        self.parent = Some(StoryParent {
+            colon_token: Default::default(),
+            path: background,
+        });
+        self.validate_generics()?;
+        Ok(self)
+    }
+
+    /// Returns true if a step is repeated or conditional, so the steps are not a fixed list.
+    pub(crate) fn has_dynamic_steps(&self) -> bool {
+        self.steps()
+            .any(|step| step.repeat().is_some() || step.when().is_some())
+    }
+
+    /// The arg of a repeated step that takes each item, which is the arg without attr arg nor
+    /// const of the same name.
+    pub(crate) fn repeat_item<'a>(&'a self, step: &'a StoryStep) -> Option<&'a syn::Ident> {
+        step.repeat()?;
+        step.fn_args().map(|(ident, _)| ident).find(|ident| {
+            step.find_attr_arg(ident).is_none() && self.find_assignments(ident).is_none()
         })
     }
+
+    fn validate_repeats(&self) -> syn::Result<()> {
+        combine_errors(self.steps().filter_map(|step| {
+            let repeat = step.repeat()?;
+            self.repeat_item(step).is_none().then(|| {
+                syn::Error::new_spanned(
+                    repeat,
+                    format!(
+                        "a repeated step needs an arg without attr arg to take each item of `{}`",
+                        crate::pretty_print_expr(repeat),
+                    ),
+                )
+            })
+        }))
+    }
+
+    /// Validates that each arg of steps has a value from an attr arg, a const of the same name, or
+    /// the items of the repeated step.
+    fn validate_arg_values(&self) -> syn::Result<()> {
+        combine_errors(self.steps().flat_map(|step| {
+            let repeat_item = self.repeat_item(step);
+            step.fn_args()
+                .map(|(ident, _)| ident)
+                .filter(move |ident| {
+                    step.find_attr_arg(ident).is_none()
+                        && self.find_const(ident).is_none()
+                        && Some(*ident) != repeat_item
+                })
+                .map(|ident| {
+                    syn::Error::new_spanned(
+                        ident,
+                        format!("no value for the arg `{ident}`; add `{ident} = ...` to #[step]"),
+                    )
+                })
+        }))
+    }
+
+    /// Validates that the placeholders in the texts of steps refer to the attr args, the consts,
+    /// or the item of a repeated step.
+    fn validate_placeholders(&self) -> syn::Result<()> {
+        let errors = self.steps().flat_map(|step| {
+            let mut known: Vec<_> = step
+                .attr_args()
+                .map(|(ident, _)| ident.to_string())
+                .chain(self.consts().map(|item| item.raw.ident.to_string()))
+                .collect();
+            if step.repeat().is_some()
+                && let Some(item) = self.repeat_item(step)
+            {
+                known.push(item.to_string());
+            }
+            // The args without values are reported by `validate_arg_values`.
+            known.extend(step.fn_args().map(|(ident, _)| ident.to_string()));
+            step.texts()
+                .filter_map(|text| {
+                    let segments = match parse_template(&text.value()) {
+                        Ok(segments) => segments,
+                        Err(message) => return Some(vec![syn::Error::new(text.span(), message)]),
+                    };
+                    let plural_errors = segments.iter().filter_map(|segment| match segment {
+                        TemplateSegment::Plural { arg, .. } => {
+                            let ty = self.placeholder_type(step, arg)?;
+                            (!is_number(ty)).then(|| {
+                                syn::Error::new(
+                                    text.span(),
+                                    format!(
+                                        "`{{{arg}:plural(..)}}` needs a number like `u32`, but `{arg}` is `{}`",
+                                        ty.to_token_stream().to_string().replace(' ', "")
+                                    ),
+                                )
+                            })
+                        }
+                        _ => None,
+                    });
+                    let errors: Vec<_> = segments
+                        .iter()
+                        .filter_map(|segment| match segment {
+                            TemplateSegment::Arg(arg) => Some(arg.root()),
+                            TemplateSegment::Plural { arg, .. } => Some(arg.as_str()),
+                            TemplateSegment::Literal(_) => None,
+                        })
+                        .filter(|root| !known.iter().any(|name| name == root))
+                        .map(|root| {
+                            let mut message =
+                                format!("`{{{root}}}` is not an argument or const of this story");
+                            if let Some(name) = did_you_mean(root, known.iter().map(String::as_str))
+                            {
+                                message.push_str(&format!("; did you mean `{name}`?"));
+                            }
+                            syn::Error::new(text.span(), message)
+                        })
+                        .chain(plural_errors)
+                        .collect();
+                    (!errors.is_empty()).then_some(errors)
+                })
+                .flatten()
+                .collect::<Vec<_>>()
+        });
+        combine_errors(errors)
+    }
+
+    /// The type of an arg or a const in the texts of the step.
+    fn placeholder_type<'a>(&'a self, step: &'a StoryStep, name: &str) -> Option<&'a syn::Type> {
+        step.fn_args()
+            .find(|(ident, _)| *ident == name)
+            .map(|(_, ty)| ty)
+            .or_else(|| {
+                self.consts()
+                    .find(|StoryConst { raw, .. }| raw.ident == name)
+                    .map(|StoryConst { raw, .. }| &raw.ty)
+            })
+    }
+
+    pub(crate) fn find_assignments<'a>(&'a self, ident: &'a syn::Ident) -> Option<&'a syn::Expr> {
+        self.find_const(ident)
+            .map(|StoryConst { default, .. }| &default.1)
+    }
+
+    pub(crate) fn find_const(&self, ident: &syn::Ident) -> Option<&StoryConst> {
+        self.consts()
+            .find(|StoryConst { raw, .. }| raw.ident == *ident)
+    }
+}
+
+/// Whether the type is a primitive number or a reference to one, which decides plural suffixes.
+fn is_number(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Reference(reference) => is_number(&reference.elem),
+        syn::Type::Paren(paren) => is_number(&paren.elem),
+        syn::Type::Path(path) => {
+            path.qself.is_none()
+                && path.path.get_ident().is_some_and(|ident| {
+                    matches!(
+                        ident.to_string().as_str(),
+                        "u8" | "u16"
+                            | "u32"
+                            | "u64"
+                            | "u128"
+                            | "usize"
+                            | "i8"
+                            | "i16"
+                            | "i32"
+                            | "i64"
+                            | "i128"
+                            | "isize"
+                            | "f32"
+                            | "f64"
+                    )
+                })
+        }
+        _ => false,
+    }
 }
 
 #[cfg(test)]
@@ -88,10 +339,14 @@ mod tests {
             attrs: _,
             trait_token: _,
             ident,
+            generics,
+            parent,
             brace_token: _,
             items,
         } = syn::parse2(input).expect("parse a story");
         assert_eq!(ident, "MyFirstStory");
+        assert!(generics.params.is_empty());
+        assert!(parent.is_none());
         assert_eq!(items.len(), 3);
         assert!(matches!(items[0], StoryItem::Const { .. }));
         assert!(matches!(items[1], StoryItem::Step(_)));
@@ -122,4 +377,82 @@ mod tests {
         assert!(matches!(items[0], StoryItem::Const { .. }));
         assert!(matches!(items[1], StoryItem::Step(_)));
     }
+
+    #[test]
+    fn parse_story_with_parent() {
+        let input = quote! {
+            trait Checkout: crate::Login {
+                #[step("pay")]
+                fn pay();
+            }
+        };
+        let story: ItemStory = syn::parse2(input).expect("parse a story with parent");
+        let parent = story.parent.expect("parent");
+        let path = &parent.path;
+        assert_eq!(quote!(#path).to_string(), quote!(crate::Login).to_string());
+        assert_eq!(parent.variant_ident(), "Login");
+        let context_path = parent.context_path();
+        assert_eq!(
+            quote!(#context_path).to_string(),
+            quote!(crate::LoginContext).to_string()
+        );
+    }
+
+    #[test]
+    fn parse_story_with_multiple_parents() {
+        let input = quote! {
+            trait Checkout: Login + Dashboard {
+                #[step("pay")]
+                fn pay();
+            }
+        };
+        let Err(err) = syn::parse2::<ItemStory>(input) else {
+            panic!("multiple parents must be rejected");
+        };
+        assert_eq!(err.to_string(), "a story can extend only one story");
+    }
+
+    #[test]
+    fn parse_malformed_items() {
+        let input = quote! {
+            trait Checkout {
+                type Item = String;
+                const PRICE: u32;
+
+                #[step("pay {amount} yen")]
+                fn pay(amount: u32);
+            }
+        };
+        let Err(err) = syn::parse2::<ItemStory>(input) else {
+            panic!("malformed items must be rejected");
+        };
+        let messages = err
+            .into_iter()
+            .map(|err| err.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                "a story cannot have a type; define `Item` outside the story with `#[narrative::local_type_for(...)]`",
+                "in a story, all consts must have a value; add it like `const PRICE: u32 = ...;`",
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_arg_without_value() {
+        let input = quote! {
+            trait Checkout {
+                #[step("pay {amount} yen")]
+                fn pay(amount: u32);
+            }
+        };
+        let Err(err) = syn::parse2::<ItemStory>(input) else {
+            panic!("an arg without value must be rejected");
+        };
+        assert_eq!(
+            err.to_string(),
+            "no value for the arg `amount`; add `amount = ...` to #[step]"
+        );
+    }
 }
@@ -4,9 +4,43 @@ use quote::quote;
 pub struct StoryConst {
     pub raw: syn::TraitItemConst,
     pub default: (syn::Token![=], syn::Expr),
+    /// The attr of a story value like `#[narrative::value] const USERS: Vec<User> = ...;`, which is
+    /// evaluated once per story run instead of being a Rust const.
+    pub value_attr: Option<syn::Attribute>,
+    /// The env var that overrides the value at run time, like `#[env("BASE_URL")]`.
+    pub env: Option<syn::LitStr>,
 }
 
 impl StoryConst {
+    /// Takes `#[narrative::value]` and `#[env("NAME")]` out of the attrs of the const.
+    pub fn new(
+        mut raw: syn::TraitItemConst,
+        default: (syn::Token![=], syn::Expr),
+    ) -> syn::Result<Self> {
+        let value_attr = raw
+            .attrs
+            .iter()
+            .position(is_value_attr)
+            .map(|position| raw.attrs.remove(position));
+        let env = raw
+            .attrs
+            .iter()
+            .position(|attr| attr.path().is_ident("env"))
+            .map(|position| raw.attrs.remove(position).parse_args())
+            .transpose()?;
+        Ok(Self {
+            raw,
+            default,
+            value_attr,
+            env,
+        })
+    }
+
+    /// Whether this is a story value, which is not a Rust const.
+    pub fn is_value(&self) -> bool {
+        self.value_attr.is_some()
+    }
+
     pub fn to_pub_const(&self) -> TokenStream {
         let syn::TraitItemConst {
             attrs,
@@ -25,3 +59,13 @@ impl StoryConst {
         }
     }
 }
+
+/// Whether the attr is `#[narrative::value]` or `#[value]`.
+fn is_value_attr(attr: &syn::Attribute) -> bool {
+    let mut segments = attr.path().segments.iter().map(|segment| &segment.ident);
+    match (segments.next(), segments.next(), segments.next()) {
+        (Some(value), None, None) => value == "value",
+        (Some(narrative), Some(value), None) => narrative == "narrative" && value == "value",
+        _ => false,
+    }
+}
@@ -0,0 +1,488 @@
+// The generated types of a story carry the type and const params of the story, but not the lifetime
+// params because steps and contexts must be `'static`. The generated impls require the story trait
+// for any lifetime instead.
+
+use proc_macro2::TokenStream;
+use quote::{ToTokens, format_ident, quote};
+use syn::visit::Visit;
+
+use super::{ItemStory, StoryItem};
+
+impl ItemStory {
+    /// The type and const params for impls of the generated types, like `T: Backend + 'static,`.
+    pub(crate) fn impl_params(&self) -> TokenStream {
+        self.generics
+            .params
+            .iter()
+            .filter_map(|param| match param {
+                syn::GenericParam::Type(param) => {
+                    let ident = &param.ident;
+                    let mut bounds = param.bounds.clone();
+                    // `'static` is added where the param is bounded, not to split the bounds.
+                    if !self.has_where_predicate(ident) {
+                        bounds.push(syn::parse_quote!('static));
+                    }
+                    Some(quote!(#ident: #bounds,))
+                }
+                syn::GenericParam::Const(param) => {
+                    let ident = &param.ident;
+                    let ty = &param.ty;
+                    Some(quote!(const #ident: #ty,))
+                }
+                syn::GenericParam::Lifetime(_) => None,
+            })
+            .collect()
+    }
+
+    /// The type and const params for definitions of the generated types, like `T, const N: usize,`.
+    pub(crate) fn type_params(&self) -> TokenStream {
+        self.generics
+            .params
+            .iter()
+            .filter_map(|param| match param {
+                syn::GenericParam::Type(param) => {
+                    let ident = &param.ident;
+                    let default = param.default.as_ref().map(|default| quote!(= #default));
+                    Some(quote!(#ident #default,))
+                }
+                syn::GenericParam::Const(param) => {
+                    let ident = &param.ident;
+                    let ty = &param.ty;
+                    let default = param.default.as_ref().map(|default| quote!(= #default));
+                    Some(quote!(const #ident: #ty #default,))
+                }
+                syn::GenericParam::Lifetime(_) => None,
+            })
+            .collect()
+    }
+
+    /// The type and const args of the generated types, like `T, N,`.
+    pub(crate) fn type_args(&self) -> TokenStream {
+        self.generics
+            .params
+            .iter()
+            .filter_map(|param| match param {
+                syn::GenericParam::Type(param) => Some(&param.ident),
+                syn::GenericParam::Const(param) => Some(&param.ident),
+                syn::GenericParam::Lifetime(_) => None,
+            })
+            .map(|ident| quote!(#ident,))
+            .collect()
+    }
+
+    /// The type and const args of the generated types in angle brackets if any, like `<T, N,>`.
+    pub(crate) fn type_generics(&self) -> Option<TokenStream> {
+        let args = self.type_args();
+        (!args.is_empty()).then(|| quote!(<#args>))
+    }
+
+    /// The type and const params for impls of the generated types in angle brackets if any.
+    pub(crate) fn impl_generics(&self) -> Option<TokenStream> {
+        let params = self.impl_params();
+        (!params.is_empty()).then(|| quote!(<#params>))
+    }
+
+    /// The marker of the type and const params and the given internal param, like
+    /// `PhantomData<fn() -> (T, [(); N], B)>`.
+    pub(crate) fn phantom_data(&self, internal: &syn::Ident) -> TokenStream {
+        let types = self
+            .generics
+            .params
+            .iter()
+            .filter_map(|param| match param {
+                syn::GenericParam::Type(param) => {
+                    let ident = &param.ident;
+                    Some(quote!(#ident))
+                }
+                syn::GenericParam::Const(param) => {
+                    let ident = &param.ident;
+                    Some(quote!([(); #ident]))
+                }
+                syn::GenericParam::Lifetime(_) => None,
+            })
+            .collect::<Vec<_>>();
+        if types.is_empty() {
+            quote!(std::marker::PhantomData<fn() -> #internal>)
+        } else {
+            quote!(std::marker::PhantomData<fn() -> (#(#types,)* #internal)>)
+        }
+    }
+
+    /// The where clause of the story for impls of the generated types, with `'static` bounds of
+    /// the type params bounded in it.
+    pub(crate) fn where_clause(&self) -> Option<syn::WhereClause> {
+        let mut where_clause = self.generics.where_clause.clone()?;
+        for param in self.generics.type_params() {
+            let predicate =
+                where_clause
+                    .predicates
+                    .iter_mut()
+                    .find_map(|predicate| match predicate {
+                        syn::WherePredicate::Type(predicate)
+                            if is_param(&predicate.bounded_ty, &param.ident) =>
+                        {
+                            Some(predicate)
+                        }
+                        _ => None,
+                    });
+            if let Some(predicate) = predicate {
+                predicate.bounds.push(syn::parse_quote!('static));
+            }
+        }
+        Some(where_clause)
+    }
+
+    fn has_where_predicate(&self, ident: &syn::Ident) -> bool {
+        self.generics.where_clause.as_ref().is_some_and(|where_clause| {
+            where_clause.predicates.iter().any(|predicate| {
+                matches!(predicate, syn::WherePredicate::Type(predicate) if is_param(&predicate.bounded_ty, ident))
+            })
+        })
+    }
+
+    /// The bound of the story trait for any lifetime, like `for<'a> UserStory<'a, T>`.
+    pub(crate) fn trait_bound(&self, trait_ident: &syn::Ident) -> TokenStream {
+        let lifetimes = self
+            .generics
+            .lifetimes()
+            .map(|param| &param.lifetime)
+            .collect::<Vec<_>>();
+        let (_, ty_generics, _) = self.generics.split_for_impl();
+        if lifetimes.is_empty() {
+            quote!(#trait_ident #ty_generics)
+        } else {
+            quote!(for<#(#lifetimes),*> #trait_ident #ty_generics)
+        }
+    }
+
+    /// Whether the story has lifetime params, which the generated impls take for any lifetime.
+    pub(crate) fn has_lifetimes(&self) -> bool {
+        self.generics.lifetimes().next().is_some()
+    }
+
+    /// The bound of the story trait for any lifetime with the error type, like
+    /// `for<'a> UserStory<'a, T, Error = E>`. Binding the error to a param avoids projections of
+    /// the bound for any lifetime, which can't be normalized in async fns.
+    pub(crate) fn trait_bound_with_error(
+        &self,
+        trait_ident: &syn::Ident,
+        error: &impl ToTokens,
+    ) -> TokenStream {
+        let lifetimes = self
+            .generics
+            .lifetimes()
+            .map(|param| &param.lifetime)
+            .collect::<Vec<_>>();
+        let args = self.generics.params.iter().map(|param| match param {
+            syn::GenericParam::Lifetime(param) => param.lifetime.to_token_stream(),
+            syn::GenericParam::Type(param) => param.ident.to_token_stream(),
+            syn::GenericParam::Const(param) => param.ident.to_token_stream(),
+        });
+        if lifetimes.is_empty() {
+            quote!(#trait_ident<#(#args,)* Error = #error>)
+        } else {
+            quote!(for<#(#lifetimes),*> #trait_ident<#(#args,)* Error = #error>)
+        }
+    }
+
+    /// Returns an ident for a generic param of the generated code, like `B` for the bindings,
+    /// renamed not to collide with the generic params of the story.
+    pub(crate) fn internal_param(&self, name: &str) -> syn::Ident {
+        let mut ident = format_ident!("{}", name);
+        while self
+            .generics
+            .type_params()
+            .any(|param| param.ident == ident)
+            || self
+                .generics
+                .const_params()
+                .any(|param| param.ident == ident)
+        {
+            ident = format_ident!("{}_", ident);
+        }
+        ident
+    }
+
+    pub(crate) fn validate_generics(&self) -> syn::Result<()> {
+        if self.generics.params.is_empty() {
+            return Ok(());
+        }
+        let mut errors = Vec::new();
+        // Bounds are copied to the impls that require the story for any lifetime.
+        for param in self.generics.lifetimes() {
+            if let Some(bound) = param.bounds.first() {
+                errors.push(syn::Error::new_spanned(
+                    bound,
+                    "lifetime params of a story cannot have bounds",
+                ));
+            }
+        }
+        let mut bounds = FindGenericParam::lifetimes_of(self);
+        for param in self.generics.type_params() {
+            bounds.visit_type_param(param);
+        }
+        if let Some(where_clause) = &self.generics.where_clause {
+            bounds.visit_where_clause(where_clause);
+        }
+        errors.extend(bounds.found.into_iter().map(|(span, name)| {
+            syn::Error::new(
+                span,
+                format!("the lifetime param `{name}` of a story cannot be used in bounds"),
+            )
+        }));
+
+        // Step args and consts are shared by all instantiations of the story, and their types are
+        // asserted to be local types of the story.
+        let mut shared = FindGenericParam::all_of(self);
+        for item in &self.items {
+            match item {
+                StoryItem::Const(item) => {
+                    shared.visit_type(&item.raw.ty);
+                    shared.visit_expr(&item.default.1);
+                }
+                StoryItem::Step(step) => {
+                    for (_, ty) in step.fn_args() {
+                        shared.visit_type(ty);
+                    }
+                    for (_, value) in step.attr_args() {
+                        shared.visit_expr(value);
+                    }
+                    if let Some(bindings) = step
+                        .sub_story_path()
+                        .and_then(|story_type| story_type.bindings.as_ref())
+                    {
+                        for binding in &bindings.bindings {
+                            shared.visit_expr(&binding.value);
+                        }
+                    }
+                }
+            }
+        }
+        errors.extend(shared.found.into_iter().map(|(span, name)| {
+            syn::Error::new(
+                span,
+                format!(
+                    "the generic param `{name}` of the story cannot be used in step args or consts, because they are shared by all instantiations of the story"
+                ),
+            )
+        }));
+
+        if let Some(parent) = &self.parent {
+            let mut parent_args = FindGenericParam::all_of(self);
+            parent_args.visit_path(&parent.path);
+            errors.extend(parent_args.found.into_iter().map(|(span, name)| {
+                syn::Error::new(
+                    span,
+                    format!(
+                        "extending a story with the generic param `{name}` is not supported, use a sub story step instead"
+                    ),
+                )
+            }));
+        }
+
+        errors
+            .into_iter()
+            .reduce(|mut combined, error| {
+                combined.combine(error);
+                combined
+            })
+            .map_or(Ok(()), Err)
+    }
+}
+
+fn is_param(ty: &syn::Type, ident: &syn::Ident) -> bool {
+    matches!(ty, syn::Type::Path(ty) if ty.qself.is_none() && ty.path.is_ident(ident))
+}
+
+struct FindGenericParam {
+    idents: Vec<syn::Ident>,
+    lifetimes: Vec<syn::Lifetime>,
+    found: Vec<(proc_macro2::Span, String)>,
+}
+
+impl FindGenericParam {
+    fn lifetimes_of(story: &ItemStory) -> Self {
+        Self {
+            idents: Vec::new(),
+            lifetimes: story
+                .generics
+                .lifetimes()
+                .map(|param| param.lifetime.clone())
+                .collect(),
+            found: Vec::new(),
+        }
+    }
+
+    fn all_of(story: &ItemStory) -> Self {
+        Self {
+            idents: story
+                .generics
+                .type_params()
+                .map(|param| param.ident.clone())
+                .chain(
+                    story
+                        .generics
+                        .const_params()
+                        .map(|param| param.ident.clone()),
+                )
+                .collect(),
+            ..Self::lifetimes_of(story)
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for FindGenericParam {
+    fn visit_ident(&mut self, ident: &'ast syn::Ident) {
+        if self.idents.contains(ident) {
+            self.found.push((ident.span(), ident.to_string()));
+        }
+    }
+
+    fn visit_lifetime(&mut self, lifetime: &'ast syn::Lifetime) {
+        if self.lifetimes.contains(lifetime) {
+            self.found.push((lifetime.span(), lifetime.to_string()));
+        }
+    }
+
+    // The param itself is not a usage.
+    fn visit_type_param(&mut self, param: &'ast syn::TypeParam) {
+        for bound in &param.bounds {
+            self.visit_type_param_bound(bound);
+        }
+        if let Some(default) = &param.default {
+            self.visit_type(default);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+
+    use super::*;
+
+    #[test]
+    fn generic_params() {
+        let story: ItemStory = syn::parse2(quote! {
+            trait UserStory<'a, T: Backend = Postgres, const N: usize> where T: Clone {
+                #[step("step")]
+                fn step();
+            }
+        })
+        .unwrap();
+        assert_eq!(
+            story.impl_params().to_string(),
+            quote!(T: Backend, const N: usize,).to_string()
+        );
+        assert_eq!(
+            story.type_params().to_string(),
+            quote!(T = Postgres, const N: usize,).to_string()
+        );
+        assert_eq!(story.type_args().to_string(), quote!(T, N,).to_string());
+        assert_eq!(
+            story.phantom_data(&story.internal_param("B")).to_string(),
+            quote!(std::marker::PhantomData<fn() -> (T, [(); N], B)>).to_string()
+        );
+        assert_eq!(
+            story.trait_bound(&story.ident).to_string(),
+            quote!(for<'a> UserStory<'a, T, N>).to_string()
+        );
+        assert_eq!(
+            story
+                .trait_bound_with_error(&story.ident, &format_ident!("E"))
+                .to_string(),
+            quote!(for<'a> UserStory<'a, T, N, Error = E>).to_string()
+        );
+        let where_clause = story.where_clause();
+        assert_eq!(
+            quote!(#where_clause).to_string(),
+            quote!(where T: Clone + 'static).to_string()
+        );
+    }
+
+    #[test]
+    fn no_generic_params() {
+        let story: ItemStory = syn::parse2(quote! {
+            trait UserStory {}
+        })
+        .unwrap();
+        assert!(story.impl_params().is_empty());
+        assert!(story.type_args().is_empty());
+        assert_eq!(
+            story.trait_bound(&story.ident).to_string(),
+            quote!(UserStory).to_string()
+        );
+    }
+
+    #[test]
+    fn internal_param() {
+        let story: ItemStory = syn::parse2(quote! {
+            trait UserStory<B: Backend, T> {}
+        })
+        .unwrap();
+        assert_eq!(story.internal_param("B"), "B_");
+        assert_eq!(story.internal_param("T"), "T_");
+        assert_eq!(story.internal_param("E"), "E");
+    }
+
+    #[test]
+    fn generic_param_in_step_arg() {
+        let Err(err) = syn::parse2::<ItemStory>(quote! {
+            trait UserStory<T, const N: usize> {
+                #[step("step", value = Default::default(), count = N)]
+                fn step(value: T, count: usize);
+            }
+        }) else {
+            panic!("generic params in step args must be rejected");
+        };
+        let messages = err
+            .into_iter()
+            .map(|err| err.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                "the generic param `T` of the story cannot be used in step args or consts, because they are shared by all instantiations of the story",
+                "the generic param `N` of the story cannot be used in step args or consts, because they are shared by all instantiations of the story",
+            ]
+        );
+    }
+
+    #[test]
+    fn generic_param_in_const() {
+        let Err(err) = syn::parse2::<ItemStory>(quote! {
+            trait UserStory<T: Default> {
+                const VALUE: T = T::default();
+            }
+        }) else {
+            panic!("generic params in consts must be rejected");
+        };
+        assert_eq!(err.into_iter().count(), 2);
+    }
+
+    #[test]
+    fn generic_param_in_parent() {
+        let Err(err) = syn::parse2::<ItemStory>(quote! {
+            trait Checkout<T>: Login<T> {}
+        }) else {
+            panic!("generic params in the parent must be rejected");
+        };
+        assert_eq!(
+            err.to_string(),
+            "extending a story with the generic param `T` is not supported, use a sub story step instead"
+        );
+    }
+
+    #[test]
+    fn lifetime_in_bounds() {
+        let Err(err) = syn::parse2::<ItemStory>(quote! {
+            trait UserStory<'a, T: Backend<'a>> {}
+        }) else {
+            panic!("lifetimes in bounds must be rejected");
+        };
+        assert_eq!(
+            err.to_string(),
+            "the lifetime param `'a` of a story cannot be used in bounds"
+        );
+    }
+}
@@ -10,19 +10,41 @@ pub enum StoryItem {
 
 impl Parse for StoryItem {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        if let Ok(step) = input.parse().map(Self::Step) {
-            Ok(step)
-        } else if let Ok(const_) = input.parse::<syn::TraitItemConst>() {
-            let Some(default) = const_.default.clone() else {
-                return Err(input.error("in a story, all consts must have a value"));
-            };
-            Ok(Self::Const(StoryConst {
-                raw: const_,
-                default,
-            }))
-        } else {
-            // I want to return more helpful error by looking ahead some tokens.
-            Err(input.error("expected a step or const"))
+        Self::new(input.parse()?)
+    }
+}
+
+impl StoryItem {
+    /// Makes the item of a story from a trait item, which is parsed first so a malformed item is
+    /// reported without stopping at it.
+    pub fn new(item: syn::TraitItem) -> syn::Result<Self> {
+        match item {
+            syn::TraitItem::Const(const_) => {
+                let Some(default) = const_.default.clone() else {
+                    return Err(syn::Error::new_spanned(
+                        &const_,
+                        format!(
+                            "in a story, all consts must have a value; add it like `const {}: {} = ...;`",
+                            const_.ident,
+                            crate::pretty_print_type(&const_.ty),
+                        ),
+                    ));
+                };
+                StoryConst::new(const_, default).map(Self::Const)
+            }
+            syn::TraitItem::Fn(fn_) => StoryStep::new(fn_).map(Self::Step),
+            syn::TraitItem::Type(type_) => Err(syn::Error::new_spanned(
+                &type_.ident,
+                format!(
+                    "a story cannot have a type; define `{}` outside the story with `#[narrative::local_type_for(...)]`",
+                    type_.ident,
+                ),
+            )),
+            syn::TraitItem::Macro(macro_) => Err(syn::Error::new_spanned(
+                &macro_.mac.path,
+                "a story cannot have a macro invocation; write the steps and consts in the story",
+            )),
+            item => Err(syn::Error::new_spanned(item, "expected a step or const")),
         }
     }
 }
@@ -45,6 +67,39 @@ mod tests {
         assert_eq!(step.inner.sig.ident, "as_a_user");
     }
 
+    #[test]
+    fn parse_value() {
+        let input = quote! {
+            /// The users.
+            #[narrative::value]
+            const USERS: Vec<User> = vec![User::new()];
+        };
+        let StoryItem::Const(const_) = syn::parse2(input).unwrap() else {
+            panic!("Expected a const");
+        };
+        assert!(const_.is_value());
+        assert_eq!(const_.raw.attrs.len(), 1);
+    }
+
+    #[test]
+    fn parse_env() {
+        let input = quote! {
+            #[env("BASE_URL")]
+            const BASE_URL: &str = "http://localhost:8080";
+        };
+        let StoryItem::Const(const_) = syn::parse2(input).unwrap() else {
+            panic!("Expected a const");
+        };
+        assert_eq!(const_.env.unwrap().value(), "BASE_URL");
+        assert!(const_.raw.attrs.is_empty());
+        assert!(
+            syn::parse2::<StoryItem>(quote! {
+                #[env(BASE_URL)]
+                const BASE_URL: &str = "http://localhost:8080";
+            })
+            .is_err()
+        );
+    }
 
     #[test]
     fn parse_const() {
@@ -1,10 +1,12 @@
+use std::collections::BTreeSet;
+
 use proc_macro2::TokenStream;
 use quote::ToTokens;
 use syn::parse::{Parse, ParseStream};
 
 use crate::{
     collect_format_args,
-    step_attr_syntax::{StepAttr, StoryType},
+    step_attr_syntax::{StepAttr, StepAttrArgs, StoryType},
 };
 
 pub struct StoryStep {
@@ -15,21 +17,7 @@ pub struct StoryStep {
 
 impl Parse for StoryStep {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        let mut attrs = input.call(syn::Attribute::parse_outer)?;
-        let Some(position) = attrs.iter().position(|attr| attr.path().is_ident("step")) else {
-            return Err(syn::Error::new(
-                input.span(),
-                "expected #[step(...)] attribute",
-            ));
-        };
-        let step_attr = attrs.remove(position);
-        let step_attr = syn::parse2::<StepAttr>(quote::quote! { #step_attr })?;
-        let inner = input.parse()?;
-        Ok(Self {
-            step_attr,
-            other_attrs: attrs,
-            inner,
-        })
+        Self::new(input.parse()?)
     }
 }
 
@@ -41,6 +29,103 @@ impl ToTokens for StoryStep {
 }
 
 impl StoryStep {
+    pub fn new(mut inner: syn::TraitItemFn) -> syn::Result<Self> {
+        let Some(position) = inner
+            .attrs
+            .iter()
+            .position(|attr| attr.path().is_ident("step"))
+        else {
+            return Err(syn::Error::new_spanned(
+                &inner.sig.ident,
+                "a fn in a story must be a step; add the text of the step like `#[step(\"...\")]`",
+            ));
+        };
+        let step_attr = inner.attrs.remove(position);
+        let step_attr = syn::parse2::<StepAttr>(quote::quote! { #step_attr })?;
+        let step = Self {
+            step_attr,
+            other_attrs: std::mem::take(&mut inner.attrs),
+            inner,
+        };
+        step.validate_signature()?;
+        Ok(step)
+    }
+
+    /// Validates that the signature only declares the args of the step, which are named in the
+    /// step attribute.
+    fn validate_signature(&self) -> syn::Result<()> {
+        let sig = &self.inner.sig;
+        let mut errors = Vec::new();
+        if let Some(body) = &self.inner.default {
+            errors.push(syn::Error::new_spanned(
+                body,
+                "a step cannot have a body, since it is implemented by the environment; replace the body with `;`",
+            ));
+        }
+        if !sig.generics.params.is_empty() {
+            errors.push(syn::Error::new_spanned(
+                &sig.generics,
+                "a step cannot be generic; declare the generic params on the story like `trait MyStory<T>`",
+            ));
+        } else if let Some(where_clause) = &sig.generics.where_clause {
+            errors.push(syn::Error::new_spanned(
+                where_clause,
+                "a step cannot have a where clause; declare it on the story like `trait MyStory<T> where ...`",
+            ));
+        }
+        let mut names = Vec::new();
+        for input in &sig.inputs {
+            match input {
+                // The receiver is optional, since a step always takes `&mut self` of the environment.
+                syn::FnArg::Receiver(receiver)
+                    if receiver.reference.is_none() || receiver.colon_token.is_some() =>
+                {
+                    errors.push(syn::Error::new_spanned(
+                        receiver,
+                        "a step takes the environment as `&mut self`; remove this receiver",
+                    ));
+                }
+                syn::FnArg::Receiver(_) => {}
+                syn::FnArg::Typed(pat_type) => match pat_type.pat.as_ref() {
+                    syn::Pat::Ident(syn::PatIdent {
+                        by_ref: None,
+                        mutability: None,
+                        ident,
+                        subpat: None,
+                        ..
+                    }) => {
+                        if names.contains(&ident) {
+                            errors.push(syn::Error::new_spanned(
+                                ident,
+                                format!("duplicate arg `{ident}`; rename one of them"),
+                            ));
+                        }
+                        names.push(ident);
+                    }
+                    pat => errors.push(syn::Error::new_spanned(
+                        pat,
+                        "an arg of a step must be a name to be given a value in #[step]; replace the pattern with a name like `arg: Type`",
+                    )),
+                },
+            }
+        }
+        for (index, arg) in self.step_attr.args.iter().enumerate() {
+            if self.step_attr.args[..index]
+                .iter()
+                .any(|other| other.ident == arg.ident)
+            {
+                errors.push(syn::Error::new_spanned(
+                    &arg.ident,
+                    format!(
+                        "duplicate `{} = ...` in the step attribute; remove one of them",
+                        arg.ident
+                    ),
+                ));
+            }
+        }
+        crate::combine_errors(errors)
+    }
+
     pub(crate) fn attr_args(&self) -> impl Iterator<Item = (&syn::Ident, &syn::Expr)> {
         self.step_attr
             .args
@@ -48,6 +133,13 @@ impl StoryStep {
             .map(|arg| (&arg.ident, &arg.value))
     }
 
+    /// The `#[cfg(...)]` attrs of the step, which gate everything generated for the step.
+    pub(crate) fn cfg_attrs(&self) -> impl Iterator<Item = &syn::Attribute> {
+        self.other_attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("cfg"))
+    }
+
     /// This ignores patterns.
     pub(crate) fn fn_args(&self) -> impl Iterator<Item = (&syn::Ident, &syn::Type)> {
         self.inner
@@ -76,14 +168,49 @@ impl StoryStep {
         })
     }
 
-    pub(crate) fn extract_format_args(&self) -> Vec<String> {
-        collect_format_args(&self.step_attr.text)
+    /// The step text and the texts in locales.
+    pub(crate) fn texts(&self) -> impl Iterator<Item = &syn::LitStr> {
+        std::iter::once(&self.step_attr.text).chain(self.localized_texts().map(|(_, text)| text))
+    }
+
+    /// The names that the texts refer to, like `user` for `{user.name}`.
+    pub(crate) fn text_args(&self) -> BTreeSet<String> {
+        self.texts().flat_map(collect_format_args).collect()
+    }
+
+    /// The attr args that the step takes, which are the args of the fn or unused ones for unused
+    /// warnings. Args only used in the texts are not needed to run the step.
+    pub(crate) fn run_attr_args(&self) -> impl Iterator<Item = &StepAttrArgs> {
+        let text_args = self.text_args();
+        self.step_attr.args.iter().filter(move |arg| {
+            self.fn_args().any(|(ident, _)| *ident == arg.ident)
+                || !text_args.contains(&arg.ident.to_string())
+        })
+    }
+
+    /// The texts of the step in locales like `ja`.
+    pub(crate) fn localized_texts(&self) -> impl Iterator<Item = (&syn::Ident, &syn::LitStr)> {
+        self.step_attr
+            .texts
+            .iter()
+            .flat_map(|texts| &texts.texts)
+            .map(|text| (&text.locale, &text.text))
     }
 
     pub(crate) fn has_sub_story(&self) -> bool {
         self.step_attr.story_type.is_some()
     }
 
+    /// The items to repeat the step for, like `ITEMS` in `repeat = ITEMS`.
+    pub(crate) fn repeat(&self) -> Option<&syn::Expr> {
+        self.step_attr.repeat.as_ref().map(|repeat| &repeat.value)
+    }
+
+    /// The condition to run the step, like `FEATURE_FLAG` in `when = FEATURE_FLAG`.
+    pub(crate) fn when(&self) -> Option<&syn::Expr> {
+        self.step_attr.when.as_ref().map(|when| &when.value)
+    }
+
     /// Gets the path to the sub-story type if this is a sub-story step
     pub(crate) fn sub_story_path(&self) -> Option<&StoryType> {
         self.step_attr.story_type.as_ref()
@@ -167,4 +294,127 @@ mod tests {
         assert_eq!(actual.other_attrs.len(), 1);
         assert!(actual.other_attrs[0].path().is_ident("doc"));
     }
+
+    #[test]
+    fn parse_localized_texts() {
+        let input = quote! {
+            #[step("I have {count} {name}", name = "apple", count = 3, to = "me", text(ja = "{name}を{count}個持っている", pt_BR = "tenho {count}"), unused = "unused")]
+            fn have(count: u32);
+        };
+        let actual = syn::parse2::<StoryStep>(input).unwrap();
+        let args: Vec<_> = actual
+            .attr_args()
+            .map(|(ident, _)| ident.to_string())
+            .collect();
+        assert_eq!(args, vec!["name", "count", "to", "unused"]);
+        let texts: Vec<_> = actual
+            .localized_texts()
+            .map(|(locale, text)| (locale.to_string(), text.value()))
+            .collect();
+        assert_eq!(
+            texts,
+            vec![
+                ("ja".to_string(), "{name}を{count}個持っている".to_string()),
+                ("pt_BR".to_string(), "tenho {count}".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_duplicate_localized_texts() {
+        let input = quote! {
+            #[step("Step 1", text(ja = "ステップ1", ja = "ステップ2"))]
+            fn step1();
+        };
+        let Err(err) = syn::parse2::<StoryStep>(input) else {
+            panic!("duplicate locales must be rejected");
+        };
+        assert_eq!(err.to_string(), "duplicate text in the locale `ja`");
+        let input = quote! {
+            #[step("Step 1", text(ja = "ステップ1"), text(fr = "Étape 1"))]
+            fn step1();
+        };
+        assert!(syn::parse2::<StoryStep>(input).is_err());
+        let input = quote! {
+            #[step("Step 1", text(japanese = "ステップ1"))]
+            fn step1();
+        };
+        assert!(syn::parse2::<StoryStep>(input).is_err());
+    }
+
+    #[test]
+    fn parse_args_named_like_locales() {
+        // String args named like locales are still args, which are only used in the text.
+        let input = quote! {
+            #[step("visit {url} as {id}", url = "http://x", id = "me")]
+            fn visit();
+        };
+        let actual = syn::parse2::<StoryStep>(input).unwrap();
+        let args: Vec<_> = actual
+            .attr_args()
+            .map(|(ident, _)| ident.to_string())
+            .collect();
+        assert_eq!(args, vec!["url", "id"]);
+        assert_eq!(actual.localized_texts().count(), 0);
+    }
+
+    #[test]
+    fn parse_receivers() {
+        for receiver in [quote!(&self), quote!(&mut self)] {
+            let input = quote! {
+                #[step("Step 1")]
+                fn step1(#receiver);
+            };
+            assert!(syn::parse2::<StoryStep>(input).is_ok());
+        }
+        let input = quote! {
+            #[step("Step 1")]
+            fn step1(self);
+        };
+        let Err(err) = syn::parse2::<StoryStep>(input) else {
+            panic!("`self` must be rejected");
+        };
+        assert_eq!(
+            err.to_string(),
+            "a step takes the environment as `&mut self`; remove this receiver"
+        );
+    }
+
+    #[test]
+    fn parse_malformed_signature() {
+        let input = quote! {
+            #[step("Step 1", id = 1)]
+            fn step1<T>(id: u32, id: u32, (a, b): (u32, u32)) {}
+        };
+        let Err(err) = syn::parse2::<StoryStep>(input) else {
+            panic!("a malformed signature must be rejected");
+        };
+        let messages = err
+            .into_iter()
+            .map(|err| err.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                "a step cannot have a body, since it is implemented by the environment; replace the body with `;`",
+                "a step cannot be generic; declare the generic params on the story like `trait MyStory<T>`",
+                "duplicate arg `id`; rename one of them",
+                "an arg of a step must be a name to be given a value in #[step]; replace the pattern with a name like `arg: Type`",
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_without_step_attr() {
+        let input = quote! {
+            fn step1();
+        };
+        let Err(err) = syn::parse2::<StoryStep>(input) else {
+            panic!("a fn without #[step] must be rejected");
+        };
+        assert_eq!(
+            err.to_string(),
+            "a fn in a story must be a step; add the text of the step like `#[step(\"...\")]`"
+        );
+    }
 }
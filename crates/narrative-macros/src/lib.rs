@@ -1,5 +1,7 @@
 mod error;
 mod extract_types_for_assertion;
+mod foreign_type_for;
+mod include_feature;
 mod item_story;
 mod local_type_for;
 mod no_foreign_type_validation;
@@ -7,11 +9,12 @@ mod output;
 mod step_attr_syntax;
 mod step_usage;
 mod story_attr_syntax;
+mod template;
 
 use item_story::ItemStory;
 use proc_macro2::TokenStream;
-use story_attr_syntax::StoryAttr;
-use syn::parse_macro_input;
+use story_attr_syntax::{BackgroundAttr, StoryAttr};
+use syn::{parse_macro_input, punctuated::Punctuated};
 
 #[proc_macro_attribute]
 /// TODO: Add documentation.
@@ -25,22 +28,98 @@ pub fn story(
 }
 
 #[proc_macro_attribute]
-/// Marks a data type as a local type for a specific story.
-/// This implements both `IndependentType` and `<StoryName>LocalType` for the type.
+/// Marks a trait as a background, whose steps come first in the stories with
+/// `background = <BackgroundName>` in the story attribute.
+/// A background is a story, and its title defaults to the name of the trait.
+pub fn background(
+    attr: proc_macro::TokenStream,
+    input: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let attr = parse_macro_input!(attr as BackgroundAttr);
+    let story = parse_macro_input!(input as ItemStory);
+    let attr = StoryAttr {
+        title: attr
+            .title
+            .unwrap_or_else(|| syn::LitStr::new(&story.ident.to_string(), story.ident.span())),
+        deserialize: None,
+        background: None,
+        error: None,
+        error_bound: None,
+        titles: Vec::new(),
+    };
+    process_story(attr, story).into()
+}
+
+#[proc_macro_attribute]
+/// Marks a data type as a local type for a specific story, or for a set of related stories like
+/// `#[local_type_for(Checkout, Login)]`.
+/// This implements both `StoryOwnedType` and `<StoryName>LocalType` for the type.
 pub fn local_type_for(
     attr: proc_macro::TokenStream,
     input: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
-    let story_name = parse_macro_input!(attr as syn::Ident);
+    let story_names =
+        parse_macro_input!(attr with Punctuated::<syn::Ident, syn::Token![,]>::parse_terminated);
     let input_item = parse_macro_input!(input as syn::Item);
+    if story_names.is_empty() {
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "expected the story like `#[local_type_for(MyStory)]`",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    local_type_for::generate(&story_names.into_iter().collect::<Vec<_>>(), &input_item).into()
+}
+
+#[proc_macro]
+/// Generates story traits from a Gherkin feature file, whose path is relative to the directory of
+/// `Cargo.toml`. Each scenario becomes a story, and the background of the feature becomes a
+/// background of the stories.
+pub fn include_feature(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let path = parse_macro_input!(input as syn::LitStr);
+    include_feature::expand(&path)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
 
-    local_type_for::generate(&story_name, &input_item).into()
+#[proc_macro_attribute]
+/// Marks a newtype of a foreign type as a local type for specific stories, like
+/// `#[foreign_type_for(MyStory)] struct StatusCode(http::StatusCode);`.
+/// The newtype is transparent in `Debug` and serde, and derefs to the foreign type.
+/// `display` serializes the value by `Display` for foreign types without serde support, and
+/// `deserialize` implements `Deserialize` too, by `FromStr` with `display`.
+pub fn foreign_type_for(
+    attr: proc_macro::TokenStream,
+    input: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let attr = parse_macro_input!(attr as foreign_type_for::ForeignTypeAttr);
+    let input_item = parse_macro_input!(input as syn::ItemStruct);
+
+    foreign_type_for::generate(&attr, &input_item).into()
 }
 
 // In general, we don't do caching some intermediate results to keep the implementation simple.
 // However, we should avoid to have heavy computation in this crate, to keep the story compilation
 // fast. So, modules have their own functionality which is simple.
 fn process_story(attr: StoryAttr, story: ItemStory) -> TokenStream {
+    let story = match &attr.background {
+        Some(background) => match story.with_background(background.clone()) {
+            Ok(story) => story,
+            Err(err) => return err.to_compile_error(),
+        },
+        None => story,
+    };
+    if story.parent.is_some()
+        && let Some(error) = attr.error_tokens()
+    {
+        return syn::Error::new_spanned(
+            error,
+            "an extending story shares the error type of its parent, customize the error in the parent instead",
+        )
+        .to_compile_error();
+    }
     output::generate(&attr, &story)
 }
 
@@ -59,6 +138,72 @@ impl quote::ToTokens for Asyncness {
     }
 }
 
+/// Whether the ident is a locale like `ja`, `pt_BR`, or `zh_Hant`, which is a language code
+/// followed by an optional region or script.
+pub(crate) fn is_locale(ident: &syn::Ident) -> bool {
+    let locale = ident.to_string();
+    let (language, rest) = locale
+        .split_once('_')
+        .map_or((locale.as_str(), None), |(language, rest)| {
+            (language, Some(rest))
+        });
+    let is_language =
+        (2..=3).contains(&language.len()) && language.chars().all(|c| c.is_ascii_lowercase());
+    let is_rest = rest.is_none_or(|rest| match rest.len() {
+        2 => rest.chars().all(|c| c.is_ascii_uppercase()),
+        3 => rest.chars().all(|c| c.is_ascii_digit()),
+        4 => {
+            let mut chars = rest.chars();
+            chars.next().is_some_and(|c| c.is_ascii_uppercase())
+                && chars.all(|c| c.is_ascii_lowercase())
+        }
+        _ => false,
+    });
+    is_language && is_rest
+}
+
+/// Combines the errors into one, so all of them are reported at once.
+pub(crate) fn combine_errors(errors: impl IntoIterator<Item = syn::Error>) -> syn::Result<()> {
+    errors
+        .into_iter()
+        .reduce(|mut combined, error| {
+            combined.combine(error);
+            combined
+        })
+        .map_or(Ok(()), Err)
+}
+
+/// Returns the candidate closest to the name if it looks like a typo of the name.
+pub(crate) fn did_you_mean<'a>(
+    name: &str,
+    candidates: impl Iterator<Item = &'a str>,
+) -> Option<&'a str> {
+    candidates
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, candidate)| {
+            *distance * 2 < name.chars().count().max(candidate.chars().count())
+        })
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// The Levenshtein distance, ignoring the case.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<_> = a.chars().flat_map(char::to_lowercase).collect();
+    let b: Vec<_> = b.chars().flat_map(char::to_lowercase).collect();
+    let mut row: Vec<_> = (0..=b.len()).collect();
+    for (i, a) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(a != b);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
 pub(crate) fn collect_format_args(lit_str: &syn::LitStr) -> Vec<String> {
     lit_str
         .value()
@@ -69,13 +214,14 @@ pub(crate) fn collect_format_args(lit_str: &syn::LitStr) -> Vec<String> {
         .flat_map(|part| part.split('{').skip(1))
         // take the part before the first '}'
         .filter_map(|part| part.split_once('}').map(|(head, _)| head))
-        // remove parts after the first ':'
+        // remove parts after the first ':', and fields after the first '.'
         .map(|format| {
             format
                 .split_once(':')
                 .map(|(head, _)| head)
                 .unwrap_or(format)
         })
+        .map(|name| name.split_once('.').map(|(root, _)| root).unwrap_or(name))
         .map(ToOwned::to_owned)
         .collect()
 }
@@ -100,6 +246,67 @@ pub(crate) fn make_static(ty: &syn::Type) -> syn::Type {
     static_ty
 }
 
+/// Whether the arg of a reference type like `&[User]` takes an owned value like `vec![...]`, which
+/// is borrowed while the step runs. References and literals are static values, which keep the
+/// type inference of nested literals like `&[&["a", "b"]]`.
+pub(crate) fn is_borrowed_value(ty: &syn::Type, value: &syn::Expr) -> bool {
+    matches!(ty, syn::Type::Reference(_))
+        && !matches!(value, syn::Expr::Reference(_) | syn::Expr::Lit(_))
+}
+
+/// Replaces the last segment of a path, like `Login` to `AsyncLogin` or `LoginContext`.
+pub(crate) fn map_last_segment(
+    path: &syn::Path,
+    f: impl FnOnce(&syn::Ident) -> syn::Ident,
+) -> syn::Path {
+    let mut cloned = path.clone();
+    if let Some(seg) = cloned.segments.last_mut() {
+        seg.ident = f(&seg.ident);
+    }
+    cloned
+}
+
+/// Replaces the last segment of a story path with the context, like `Login<'a, T>` to
+/// `LoginContext<T>`. Contexts don't have the lifetime params of the story.
+pub(crate) fn context_path(path: &syn::Path) -> syn::Path {
+    let mut cloned = map_last_segment(path, |ident| quote::format_ident!("{}Context", ident));
+    if let Some(seg) = cloned.segments.last_mut()
+        && let syn::PathArguments::AngleBracketed(args) = &mut seg.arguments
+    {
+        args.args = std::mem::take(&mut args.args)
+            .into_iter()
+            .filter(|arg| !matches!(arg, syn::GenericArgument::Lifetime(_)))
+            .collect();
+        if args.args.is_empty() {
+            seg.arguments = syn::PathArguments::None;
+        }
+    }
+    cloned
+}
+
+/// Appends a generic arg to the last segment of a path, like `Login<T>` to `Login<T, Error = E>`.
+pub(crate) fn push_generic_arg(path: &syn::Path, arg: syn::GenericArgument) -> syn::Path {
+    let mut cloned = path.clone();
+    if let Some(seg) = cloned.segments.last_mut() {
+        match &mut seg.arguments {
+            syn::PathArguments::AngleBracketed(args) => args.args.push(arg),
+            arguments => *arguments = syn::PathArguments::AngleBracketed(syn::parse_quote!(<#arg>)),
+        }
+    }
+    cloned
+}
+
+/// Turns a type path into an expression path, like `LoginContext<T>` to `LoginContext::<T>`.
+pub(crate) fn expr_path(path: &syn::Path) -> syn::Path {
+    let mut cloned = path.clone();
+    for seg in &mut cloned.segments {
+        if let syn::PathArguments::AngleBracketed(args) = &mut seg.arguments {
+            args.colon2_token.get_or_insert_with(Default::default);
+        }
+    }
+    cloned
+}
+
 pub(crate) fn pretty_print_expr(expr: &syn::Expr) -> String {
     prettyplease::unparse(
         &syn::parse_file(
@@ -40,7 +40,7 @@ fn extract_field_types(input_item: &syn::Item) -> Vec<&syn::Type> {
 }
 
 pub(crate) fn generate(
-    story_name: &syn::Ident,
+    story_names: &[syn::Ident],
     input_item: &syn::Item,
 ) -> proc_macro2::TokenStream {
     let (type_name, generics) = match &input_item {
@@ -55,28 +55,88 @@ pub(crate) fn generate(
         }
     };
 
-    let local_type_trait = format_ident!("{}LocalType", story_name);
+    let local_type_traits: Vec<_> = story_names
+        .iter()
+        .map(|story_name| format_ident!("{}LocalType", story_name))
+        .collect();
 
-    // Add StoryOwnedType bound to all type parameters
-    let mut impl_generics = generics.clone();
-    impl_generics.type_params_mut().for_each(|param| {
-        param
-            .bounds
-            .push(syn::parse_quote!(narrative::StoryOwnedType));
-        param.bounds.push(syn::parse_quote!(#local_type_trait));
-    });
+    // Type params must satisfy the supertraits of StoryOwnedType for the derived impls.
+    let type_params: Vec<_> = generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect();
+    let mut owned_generics = generics.clone();
+    for param in &type_params {
+        owned_generics
+            .make_where_clause()
+            .predicates
+            .push(syn::parse_quote! {
+                #param: std::fmt::Debug + Clone + narrative::serde::Serialize
+            });
+    }
+    let (impl_generics, type_generics, where_clause) = owned_generics.split_for_impl();
 
-    // Type parameters without bounds for usage
-    let mut type_generics = generics.clone();
-    type_generics.type_params_mut().for_each(|param| {
-        param.bounds.clear();
+    // A generic type is local only when its type params are local to the story too, with the
+    // markers of the params inferred like the ones of containers.
+    let local_type_impls = local_type_traits.iter().map(|local_type_trait| {
+        if type_params.is_empty() {
+            return quote::quote! {
+                impl #impl_generics #local_type_trait for #type_name #type_generics #where_clause {}
+            };
+        }
+        let markers: Vec<_> = (0..type_params.len())
+            .map(|i| format_ident!("__M{}", i))
+            .collect();
+        let mut generics = owned_generics.clone();
+        for (param, marker) in type_params.iter().zip(&markers) {
+            generics.params.push(syn::parse_quote!(#marker));
+            generics
+                .make_where_clause()
+                .predicates
+                .push(syn::parse_quote!(#param: #local_type_trait<#marker>));
+        }
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        quote::quote! {
+            impl #impl_generics #local_type_trait<narrative::Contains<(#(#markers,)*)>> for #type_name #type_generics #where_clause {}
+        }
     });
 
+    // The assertions see the type params as local to all the stories.
+    let mut assertion_generics = owned_generics.clone();
+    for (i, param) in type_params.iter().enumerate() {
+        let bounds = local_type_traits
+            .iter()
+            .enumerate()
+            .map(|(j, local_type_trait)| {
+                let marker = format_ident!("__M{}_{}", j, i);
+                assertion_generics.params.push(syn::parse_quote!(#marker));
+                quote::quote!(#local_type_trait<#marker>)
+            });
+        let predicate = syn::parse_quote!(#param: #(#bounds)+*);
+        assertion_generics
+            .make_where_clause()
+            .predicates
+            .push(predicate);
+    }
+    let (assertion_impl_generics, _, assertion_where_clause) = assertion_generics.split_for_impl();
+    let markers: Vec<_> = (0..local_type_traits.len())
+        .map(|i| format_ident!("M{}", i))
+        .collect();
+    let inferred: Vec<_> = markers.iter().map(|_| quote::quote!(_)).collect();
+    // Spanned to the stories for the diagnostics of the bounds.
+    let bounds = story_names
+        .iter()
+        .zip(&local_type_traits)
+        .zip(&markers)
+        .map(|((story_name, local_type_trait), marker)| {
+            quote::quote_spanned!(story_name.span()=> #local_type_trait<#marker>)
+        });
+
     // Extract field types and generate assertions
     let field_types = extract_field_types(input_item);
     let assertions = field_types
         .iter()
-        .map(|ty| quote::quote!(assert_local_type::<#ty>();));
+        .map(|ty| quote::quote!(assert_local_type::<#ty, #(#inferred),*>();));
 
     // Generate unique assertion function name based on type name
     let assertion_fn_name = format_ident!("_local_type_assertions_{}", type_name);
@@ -86,16 +146,16 @@ pub(crate) fn generate(
 
         // Implement StoryOwnedType for this type
         // This will conflict if #[local_type_for] is applied to the same type twice,
-        // preventing a data type from being a local type for multiple stories
-        impl #impl_generics narrative::StoryOwnedType for #type_name #type_generics {}
+        // preventing a data type from being a local type for stories other than the listed ones
+        impl #impl_generics narrative::StoryOwnedType for #type_name #type_generics #where_clause {}
 
         // Implement StoryLocalType for this type
-        impl #impl_generics #local_type_trait for #type_name #type_generics {}
+        #(#local_type_impls)*
 
-        // Type assertions for field types
+        // Type assertions for field types, which must be local to all the stories
         #[allow(non_snake_case)]
-        fn #assertion_fn_name() {
-            fn assert_local_type<T: #local_type_trait>() {}
+        fn #assertion_fn_name #assertion_impl_generics() #assertion_where_clause {
+            fn assert_local_type<T: #(#bounds +)* ?Sized, #(#markers),*>() {}
             #(#assertions)*
         }
     }
@@ -109,14 +169,14 @@ mod tests {
 
     #[test]
     fn test_struct_with_fields() {
-        let story_name: syn::Ident = syn::parse_quote!(User);
+        let story_names = [syn::parse_quote!(User)];
         let input: syn::Item = syn::parse_quote! {
             struct UserId {
                 id: u64,
                 tags: Vec<String>,
             }
         };
-        let actual = generate(&story_name, &input);
+        let actual = generate(&story_names, &input);
         let expected = quote! {
             struct UserId {
                 id: u64,
@@ -129,9 +189,9 @@ mod tests {
 
             #[allow(non_snake_case)]
             fn _local_type_assertions_UserId() {
-                fn assert_local_type<T: UserLocalType>() {}
-                assert_local_type::<u64>();
-                assert_local_type::<String>();
+                fn assert_local_type<T: UserLocalType<M0> + ?Sized, M0>() {}
+                assert_local_type::<u64, _>();
+                assert_local_type::<Vec<String>, _>();
             }
         };
         assert_eq!(actual.to_string(), expected.to_string());
@@ -139,14 +199,14 @@ mod tests {
 
     #[test]
     fn test_enum_with_variants() {
-        let story_name: syn::Ident = syn::parse_quote!(User);
+        let story_names = [syn::parse_quote!(User)];
         let input: syn::Item = syn::parse_quote! {
             enum UserEvent {
                 Created(UserId),
                 Updated { id: UserId, name: String },
             }
         };
-        let actual = generate(&story_name, &input);
+        let actual = generate(&story_names, &input);
         let expected = quote! {
             enum UserEvent {
                 Created(UserId),
@@ -159,10 +219,84 @@ mod tests {
 
             #[allow(non_snake_case)]
             fn _local_type_assertions_UserEvent() {
-                fn assert_local_type<T: UserLocalType>() {}
-                assert_local_type::<UserId>();
-                assert_local_type::<UserId>();
-                assert_local_type::<String>();
+                fn assert_local_type<T: UserLocalType<M0> + ?Sized, M0>() {}
+                assert_local_type::<UserId, _>();
+                assert_local_type::<UserId, _>();
+                assert_local_type::<String, _>();
+            }
+        };
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn test_multiple_stories() {
+        let story_names = [syn::parse_quote!(Checkout), syn::parse_quote!(Login)];
+        let input: syn::Item = syn::parse_quote! {
+            struct UserId(u64);
+        };
+        let actual = generate(&story_names, &input);
+        let expected = quote! {
+            struct UserId(u64);
+
+            impl narrative::StoryOwnedType for UserId {}
+
+            impl CheckoutLocalType for UserId {}
+            impl LoginLocalType for UserId {}
+
+            #[allow(non_snake_case)]
+            fn _local_type_assertions_UserId() {
+                fn assert_local_type<T: CheckoutLocalType<M0> + LoginLocalType<M1> + ?Sized, M0, M1>() {}
+                assert_local_type::<u64, _, _>();
+            }
+        };
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn test_generics() {
+        let story_names = [syn::parse_quote!(User)];
+        let input: syn::Item = syn::parse_quote! {
+            struct Page<'a, T, const N: usize>
+            where
+                T: PartialEq,
+            {
+                title: &'a str,
+                items: [T; N],
+            }
+        };
+        let actual = generate(&story_names, &input);
+        let expected = quote! {
+            struct Page<'a, T, const N: usize>
+            where
+                T: PartialEq,
+            {
+                title: &'a str,
+                items: [T; N],
+            }
+
+            impl<'a, T, const N: usize> narrative::StoryOwnedType for Page<'a, T, N>
+            where
+                T: PartialEq,
+                T: std::fmt::Debug + Clone + narrative::serde::Serialize
+            {}
+
+            impl<'a, T, const N: usize, __M0> UserLocalType<narrative::Contains<(__M0,)>> for Page<'a, T, N>
+            where
+                T: PartialEq,
+                T: std::fmt::Debug + Clone + narrative::serde::Serialize,
+                T: UserLocalType<__M0>
+            {}
+
+            #[allow(non_snake_case)]
+            fn _local_type_assertions_Page<'a, T, const N: usize, __M0_0>()
+            where
+                T: PartialEq,
+                T: std::fmt::Debug + Clone + narrative::serde::Serialize,
+                T: UserLocalType<__M0_0>
+            {
+                fn assert_local_type<T: UserLocalType<M0> + ?Sized, M0>() {}
+                assert_local_type::<&'a str, _>();
+                assert_local_type::<[T; N], _>();
             }
         };
         assert_eq!(actual.to_string(), expected.to_string());
@@ -13,11 +13,19 @@ mod story_consts;
 mod story_context;
 
 mod dummy_environment;
+mod map_error;
 
-use proc_macro2::TokenStream;
+use std::collections::BTreeSet;
+
+use proc_macro2::{TokenStream, TokenTree};
 use quote::{format_ident, quote, ToTokens};
 
-use crate::{item_story::ItemStory, story_attr_syntax::StoryAttr, Asyncness};
+use crate::{
+    collect_format_args,
+    item_story::{ItemStory, story_const::StoryConst},
+    story_attr_syntax::StoryAttr,
+    Asyncness,
+};
 
 pub(crate) fn generate(attr: &StoryAttr, item: &ItemStory) -> TokenStream {
     let mod_ident = format_ident!("mod_{}", item.ident);
@@ -26,17 +34,19 @@ pub(crate) fn generate(attr: &StoryAttr, item: &ItemStory) -> TokenStream {
     let context_ident = format_ident!("{}Context", item.ident);
     let base_trait = base_trait::generate(item, Asyncness::Sync);
     let async_base_trait = base_trait::generate(item, Asyncness::Async);
-    let story_trait = story_trait::generate(item, Asyncness::Sync);
-    let async_story_trait = story_trait::generate(item, Asyncness::Async);
-    let step_args = step_args::generate(item);
-    let step_types = step_types::generate(item);
-    let story_consts = story_consts::generate(item);
+    let story_trait = story_trait::generate(attr, item, Asyncness::Sync);
+    let async_story_trait = story_trait::generate(attr, item, Asyncness::Async);
+    let step_args = step_args::generate(attr, item);
+    let step_types = step_types::generate(attr, item);
+    let story_consts = story_consts::generate(attr, item);
     let story_context = story_context::generate(attr, item);
     let context_ext = story_context::generate_ext(item);
     let local_type_impls = local_type_impls::generate(item);
     let local_type_assertions = local_type_assertions::generate(item);
-    let dummy_environment = dummy_environment::generate(item, Asyncness::Sync);
-    let async_dummy_environment = dummy_environment::generate(item, Asyncness::Async);
+    let dummy_environment = dummy_environment::generate(attr, item, Asyncness::Sync);
+    let async_dummy_environment = dummy_environment::generate(attr, item, Asyncness::Async);
+    let map_error = map_error::generate(attr, item, Asyncness::Sync);
+    let async_map_error = map_error::generate(attr, item, Asyncness::Async);
     let local_type_trait = format_ident!("{}LocalType", ident);
     quote! {
         #[allow(non_snake_case, unused_imports)]
@@ -55,6 +65,8 @@ pub(crate) fn generate(attr: &StoryAttr, item: &ItemStory) -> TokenStream {
             #local_type_assertions
             #dummy_environment
             #async_dummy_environment
+            #map_error
+            #async_map_error
         }
         #[allow(unused_imports)]
         use narrative::prelude::*;
@@ -142,3 +154,71 @@ impl ToTokens for MatchArms {
         tokens.extend(::core::iter::once(extend));
     }
 }
+
+struct FindReference<'a>(Option<&'a syn::TypeReference>);
+
+impl<'a> syn::visit::Visit<'a> for FindReference<'a> {
+    fn visit_type_reference(&mut self, i: &'a syn::TypeReference) {
+        self.0.get_or_insert(i);
+    }
+}
+
+/// Returns the function to deserialize a value of the type, or an error if the type is borrowed.
+fn deserialize_fn(ty: &syn::Type) -> syn::Result<TokenStream> {
+    let mut finder = FindReference(None);
+    syn::visit::Visit::visit_type(&mut finder, ty);
+    if let Some(reference) = finder.0 {
+        return Err(syn::Error::new_spanned(
+            reference,
+            format!(
+                "`{}` is borrowed and cannot be deserialized, use an owned type like `String` instead of `&str`",
+                crate::pretty_print_type(&syn::Type::Reference(reference.clone())),
+            ),
+        ));
+    }
+    Ok(quote!(narrative::value::deserialize_boxed::<#ty>))
+}
+
+/// Returns `let` statements that bind the story consts referred in the tokens to the values of the
+/// bindings, so an expression sees the values bound by the parent story.
+fn bind_consts(story: &ItemStory, bindings: &syn::Ident, tokens: impl ToTokens) -> TokenStream {
+    let mut referred = BTreeSet::new();
+    collect_idents(tokens.into_token_stream(), &mut referred);
+    story
+        .consts()
+        .filter(|item| referred.contains(&item.raw.ident.to_string()))
+        .map(|item| {
+            let ident = &item.raw.ident;
+            let value = const_value(item, bindings);
+            quote!(let #ident = #value;)
+        })
+        .collect()
+}
+
+/// Returns the value of the const bound by the bindings, or overridden by the env var.
+fn const_value(item: &StoryConst, bindings: &syn::Ident) -> TokenStream {
+    let ident = &item.raw.ident;
+    if item.env.is_some() {
+        quote!(story_consts::#ident::value::<#bindings>())
+    } else {
+        quote!((#bindings::CONSTS.#ident)())
+    }
+}
+
+// Idents in string literals are collected too for consts captured by format strings.
+fn collect_idents(tokens: TokenStream, idents: &mut BTreeSet<String>) {
+    for token in tokens {
+        match token {
+            TokenTree::Ident(ident) => {
+                idents.insert(ident.to_string());
+            }
+            TokenTree::Group(group) => collect_idents(group.stream(), idents),
+            TokenTree::Literal(literal) => {
+                if let Ok(lit_str) = syn::parse2::<syn::LitStr>(literal.into_token_stream()) {
+                    idents.extend(collect_format_args(&lit_str));
+                }
+            }
+            TokenTree::Punct(_) => {}
+        }
+    }
+}
@@ -19,24 +19,36 @@ pub fn generate(input: &ItemStory, asyncness: Asyncness) -> TokenStream {
         Asyncness::Sync => input.ident.clone(),
         Asyncness::Async => format_ident!("Async{}", input.ident),
     };
-    let consts = input.consts().map(|StoryConst { raw, .. }| {
+    let consts = input.rust_consts().map(|StoryConst { raw, .. }| {
         let ident = &raw.ident;
         let ty = &raw.ty;
         Some(quote! {
             const #ident: #ty;
         })
     });
-    let consts_assigns = input.consts().map(|StoryConst { raw, .. }| raw);
+    let consts_assigns = input.rust_consts().map(|StoryConst { raw, .. }| {
+        let ident = &raw.ident;
+        let ty = &raw.ty;
+        quote! {
+            const #ident: #ty = consts::#ident;
+        }
+    });
+    let impl_params = input.impl_params();
+    let trait_params = (!impl_params.is_empty()).then(|| quote!(<#impl_params>));
+    let type_generics = input.type_generics();
+    let where_clause = input.where_clause();
+    let story_trait_bound = input.trait_bound(&story_trait_ident);
+    let env = input.internal_param("T");
     quote! {
-        pub trait #trait_ident {
+        pub trait #trait_ident #trait_params #where_clause {
             #(#consts)*
             type Context: narrative::story::StoryContext;
             const CONTEXT: Self::Context;
         }
-        impl<T: #story_trait_ident> #trait_ident for T {
+        impl<#impl_params #env: #story_trait_bound> #trait_ident #type_generics for #env #where_clause {
             #(#consts_assigns)*
-            type Context = StoryContext;
-            const CONTEXT: StoryContext = StoryContext;
+            type Context = StoryContext #type_generics;
+            const CONTEXT: StoryContext #type_generics = StoryContext::new();
         }
     }
 }
@@ -64,12 +76,36 @@ mod tests {
             }
             impl<T: User> BaseTrait for T {
                 type Context = StoryContext;
-                const CONTEXT: StoryContext = StoryContext;
+                const CONTEXT: StoryContext = StoryContext::new();
             }
         };
         assert_eq!(actual.to_string(), expected.to_string());
     }
 
+    #[test]
+    fn test_generic() {
+        let story_syntax = syn::parse_quote! {
+            trait User<'a, T: Backend> {
+                const NAME: &str = "alice";
+                #[step("Step 1")]
+                fn step1();
+            }
+        };
+        let actual = generate(&story_syntax, Asyncness::Sync);
+        let expected = quote! {
+            pub trait BaseTrait<T: Backend + 'static,> {
+                const NAME: &str;
+                type Context: narrative::story::StoryContext;
+                const CONTEXT: Self::Context;
+            }
+            impl<T: Backend + 'static, T_: for<'a> User<'a, T> > BaseTrait<T,> for T_ {
+                const NAME: &str = consts::NAME;
+                type Context = StoryContext<T,>;
+                const CONTEXT: StoryContext<T,> = StoryContext::new();
+            }
+        };
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
 
     #[test]
     fn test_async() {
@@ -89,7 +125,7 @@ mod tests {
             }
             impl<T: AsyncUser> AsyncBaseTrait for T {
                 type Context = StoryContext;
-                const CONTEXT: StoryContext = StoryContext;
+                const CONTEXT: StoryContext = StoryContext::new();
             }
         };
         assert_eq!(actual.to_string(), expected.to_string());
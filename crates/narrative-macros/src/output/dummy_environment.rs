@@ -1,19 +1,38 @@
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
 
-use crate::{item_story::ItemStory, output::step_fn, Asyncness};
+use crate::{item_story::ItemStory, output::step_fn, story_attr_syntax::StoryAttr, Asyncness};
 
-pub(crate) fn generate(input: &ItemStory, asyncness: Asyncness) -> TokenStream {
+pub(crate) fn generate(attr: &StoryAttr, input: &ItemStory, asyncness: Asyncness) -> TokenStream {
     let ident = match asyncness {
         Asyncness::Sync => input.ident.clone(),
         Asyncness::Async => format_ident!("Async{}", input.ident),
     };
-    let generics = match asyncness {
-        Asyncness::Sync => quote!(<E>),
-        Asyncness::Async => quote!(<E: Send>),
+    let mut generics = input.generics.clone();
+    // A story with a custom error is only implemented for the environment with the error.
+    let error = match &attr.error {
+        Some(error) => quote!(#error),
+        None => {
+            let error = input.internal_param("E");
+            generics.params.push(match asyncness {
+                Asyncness::Sync => syn::parse_quote!(#error),
+                Asyncness::Async => syn::parse_quote!(#error: Send),
+            });
+            quote!(#error)
+        }
     };
+    if attr.error.is_none()
+        && let Some(bounds) = attr.error_bounds()
+    {
+        generics
+            .make_where_clause()
+            .predicates
+            .push(syn::parse_quote!(#error: #bounds));
+    }
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
     let steps = input.steps().map(|step| {
-        let step_fn = step_fn::generate(step, asyncness);
+        let step_fn = step_fn::generate(step, asyncness, attr.converts_errors());
         let body = if step.has_sub_story() {
             quote!(Ok(
                 narrative::environment::DummyEnvironment::<Self::Error>::default()
@@ -33,10 +52,12 @@ pub(crate) fn generate(input: &ItemStory, asyncness: Asyncness) -> TokenStream {
         }
     });
 
+    let error_ty = input.parent.is_none().then(|| quote!(type Error = #error;));
+
     quote! {
         #[allow(unused_variables)]
-        impl #generics #ident for narrative::environment::DummyEnvironment<E> {
-            type Error = E;
+        impl #impl_generics #ident #ty_generics for narrative::environment::DummyEnvironment<#error> #where_clause {
+            #error_ty
             #(#steps)*
         }
     }
@@ -57,7 +78,7 @@ mod tests {
                 fn step2();
             }
         };
-        let actual = generate(&story_syntax, Asyncness::Sync);
+        let actual = generate(&syn::parse_quote!("User Story"), &story_syntax, Asyncness::Sync);
         let expected = quote! {
             #[allow(unused_variables)]
             impl<E> UserStory for narrative::environment::DummyEnvironment<E> {
@@ -77,6 +98,29 @@ mod tests {
         assert_eq!(actual.to_string(), expected.to_string());
     }
 
+    #[test]
+    fn test_generic() {
+        let story_syntax = syn::parse_quote! {
+            trait UserStory<'a, E: Backend = Postgres> where E: Clone {
+                #[step(story: SubStory<E>, "Step 1")]
+                fn step1();
+            }
+        };
+        let actual = generate(&syn::parse_quote!("User Story"), &story_syntax, Asyncness::Sync);
+        let expected = quote! {
+            #[allow(unused_variables)]
+            impl<'a, E: Backend, E_> UserStory<'a, E> for narrative::environment::DummyEnvironment<E_> where E: Clone {
+                type Error = E_;
+                #[inline]
+                #[allow(clippy::manual_async_fn)]
+                fn step1(&mut self) -> Result<impl SubStory<E, Error = Self::Error>, Self::Error> {
+                    Ok(narrative::environment::DummyEnvironment::<Self::Error>::default())
+                }
+            }
+        };
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
     #[test]
     fn test_async() {
         let story_syntax = syn::parse_quote! {
@@ -87,7 +131,7 @@ mod tests {
                 fn step2();
             }
         };
-        let actual = generate(&story_syntax, Asyncness::Async);
+        let actual = generate(&syn::parse_quote!("User Story"), &story_syntax, Asyncness::Async);
         let expected = quote! {
             #[allow(unused_variables)]
             impl<E: Send> AsyncUserStory for narrative::environment::DummyEnvironment<E> {
@@ -115,7 +159,7 @@ mod tests {
                 fn sub_step_1();
             }
         };
-        let actual = generate(&story_syntax, Asyncness::Sync);
+        let actual = generate(&syn::parse_quote!("User Story"), &story_syntax, Asyncness::Sync);
         let expected = quote! {
             #[allow(unused_variables)]
             impl<E> StoryDef for narrative::environment::DummyEnvironment<E> {
@@ -138,7 +182,7 @@ mod tests {
                 fn sub_step_1();
             }
         };
-        let actual = generate(&story_syntax, Asyncness::Async);
+        let actual = generate(&syn::parse_quote!("User Story"), &story_syntax, Asyncness::Async);
         let expected = quote! {
             #[allow(unused_variables)]
             impl<E: Send> AsyncStoryDef for narrative::environment::DummyEnvironment<E> {
@@ -1,5 +1,5 @@
 use proc_macro2::TokenStream;
-use quote::{format_ident, quote};
+use quote::{format_ident, quote, quote_spanned};
 
 use crate::{
     extract_types_for_assertion::extract_types_for_assertion,
@@ -9,6 +9,8 @@ use crate::{
 pub fn generate(input: &ItemStory) -> TokenStream {
     let story_name = &input.ident;
     let local_type_trait = format_ident!("{}LocalType", story_name);
+    // Spanned to the story for the diagnostics of the bound.
+    let bound = quote_spanned!(story_name.span()=> #local_type_trait<M>);
 
     let assertions = input.items.iter().map(|item| match item {
         StoryItem::Step(step) => {
@@ -22,9 +24,11 @@ pub fn generate(input: &ItemStory) -> TokenStream {
                 .flat_map(extract_types_for_assertion)
                 .collect();
 
+            let cfg_attrs = step.cfg_attrs().collect::<Vec<_>>();
             let assertions = types.iter().map(|ty| {
                 quote! {
-                    assert_local_type::<#ty>();
+                    #(#cfg_attrs)*
+                    assert_local_type::<#ty, _>();
                 }
             });
             quote! {
@@ -35,7 +39,7 @@ pub fn generate(input: &ItemStory) -> TokenStream {
     });
     quote! {
         fn _local_type_assertions() {
-            fn assert_local_type<T: #local_type_trait>() {}
+            fn assert_local_type<T: #bound + ?Sized, M>() {}
             #(#assertions)*
         }
     }
@@ -52,16 +56,16 @@ mod tests {
             trait User {
                 #[step("Step 1")]
                 fn step1();
-                #[step("Step 2")]
+                #[step("Step 2", id = UserId::new(), name = "Alice")]
                 fn step2(id: UserId, name: &str);
             }
         };
         let actual = generate(&input);
         let expected = quote! {
             fn _local_type_assertions() {
-                fn assert_local_type<T: UserLocalType>() {}
-                assert_local_type::<UserId>();
-                assert_local_type::<&str>();
+                fn assert_local_type<T: UserLocalType<M> + ?Sized, M>() {}
+                assert_local_type::<UserId, _>();
+                assert_local_type::<&str, _>();
             }
         };
         assert_eq!(actual.to_string(), expected.to_string());
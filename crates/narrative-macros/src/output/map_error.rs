@@ -0,0 +1,90 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+use crate::{
+    Asyncness, item_story::ItemStory, output::step_fn, push_generic_arg,
+    story_attr_syntax::StoryAttr,
+};
+
+/// Implements the story for `MapError`, which converts the errors of the inner environment, so a
+/// story can run this story as a sub story on an environment with a different error type.
+pub(crate) fn generate(attr: &StoryAttr, input: &ItemStory, asyncness: Asyncness) -> TokenStream {
+    let ident = match asyncness {
+        Asyncness::Sync => input.ident.clone(),
+        Asyncness::Async => format_ident!("Async{}", input.ident),
+    };
+    let env = input.internal_param("T");
+    let inner_error = input.internal_param("F");
+    let conversion = input.internal_param("C");
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let mut generics = input.generics.clone();
+    let story_bound = push_generic_arg(
+        &syn::parse_quote!(#ident #ty_generics),
+        syn::parse_quote!(Error = #inner_error),
+    );
+    generics.params.push(syn::parse_quote!(#env: #story_bound));
+    generics.params.push(syn::parse_quote!(#inner_error));
+    let conversion_trait = quote!(narrative::environment::ErrorConversion<#inner_error>);
+    // A custom error is fixed, so the conversion must end with it.
+    let conversion_bound: syn::TypeParamBound = match &attr.error {
+        Some(error) => {
+            syn::parse_quote!(narrative::environment::ErrorConversion<#inner_error, To = #error>)
+        }
+        None => syn::parse_quote!(#conversion_trait),
+    };
+    generics
+        .params
+        .push(syn::parse_quote!(#conversion: #conversion_bound));
+    if attr.error.is_none()
+        && let Some(bounds) = attr.error_bounds()
+    {
+        generics
+            .make_where_clause()
+            .predicates
+            .push(syn::parse_quote!(<#conversion as #conversion_trait>::To: #bounds));
+    }
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let convert = quote!(<#conversion as #conversion_trait>::convert);
+    let steps = input.steps().map(|step| {
+        let step_fn = step_fn::generate(step, asyncness, attr.converts_errors());
+        let step_name = &step.inner.sig.ident;
+        let args = step.fn_args().map(|(ident, _)| ident);
+        let body = if step.has_sub_story() {
+            quote! {
+                match self.inner_mut().#step_name(#(#args),*) {
+                    Ok(sub_story) => Ok(narrative::environment::MapError::<#env, #conversion>::chain::<_, #inner_error>(sub_story)),
+                    Err(error) => Err(#convert(error)),
+                }
+            }
+        } else {
+            match asyncness {
+                Asyncness::Sync => quote! {
+                    self.inner_mut().#step_name(#(#args),*).map_err(#convert)
+                },
+                Asyncness::Async => quote! {
+                    let future = self.inner_mut().#step_name(#(#args),*);
+                    async move { future.await.map_err(#convert) }
+                },
+            }
+        };
+        quote! {
+            #[inline]
+            #[allow(clippy::manual_async_fn)]
+            #step_fn {
+                #body
+            }
+        }
+    });
+
+    let error_ty = input
+        .parent
+        .is_none()
+        .then(|| quote!(type Error = <#conversion as #conversion_trait>::To;));
+
+    quote! {
+        impl #impl_generics #ident #ty_generics for narrative::environment::MapError<#env, #conversion> #where_clause {
+            #error_ty
+            #(#steps)*
+        }
+    }
+}
@@ -6,68 +6,95 @@ use proc_macro2::TokenStream;
 use quote::{format_ident, quote, quote_spanned};
 
 use crate::{
+    is_borrowed_value,
     item_story::{ItemStory, StoryStep},
     make_static,
-    output::MatchArms,
+    output::{MatchArms, bind_consts, const_value, deserialize_fn},
     pretty_print_expr, pretty_print_type,
+    story_attr_syntax::StoryAttr,
 };
 
-pub(crate) fn generate(story: &ItemStory) -> TokenStream {
+pub(crate) fn generate(attr: &StoryAttr, story: &ItemStory) -> TokenStream {
     let steps_with_args = story
         .steps()
         .filter(|step| step.fn_args().next().is_some())
         .collect::<Vec<_>>();
-    if steps_with_args.is_empty() {
-        return quote! {
-            /// This is placeholder and never be constructed.
-            pub type StepArg = narrative::step::DynStepArg;
-        };
-    }
     let step_names: Vec<_> = steps_with_args
         .iter()
         .map(|step| &step.inner.sig.ident)
         .collect();
-    let step_enums = steps_with_args.iter().cloned().map(generate_step_enum);
+    // The variants and arms of a step are gated by the cfg attrs of the step.
+    let step_cfgs: Vec<_> = steps_with_args
+        .iter()
+        .map(|step| {
+            let cfg_attrs = step.cfg_attrs();
+            quote!(#(#cfg_attrs)*)
+        })
+        .collect();
+    let step_enums = steps_with_args
+        .iter()
+        .map(|step| generate_step_enum(story, step));
     let arg_impls = steps_with_args
         .iter()
-        .map(|step| generate_arg_impl(story, step));
-    let serde_impls = steps_with_args.iter().cloned().map(generate_serialize_impl);
+        .map(|step| generate_arg_impl(story, step, attr.deserialize.is_some()));
     let debug_impls = steps_with_args.iter().cloned().map(generate_debug_impl);
     let arg_values = steps_with_args.iter().cloned().map(generate_arg_values);
     let arg_value_debug_arms = steps_with_args
         .iter()
         .map(|step| {
             let step_ident = &step.inner.sig.ident;
-            quote!(Self::#step_ident(arg) => arg.fmt(f),)
+            let cfg_attrs = step.cfg_attrs();
+            quote!(#(#cfg_attrs)* Self::#step_ident(arg) => arg.fmt(f),)
         })
         .collect::<MatchArms>();
     let step_arg_value_arms = steps_with_args
         .iter()
         .map(|step| {
             let step_ident = &step.inner.sig.ident;
-            quote!(StepArgInner::#step_ident(arg) => arg.value(),)
+            let cfg_attrs = step.cfg_attrs();
+            quote!(#(#cfg_attrs)* StepArgInner::#step_ident(arg) => arg.value::<B>(),)
         })
         .collect::<MatchArms>()
         .match_target(quote!(self.0))
         .cast_as(quote!(narrative::value::BoxedValue));
+    let deserialize_value = attr.deserialize.is_some().then(|| {
+        quote! {
+            #[inline]
+            fn deserialize_value<'de, D: narrative::serde::Deserializer<'de>>(&self, deserializer: D) -> Result<narrative::value::BoxedValue, D::Error> {
+                narrative::step::StepArg::deserialize_value(&self.to_dyn(), deserializer)
+            }
+        }
+    });
 
     quote! {
-        #[derive(Clone, Copy, narrative::serde::Serialize)]
-        #[serde(transparent, crate = "narrative::serde")]
-        pub struct StepArg(StepArgInner);
+        pub struct StepArg<B = narrative::story::DefaultBindings>(StepArgInner, std::marker::PhantomData<fn() -> B>);
 
-        #[derive(Clone, Copy, narrative::serde::Serialize)]
+        impl<B> StepArg<B> {
+            const fn new(inner: StepArgInner) -> Self {
+                Self(inner, std::marker::PhantomData)
+            }
+        }
+
+        impl<B> Clone for StepArg<B> {
+            #[inline]
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl<B> Copy for StepArg<B> {}
+
+        #[derive(Clone, Copy)]
         #[allow(non_camel_case_types)]
-        #[serde(untagged, crate = "narrative::serde")]
         enum StepArgInner {
-            #(#step_names(args::#step_names)),*
+            #(#step_cfgs #step_names(args::#step_names)),*
         }
 
         #[allow(non_camel_case_types)]
         #[derive(narrative::serde::Serialize)]
         #[serde(untagged, crate = "narrative::serde")]
         enum ArgValue {
-            #(#step_names(arg_values::#step_names)),*
+            #(#step_cfgs #step_names(arg_values::#step_names)),*
         }
 
         impl std::fmt::Debug for ArgValue {
@@ -77,40 +104,41 @@ pub(crate) fn generate(story: &ItemStory) -> TokenStream {
             }
         }
 
-        impl narrative::step::StepArg for StepArg {
+        impl<B: narrative::story::ConstBindings<ConstValues>> narrative::step::StepArg for StepArg<B> {
             #[inline]
             fn name(&self) -> &'static str {
                 match self.0 {
-                    #(StepArgInner::#step_names(arg) => arg.name()),*
+                    #(#step_cfgs StepArgInner::#step_names(arg) => arg.name()),*
                 }
             }
             #[inline]
             fn ty(&self) -> &'static str {
                 match self.0 {
-                    #(StepArgInner::#step_names(arg) => arg.ty()),*
+                    #(#step_cfgs StepArgInner::#step_names(arg) => arg.ty()),*
                 }
             }
             #[inline]
             fn expr(&self) -> &'static str {
                 match self.0 {
-                    #(StepArgInner::#step_names(arg) => arg.expr()),*
+                    #(#step_cfgs StepArgInner::#step_names(arg) => arg.expr()),*
                 }
             }
             #[inline]
             fn value(&self) -> impl narrative::value::Value {
                 #step_arg_value_arms
             }
+            #deserialize_value
         }
 
-        impl StepArg {
+        impl<B: narrative::story::ConstBindings<ConstValues>> StepArg<B> {
             pub fn to_dyn(&self) -> narrative::step::DynStepArg {
                 match self.0 {
-                    #(StepArgInner::#step_names(arg) => arg.to_dyn()),*
+                    #(#step_cfgs StepArgInner::#step_names(arg) => arg.to_dyn::<B>()),*
                 }
             }
         }
 
-        impl std::fmt::Debug for StepArg {
+        impl<B: narrative::story::ConstBindings<ConstValues>> std::fmt::Debug for StepArg<B> {
             #[inline]
             fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
                 use narrative::step::StepArg;
@@ -118,6 +146,20 @@ pub(crate) fn generate(story: &ItemStory) -> TokenStream {
             }
         }
 
+        impl<B: narrative::story::ConstBindings<ConstValues>> narrative::serde::Serialize for StepArg<B> {
+            #[inline]
+            fn serialize<T: narrative::serde::Serializer>(&self, serializer: T) -> Result<T::Ok, T::Error> {
+                use narrative::step::StepArg;
+                use narrative::serde::ser::SerializeMap;
+                let mut map = serializer.serialize_map(Some(4))?;
+                map.serialize_entry("name", self.name())?;
+                map.serialize_entry("ty", self.ty())?;
+                map.serialize_entry("expr", self.expr())?;
+                map.serialize_entry("value", &self.value())?;
+                map.end()
+            }
+        }
+
         mod arg_values {
             use super::*;
             #(#arg_values)*
@@ -127,7 +169,6 @@ pub(crate) fn generate(story: &ItemStory) -> TokenStream {
 
             #(#step_enums)*
             #(#arg_impls)*
-            #(#serde_impls)*
             #(#debug_impls)*
         }
     }
@@ -135,7 +176,9 @@ pub(crate) fn generate(story: &ItemStory) -> TokenStream {
 
 fn generate_debug_impl(step: &StoryStep) -> TokenStream {
     let step_ident = &step.inner.sig.ident;
+    let cfg_attrs = step.cfg_attrs();
     quote! {
+        #(#cfg_attrs)*
         impl std::fmt::Debug for #step_ident {
             #[inline]
             fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
@@ -145,10 +188,20 @@ fn generate_debug_impl(step: &StoryStep) -> TokenStream {
     }
 }
 
-fn generate_step_enum(step: &StoryStep) -> TokenStream {
+fn generate_step_enum(story: &ItemStory, step: &StoryStep) -> TokenStream {
     let step_ident = &step.inner.sig.ident;
-    let variants = step.fn_args().map(|(ident, _)| ident);
+    let repeat_item = story.repeat_item(step);
+    // The arg taking the items of a repeated step holds the index of the item.
+    let variants = step.fn_args().map(|(ident, _)| {
+        if Some(ident) == repeat_item {
+            quote!(#ident(usize))
+        } else {
+            quote!(#ident)
+        }
+    });
+    let cfg_attrs = step.cfg_attrs();
     quote! {
+        #(#cfg_attrs)*
         #[allow(non_camel_case_types)]
         #[derive(Clone, Copy)]
         pub(super) enum #step_ident {
@@ -157,27 +210,16 @@ fn generate_step_enum(step: &StoryStep) -> TokenStream {
     }
 }
 
-fn generate_serialize_impl(step: &StoryStep) -> TokenStream {
-    let step_ident = &step.inner.sig.ident;
-    quote! {
-        impl narrative::serde::Serialize for #step_ident {
-            #[inline]
-            fn serialize<T: narrative::serde::Serializer>(&self, serializer: T) -> Result<T::Ok, T::Error> {
-                use narrative::serde::ser::SerializeMap;
-                let mut map = serializer.serialize_map(Some(4))?;
-                map.serialize_entry("name", self.name())?;
-                map.serialize_entry("ty", self.ty())?;
-                map.serialize_entry("expr", self.expr())?;
-                map.serialize_entry("value", &self.value())?;
-                map.end()
-            }
-        }
-    }
-}
-
 fn generate_arg_values(step: &StoryStep) -> TokenStream {
     let step_ident = &step.inner.sig.ident;
+    // The values of borrowed args are boxed as they may be owned values like `Vec<T>` for `&[T]`.
     let variants = step.fn_args().map(|(ident, ty)| {
+        if step
+            .find_attr_arg(ident)
+            .is_some_and(|value| is_borrowed_value(ty, value))
+        {
+            return quote!(#ident(narrative::value::BoxedValue));
+        }
         let static_ty = make_static(ty);
         quote! {
             #ident(#static_ty)
@@ -187,7 +229,9 @@ fn generate_arg_values(step: &StoryStep) -> TokenStream {
         .fn_args()
         .map(|(ident, _)| quote!(Self::#ident(arg) => arg.fmt(f),))
         .collect::<MatchArms>();
+    let cfg_attrs = step.cfg_attrs().collect::<Vec<_>>();
     quote! {
+        #(#cfg_attrs)*
         #[allow(non_camel_case_types)]
         #[derive(narrative::serde::Serialize)]
         #[serde(untagged, crate = "narrative::serde")]
@@ -195,6 +239,7 @@ fn generate_arg_values(step: &StoryStep) -> TokenStream {
             #(#variants,)*
         }
 
+        #(#cfg_attrs)*
         impl std::fmt::Debug for #step_ident {
             #[inline]
             fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
@@ -204,7 +249,7 @@ fn generate_arg_values(step: &StoryStep) -> TokenStream {
     }
 }
 
-fn generate_arg_impl(story: &ItemStory, step: &StoryStep) -> TokenStream {
+fn generate_arg_impl(story: &ItemStory, step: &StoryStep, deserialize: bool) -> TokenStream {
     let step_ident = &step.inner.sig.ident;
     // Create identifier for the outer step args module (e.g., my_step_args)
     let step_ident_args_mod = format_ident!("{}_args", step_ident);
@@ -213,14 +258,18 @@ fn generate_arg_impl(story: &ItemStory, step: &StoryStep) -> TokenStream {
         ident: &'a syn::Ident,
         ty: &'a syn::Type,
         mod_ident: syn::Ident,
+        /// The variant and the index for the arg taking the items of a repeated step.
+        indexed: Option<(TokenStream, TokenStream)>,
     }
 
+    let repeat_item = story.repeat_item(step);
     let args = step
         .fn_args()
         .map(|(ident, ty)| FnArg {
             ident,
             ty,
             mod_ident: format_ident!("mod_{}", ident),
+            indexed: (Some(ident) == repeat_item).then(|| (quote!((index)), quote!(*index))),
         })
         .collect::<Vec<_>>();
 
@@ -228,49 +277,132 @@ fn generate_arg_impl(story: &ItemStory, step: &StoryStep) -> TokenStream {
         .iter()
         .map(
             |FnArg {
-                 ident, mod_ident, ..
-             }| quote!(Self::#ident => #step_ident_args_mod::#mod_ident::__NAME,),
+                 ident,
+                 mod_ident,
+                 indexed,
+                 ..
+             }| {
+                let index = indexed.as_ref().map(|_| quote!((_)));
+                quote!(Self::#ident #index => #step_ident_args_mod::#mod_ident::__NAME,)
+            },
         )
         .collect::<MatchArms>();
     let ty_arms = args
         .iter()
         .map(
             |FnArg {
-                 ident, mod_ident, ..
-             }| quote!(Self::#ident => #step_ident_args_mod::#mod_ident::__TY,),
+                 ident,
+                 mod_ident,
+                 indexed,
+                 ..
+             }| {
+                let index = indexed.as_ref().map(|_| quote!((_)));
+                quote!(Self::#ident #index => #step_ident_args_mod::#mod_ident::__TY,)
+            },
         )
         .collect::<MatchArms>();
     let expr_arms = args
         .iter()
         .map(
             |FnArg {
-                 ident, mod_ident, ..
-             }| quote!(Self::#ident => #step_ident_args_mod::#mod_ident::__EXPR,),
+                 ident,
+                 mod_ident,
+                 indexed,
+                 ..
+             }| {
+                let index = indexed.as_ref().map(|_| quote!((_)));
+                quote!(Self::#ident #index => #step_ident_args_mod::#mod_ident::__EXPR,)
+            },
         )
         .collect::<MatchArms>();
     let value_arms = args
         .iter()
-        .map(|FnArg { ident, mod_ident, .. }| quote!(Self::#ident => ArgValue::#step_ident(arg_values::#step_ident::#ident(#step_ident_args_mod::#mod_ident::value())),))
+        .map(|FnArg { ident, mod_ident, indexed, .. }| {
+            let (pat, index) = indexed.clone().unzip();
+            quote!(Self::#ident #pat => ArgValue::#step_ident(arg_values::#step_ident::#ident(#step_ident_args_mod::#mod_ident::value::<B>(#index))),)
+        })
         .collect::<MatchArms>();
     let to_dyn_arms = args
         .iter()
         .map(
             |FnArg {
-                 ident, mod_ident, ..
-             }| quote!(Self::#ident => #step_ident_args_mod::#mod_ident::DYN_STEP_ARG,),
+                 ident, mod_ident, indexed, ..
+             }| {
+                let (pat, index) = indexed.clone().unzip();
+                quote!(Self::#ident #pat => #step_ident_args_mod::#mod_ident::dyn_step_arg::<B>(#index),)
+            },
         )
         .collect::<MatchArms>();
-    let result = args.iter().map(|FnArg { ident, ty, mod_ident }| {
-        let Some(expr) = step
-            .find_attr_arg(ident)
-            .or_else(|| story.find_assignments(ident)) else {
-                return Err(quote_spanned! { ident.span() => compile_error!("No attr arg or assignment found") });
-            };
-
+    let result = args.iter().map(|FnArg { ident, ty, mod_ident, indexed }| {
         let static_ty = make_static(ty);
-
         let ty_str = pretty_print_type(ty);
+        let (with_deserialize, deserialize_error) = match deserialize.then(|| deserialize_fn(ty)) {
+            Some(Ok(deserialize_fn)) => (Some(quote!(.with_deserialize(#deserialize_fn))), None),
+            Some(Err(err)) => (None, Some(err.to_compile_error())),
+            None => (None, None),
+        };
+        // The arg of a repeated step takes the item at the index.
+        if let (Some(_), Some(repeat)) = (indexed, step.repeat()) {
+            let bind_consts = bind_consts(story, &format_ident!("B"), repeat);
+            let expr_str = pretty_print_expr(repeat);
+            return Ok(quote! {
+                pub mod #mod_ident {
+                    use super::*;
+                    pub const __NAME: &str = stringify!(#ident);
+                    pub const __TY: &str = #ty_str;
+                    pub const __EXPR: &str = #expr_str;
+                    /// Returns the number of the items.
+                    #[inline]
+                    pub fn len<B: narrative::story::ConstBindings<ConstValues>>() -> usize {
+                        #bind_consts
+                        (#repeat).len()
+                    }
+                    #[inline]
+                    pub fn value<B: narrative::story::ConstBindings<ConstValues>>(index: usize) -> #static_ty {
+                        #bind_consts
+                        (#repeat)[index].clone()
+                    }
+                    pub fn dyn_step_arg<B: narrative::story::ConstBindings<ConstValues>>(index: usize) -> narrative::step::DynStepArg {
+                        narrative::step::DynStepArg::new_repeated(
+                            index,
+                            __NAME,
+                            __TY,
+                            __EXPR,
+                            |index| narrative::value::BoxedValue::new(value::<B>(index)),
+                            |index| narrative::value::BoxedValue::new(StepArg::<B>::new(StepArgInner::#step_ident(#step_ident::#ident(index))))
+                        )#with_deserialize
+                    }
+                    #deserialize_error
+                }
+            });
+        }
+        // An arg without attr arg takes the value of the const with the same name.
+        let (expr, value) = if let Some(expr) = step.find_attr_arg(ident) {
+            let bind_consts = bind_consts(story, &format_ident!("B"), expr);
+            (expr, quote!(#bind_consts #expr))
+        } else if let Some(item) = story.find_const(ident) {
+            (&item.default.1, const_value(item, &format_ident!("B")))
+        } else {
+            return Err(quote_spanned! { ident.span() => compile_error!("No attr arg or assignment found") });
+        };
         let expr_str = pretty_print_expr(expr);
+        // The owned value of a borrowed arg is boxed as is, since its type is not known.
+        let borrowed = step
+            .find_attr_arg(ident)
+            .is_some_and(|value| is_borrowed_value(ty, value));
+        let (value_ty, value, boxed_value) = if borrowed {
+            (
+                quote!(narrative::value::BoxedValue),
+                quote!(narrative::value::BoxedValue::new({ #value })),
+                quote!(value::<B>()),
+            )
+        } else {
+            (
+                quote!(#static_ty),
+                value,
+                quote!(narrative::value::BoxedValue::new(value::<B>())),
+            )
+        };
 
         Ok(quote! {
             pub mod #mod_ident {
@@ -279,16 +411,19 @@ fn generate_arg_impl(story: &ItemStory, step: &StoryStep) -> TokenStream {
                 pub const __TY: &str = #ty_str;
                 pub const __EXPR: &str = #expr_str;
                 #[inline]
-                pub fn value() -> #static_ty {
-                    #expr
+                pub fn value<B: narrative::story::ConstBindings<ConstValues>>() -> #value_ty {
+                    #value
+                }
+                pub fn dyn_step_arg<B: narrative::story::ConstBindings<ConstValues>>() -> narrative::step::DynStepArg {
+                    narrative::step::DynStepArg::new(
+                        __NAME,
+                        __TY,
+                        __EXPR,
+                        || #boxed_value,
+                        || narrative::value::BoxedValue::new(StepArg::<B>::new(StepArgInner::#step_ident(#step_ident::#ident)))
+                    )#with_deserialize
                 }
-                pub const DYN_STEP_ARG: narrative::step::DynStepArg = narrative::step::DynStepArg::new(
-                    __NAME,
-                    __TY,
-                    __EXPR,
-                    || narrative::value::BoxedValue::new(value()),
-                    || narrative::value::BoxedValue::new(#step_ident::#ident)
-                );
+                #deserialize_error
             }
         })
     })
@@ -300,11 +435,14 @@ fn generate_arg_impl(story: &ItemStory, step: &StoryStep) -> TokenStream {
         }
     };
 
+    let cfg_attrs = step.cfg_attrs().collect::<Vec<_>>();
     quote! {
+        #(#cfg_attrs)*
         pub mod #step_ident_args_mod {
             use super::*;
             #(#defs)*
         }
+        #(#cfg_attrs)*
         impl #step_ident {
             #[inline]
             pub(super) fn name(&self) -> &'static str {
@@ -319,11 +457,11 @@ fn generate_arg_impl(story: &ItemStory, step: &StoryStep) -> TokenStream {
                 #expr_arms
             }
             #[inline]
-            pub(super) fn value(&self) -> ArgValue {
+            pub(super) fn value<B: narrative::story::ConstBindings<ConstValues>>(&self) -> ArgValue {
                 #value_arms
             }
             #[inline]
-            pub(super) fn to_dyn(&self) -> narrative::step::DynStepArg {
+            pub(super) fn to_dyn<B: narrative::story::ConstBindings<ConstValues>>(&self) -> narrative::step::DynStepArg {
                 #to_dyn_arms
             }
         }
@@ -348,7 +486,7 @@ mod tests {
                 #step
             }
         };
-        let actual = generate_arg_impl(&story_syntax, &step);
+        let actual = generate_arg_impl(&story_syntax, &step, false);
         let expected = quote! {
             pub mod my_step1_args {
                 use super::*;
@@ -367,11 +505,11 @@ mod tests {
                     unreachable!()
                 }
                 #[inline]
-                pub(super) fn value(&self) -> ArgValue {
+                pub(super) fn value<B: narrative::story::ConstBindings<ConstValues>>(&self) -> ArgValue {
                     unreachable!()
                 }
                 #[inline]
-                pub(super) fn to_dyn(&self) -> narrative::step::DynStepArg {
+                pub(super) fn to_dyn<B: narrative::story::ConstBindings<ConstValues>>(&self) -> narrative::step::DynStepArg {
                     unreachable!()
                 }
             }
@@ -390,7 +528,7 @@ mod tests {
                 #step
             }
         };
-        let actual = generate_arg_impl(&story_syntax, &step);
+        let actual = generate_arg_impl(&story_syntax, &step, false);
         let expected = quote! {
             pub mod my_step1_args {
                 use super::*;
@@ -400,16 +538,18 @@ mod tests {
                     pub const __TY: &str = "&str";
                     pub const __EXPR: &str = "\"ryo\"";
                     #[inline]
-                    pub fn value() -> &'static str {
+                    pub fn value<B: narrative::story::ConstBindings<ConstValues>>() -> &'static str {
                         "ryo"
                     }
-                    pub const DYN_STEP_ARG: narrative::step::DynStepArg = narrative::step::DynStepArg::new(
-                        __NAME,
-                        __TY,
-                        __EXPR,
-                        || narrative::value::BoxedValue::new(value()),
-                        || narrative::value::BoxedValue::new(my_step1::name)
-                    );
+                    pub fn dyn_step_arg<B: narrative::story::ConstBindings<ConstValues>>() -> narrative::step::DynStepArg {
+                        narrative::step::DynStepArg::new(
+                            __NAME,
+                            __TY,
+                            __EXPR,
+                            || narrative::value::BoxedValue::new(value::<B>()),
+                            || narrative::value::BoxedValue::new(StepArg::<B>::new(StepArgInner::my_step1(my_step1::name)))
+                        )
+                    }
                 }
             }
             impl my_step1 {
@@ -432,15 +572,15 @@ mod tests {
                     }
                 }
                 #[inline]
-                pub(super) fn value(&self) -> ArgValue {
+                pub(super) fn value<B: narrative::story::ConstBindings<ConstValues>>(&self) -> ArgValue {
                     match self {
-                        Self::name => ArgValue::my_step1(arg_values::my_step1::name(my_step1_args::mod_name::value())),
+                        Self::name => ArgValue::my_step1(arg_values::my_step1::name(my_step1_args::mod_name::value::<B>())),
                     }
                 }
                 #[inline]
-                pub(super) fn to_dyn(&self) -> narrative::step::DynStepArg {
+                pub(super) fn to_dyn<B: narrative::story::ConstBindings<ConstValues>>(&self) -> narrative::step::DynStepArg {
                     match self {
-                        Self::name => my_step1_args::mod_name::DYN_STEP_ARG,
+                        Self::name => my_step1_args::mod_name::dyn_step_arg::<B>(),
                     }
                 }
             }
@@ -462,7 +602,7 @@ mod tests {
                 #step
             }
         };
-        let actual = generate_arg_impl(&story_syntax, &step);
+        let actual = generate_arg_impl(&story_syntax, &step, false);
         let expected = quote! {
             pub mod my_step1_args {
                 use super::*;
@@ -472,16 +612,18 @@ mod tests {
                     pub const __TY: &str = "UserId";
                     pub const __EXPR: &str = "UserId::new()";
                     #[inline]
-                    pub fn value() -> UserId {
-                        UserId::new()
+                    pub fn value<B: narrative::story::ConstBindings<ConstValues>>() -> UserId {
+                        (B::CONSTS.id)()
+                    }
+                    pub fn dyn_step_arg<B: narrative::story::ConstBindings<ConstValues>>() -> narrative::step::DynStepArg {
+                        narrative::step::DynStepArg::new(
+                            __NAME,
+                            __TY,
+                            __EXPR,
+                            || narrative::value::BoxedValue::new(value::<B>()),
+                            || narrative::value::BoxedValue::new(StepArg::<B>::new(StepArgInner::my_step1(my_step1::id)))
+                        )
                     }
-                    pub const DYN_STEP_ARG: narrative::step::DynStepArg = narrative::step::DynStepArg::new(
-                        __NAME,
-                        __TY,
-                        __EXPR,
-                        || narrative::value::BoxedValue::new(value()),
-                        || narrative::value::BoxedValue::new(my_step1::id)
-                    );
                 }
                 pub mod mod_name {
                     use super::*;
@@ -489,16 +631,18 @@ mod tests {
                     pub const __TY: &str = "&str";
                     pub const __EXPR: &str = "\"Alice\"";
                     #[inline]
-                    pub fn value() -> &'static str {
-                        "Alice"
+                    pub fn value<B: narrative::story::ConstBindings<ConstValues>>() -> &'static str {
+                        (B::CONSTS.name)()
+                    }
+                    pub fn dyn_step_arg<B: narrative::story::ConstBindings<ConstValues>>() -> narrative::step::DynStepArg {
+                        narrative::step::DynStepArg::new(
+                            __NAME,
+                            __TY,
+                            __EXPR,
+                            || narrative::value::BoxedValue::new(value::<B>()),
+                            || narrative::value::BoxedValue::new(StepArg::<B>::new(StepArgInner::my_step1(my_step1::name)))
+                        )
                     }
-                    pub const DYN_STEP_ARG: narrative::step::DynStepArg = narrative::step::DynStepArg::new(
-                        __NAME,
-                        __TY,
-                        __EXPR,
-                        || narrative::value::BoxedValue::new(value()),
-                        || narrative::value::BoxedValue::new(my_step1::name)
-                    );
                 }
             }
             impl my_step1 {
@@ -524,17 +668,17 @@ mod tests {
                     }
                 }
                 #[inline]
-                pub(super) fn value(&self) -> ArgValue {
+                pub(super) fn value<B: narrative::story::ConstBindings<ConstValues>>(&self) -> ArgValue {
                     match self {
-                        Self::id => ArgValue::my_step1(arg_values::my_step1::id(my_step1_args::mod_id::value())),
-                        Self::name => ArgValue::my_step1(arg_values::my_step1::name(my_step1_args::mod_name::value())),
+                        Self::id => ArgValue::my_step1(arg_values::my_step1::id(my_step1_args::mod_id::value::<B>())),
+                        Self::name => ArgValue::my_step1(arg_values::my_step1::name(my_step1_args::mod_name::value::<B>())),
                     }
                 }
                 #[inline]
-                pub(super) fn to_dyn(&self) -> narrative::step::DynStepArg {
+                pub(super) fn to_dyn<B: narrative::story::ConstBindings<ConstValues>>(&self) -> narrative::step::DynStepArg {
                     match self {
-                        Self::id => my_step1_args::mod_id::DYN_STEP_ARG,
-                        Self::name => my_step1_args::mod_name::DYN_STEP_ARG,
+                        Self::id => my_step1_args::mod_id::dyn_step_arg::<B>(),
+                        Self::name => my_step1_args::mod_name::dyn_step_arg::<B>(),
                     }
                 }
             }
@@ -556,7 +700,7 @@ mod tests {
                 #step
             }
         };
-        let actual = generate_arg_impl(&story_syntax, &step);
+        let actual = generate_arg_impl(&story_syntax, &step, false);
         let expected = quote! {
             pub mod my_step1_args {
                 use super::*;
@@ -566,16 +710,18 @@ mod tests {
                     pub const __TY: &str = "UserId";
                     pub const __EXPR: &str = "UserId::new()";
                     #[inline]
-                    pub fn value() -> UserId {
-                        UserId::new()
+                    pub fn value<B: narrative::story::ConstBindings<ConstValues>>() -> UserId {
+                        (B::CONSTS.id)()
+                    }
+                    pub fn dyn_step_arg<B: narrative::story::ConstBindings<ConstValues>>() -> narrative::step::DynStepArg {
+                        narrative::step::DynStepArg::new(
+                            __NAME,
+                            __TY,
+                            __EXPR,
+                            || narrative::value::BoxedValue::new(value::<B>()),
+                            || narrative::value::BoxedValue::new(StepArg::<B>::new(StepArgInner::my_step1(my_step1::id)))
+                        )
                     }
-                    pub const DYN_STEP_ARG: narrative::step::DynStepArg = narrative::step::DynStepArg::new(
-                        __NAME,
-                        __TY,
-                        __EXPR,
-                        || narrative::value::BoxedValue::new(value()),
-                        || narrative::value::BoxedValue::new(my_step1::id)
-                    );
                 }
                 pub mod mod_name {
                     use super::*;
@@ -583,16 +729,18 @@ mod tests {
                     pub const __TY: &str = "&str";
                     pub const __EXPR: &str = "\"Bob\"";
                     #[inline]
-                    pub fn value() -> &'static str {
+                    pub fn value<B: narrative::story::ConstBindings<ConstValues>>() -> &'static str {
                         "Bob"
                     }
-                    pub const DYN_STEP_ARG: narrative::step::DynStepArg = narrative::step::DynStepArg::new(
-                        __NAME,
-                        __TY,
-                        __EXPR,
-                        || narrative::value::BoxedValue::new(value()),
-                        || narrative::value::BoxedValue::new(my_step1::name)
-                    );
+                    pub fn dyn_step_arg<B: narrative::story::ConstBindings<ConstValues>>() -> narrative::step::DynStepArg {
+                        narrative::step::DynStepArg::new(
+                            __NAME,
+                            __TY,
+                            __EXPR,
+                            || narrative::value::BoxedValue::new(value::<B>()),
+                            || narrative::value::BoxedValue::new(StepArg::<B>::new(StepArgInner::my_step1(my_step1::name)))
+                        )
+                    }
                 }
             }
             impl my_step1 {
@@ -618,17 +766,17 @@ mod tests {
                     }
                 }
                 #[inline]
-                pub(super) fn value(&self) -> ArgValue {
+                pub(super) fn value<B: narrative::story::ConstBindings<ConstValues>>(&self) -> ArgValue {
                     match self {
-                        Self::id => ArgValue::my_step1(arg_values::my_step1::id(my_step1_args::mod_id::value())),
-                        Self::name => ArgValue::my_step1(arg_values::my_step1::name(my_step1_args::mod_name::value())),
+                        Self::id => ArgValue::my_step1(arg_values::my_step1::id(my_step1_args::mod_id::value::<B>())),
+                        Self::name => ArgValue::my_step1(arg_values::my_step1::name(my_step1_args::mod_name::value::<B>())),
                     }
                 }
                 #[inline]
-                pub(super) fn to_dyn(&self) -> narrative::step::DynStepArg {
+                pub(super) fn to_dyn<B: narrative::story::ConstBindings<ConstValues>>(&self) -> narrative::step::DynStepArg {
                     match self {
-                        Self::id => my_step1_args::mod_id::DYN_STEP_ARG,
-                        Self::name => my_step1_args::mod_name::DYN_STEP_ARG,
+                        Self::id => my_step1_args::mod_id::dyn_step_arg::<B>(),
+                        Self::name => my_step1_args::mod_name::dyn_step_arg::<B>(),
                     }
                 }
             }
@@ -665,6 +813,30 @@ mod tests {
         assert_eq!(actual.to_string(), expected.to_string());
     }
 
+    #[test]
+    fn test_borrowed_value() {
+        let step: StoryStep = parse_quote! {
+            #[step("list {users:?}", users = vec![UserId::new()], names = &["ryo"])]
+            fn list(users: &[UserId], names: &[&str]);
+        };
+        let story_syntax = syn::parse_quote! {
+            trait User {
+                #step
+            }
+        };
+        let actual = generate_arg_values(&step).to_string();
+        assert!(actual.contains(&quote!(users(narrative::value::BoxedValue)).to_string()));
+        assert!(actual.contains(&quote!(names(&'static [&'static str])).to_string()));
+        let actual = generate_arg_impl(&story_syntax, &step, false).to_string();
+        let value = quote! {
+            pub fn value<B: narrative::story::ConstBindings<ConstValues>>() -> narrative::value::BoxedValue {
+                narrative::value::BoxedValue::new({ vec![UserId::new()] })
+            }
+        };
+        assert!(actual.contains(&value.to_string()));
+        assert!(actual.contains(&quote!(|| value::<B>(),).to_string()));
+    }
+
     #[test]
     fn test_const_usage() {
         let step: StoryStep = parse_quote! {
@@ -677,7 +849,7 @@ mod tests {
                 #step
             }
         };
-        let actual = generate_arg_impl(&story_syntax, &step);
+        let actual = generate_arg_impl(&story_syntax, &step, false);
         let expected = quote! {
             pub mod step_with_const_args {
                 use super::*;
@@ -687,16 +859,19 @@ mod tests {
                     pub const __TY: &str = "i32";
                     pub const __EXPR: &str = "MY_CONST * 2";
                     #[inline]
-                    pub fn value() -> i32 {
+                    pub fn value<B: narrative::story::ConstBindings<ConstValues>>() -> i32 {
+                        let MY_CONST = (B::CONSTS.MY_CONST)();
                         MY_CONST * 2
                     }
-                    pub const DYN_STEP_ARG: narrative::step::DynStepArg = narrative::step::DynStepArg::new(
-                        __NAME,
-                        __TY,
-                        __EXPR,
-                        || narrative::value::BoxedValue::new(value()),
-                        || narrative::value::BoxedValue::new(step_with_const::val)
-                    );
+                    pub fn dyn_step_arg<B: narrative::story::ConstBindings<ConstValues>>() -> narrative::step::DynStepArg {
+                        narrative::step::DynStepArg::new(
+                            __NAME,
+                            __TY,
+                            __EXPR,
+                            || narrative::value::BoxedValue::new(value::<B>()),
+                            || narrative::value::BoxedValue::new(StepArg::<B>::new(StepArgInner::step_with_const(step_with_const::val)))
+                        )
+                    }
                 }
             }
             impl step_with_const {
@@ -719,15 +894,15 @@ mod tests {
                     }
                 }
                 #[inline]
-                pub(super) fn value(&self) -> ArgValue {
+                pub(super) fn value<B: narrative::story::ConstBindings<ConstValues>>(&self) -> ArgValue {
                     match self {
-                        Self::val => ArgValue::step_with_const(arg_values::step_with_const::val(step_with_const_args::mod_val::value())),
+                        Self::val => ArgValue::step_with_const(arg_values::step_with_const::val(step_with_const_args::mod_val::value::<B>())),
                     }
                 }
                 #[inline]
-                pub(super) fn to_dyn(&self) -> narrative::step::DynStepArg {
+                pub(super) fn to_dyn<B: narrative::story::ConstBindings<ConstValues>>(&self) -> narrative::step::DynStepArg {
                     match self {
-                        Self::val => step_with_const_args::mod_val::DYN_STEP_ARG,
+                        Self::val => step_with_const_args::mod_val::dyn_step_arg::<B>(),
                     }
                 }
             }
@@ -747,7 +922,7 @@ mod tests {
                 #step
             }
         };
-        let actual = generate_arg_impl(&story_syntax, &step);
+        let actual = generate_arg_impl(&story_syntax, &step, false);
         let expected = quote! {
             pub mod step_with_const_args {
                 use super::*;
@@ -757,16 +932,19 @@ mod tests {
                     pub const __TY: &str = "String";
                     pub const __EXPR: &str = "format!(\"const: {MY_CONST}\")";
                     #[inline]
-                    pub fn value() -> String {
+                    pub fn value<B: narrative::story::ConstBindings<ConstValues>>() -> String {
+                        let MY_CONST = (B::CONSTS.MY_CONST)();
                         format!("const: {MY_CONST}")
                     }
-                    pub const DYN_STEP_ARG: narrative::step::DynStepArg = narrative::step::DynStepArg::new(
-                        __NAME,
-                        __TY,
-                        __EXPR,
-                        || narrative::value::BoxedValue::new(value()),
-                        || narrative::value::BoxedValue::new(step_with_const::val)
-                    );
+                    pub fn dyn_step_arg<B: narrative::story::ConstBindings<ConstValues>>() -> narrative::step::DynStepArg {
+                        narrative::step::DynStepArg::new(
+                            __NAME,
+                            __TY,
+                            __EXPR,
+                            || narrative::value::BoxedValue::new(value::<B>()),
+                            || narrative::value::BoxedValue::new(StepArg::<B>::new(StepArgInner::step_with_const(step_with_const::val)))
+                        )
+                    }
                 }
             }
             impl step_with_const {
@@ -789,19 +967,108 @@ mod tests {
                     }
                 }
                 #[inline]
-                pub(super) fn value(&self) -> ArgValue {
+                pub(super) fn value<B: narrative::story::ConstBindings<ConstValues>>(&self) -> ArgValue {
+                    match self {
+                        Self::val => ArgValue::step_with_const(arg_values::step_with_const::val(step_with_const_args::mod_val::value::<B>())),
+                    }
+                }
+                #[inline]
+                pub(super) fn to_dyn<B: narrative::story::ConstBindings<ConstValues>>(&self) -> narrative::step::DynStepArg {
+                    match self {
+                        Self::val => step_with_const_args::mod_val::dyn_step_arg::<B>(),
+                    }
+                }
+            }
+        };
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let step: StoryStep = parse_quote! {
+            #[step("Step 1", id = UserId::new())]
+            fn my_step1(id: UserId);
+        };
+        let story_syntax = syn::parse_quote! {
+            trait User {
+                #step
+            }
+        };
+        let actual = generate_arg_impl(&story_syntax, &step, true);
+        let expected = quote! {
+            pub mod my_step1_args {
+                use super::*;
+                pub mod mod_id {
+                    use super::*;
+                    pub const __NAME: &str = stringify!(id);
+                    pub const __TY: &str = "UserId";
+                    pub const __EXPR: &str = "UserId::new()";
+                    #[inline]
+                    pub fn value<B: narrative::story::ConstBindings<ConstValues>>() -> UserId {
+                        UserId::new()
+                    }
+                    pub fn dyn_step_arg<B: narrative::story::ConstBindings<ConstValues>>() -> narrative::step::DynStepArg {
+                        narrative::step::DynStepArg::new(
+                            __NAME,
+                            __TY,
+                            __EXPR,
+                            || narrative::value::BoxedValue::new(value::<B>()),
+                            || narrative::value::BoxedValue::new(StepArg::<B>::new(StepArgInner::my_step1(my_step1::id)))
+                        ).with_deserialize(narrative::value::deserialize_boxed::<UserId>)
+                    }
+                }
+            }
+            impl my_step1 {
+                #[inline]
+                pub(super) fn name(&self) -> &'static str {
                     match self {
-                        Self::val => ArgValue::step_with_const(arg_values::step_with_const::val(step_with_const_args::mod_val::value())),
+                        Self::id => my_step1_args::mod_id::__NAME,
                     }
                 }
                 #[inline]
-                pub(super) fn to_dyn(&self) -> narrative::step::DynStepArg {
+                pub(super) fn ty(&self) -> &'static str {
                     match self {
-                        Self::val => step_with_const_args::mod_val::DYN_STEP_ARG,
+                        Self::id => my_step1_args::mod_id::__TY,
+                    }
+                }
+                #[inline]
+                pub(super) fn expr(&self) -> &'static str {
+                    match self {
+                        Self::id => my_step1_args::mod_id::__EXPR,
+                    }
+                }
+                #[inline]
+                pub(super) fn value<B: narrative::story::ConstBindings<ConstValues>>(&self) -> ArgValue {
+                    match self {
+                        Self::id => ArgValue::my_step1(arg_values::my_step1::id(my_step1_args::mod_id::value::<B>())),
+                    }
+                }
+                #[inline]
+                pub(super) fn to_dyn<B: narrative::story::ConstBindings<ConstValues>>(&self) -> narrative::step::DynStepArg {
+                    match self {
+                        Self::id => my_step1_args::mod_id::dyn_step_arg::<B>(),
                     }
                 }
             }
         };
         assert_eq!(actual.to_string(), expected.to_string());
     }
+
+    #[test]
+    fn test_deserialize_borrowed() {
+        let step: StoryStep = parse_quote! {
+            #[step("Step 1", names = vec!["ryo"])]
+            fn my_step1(names: Vec<&str>);
+        };
+        let story_syntax = syn::parse_quote! {
+            trait User {
+                #step
+            }
+        };
+        let actual = generate_arg_impl(&story_syntax, &step, true).to_string();
+        assert!(actual.contains(
+            "compile_error ! { \"`&str` is borrowed and cannot be deserialized, use an owned type like `String` instead of `&str`\" }"
+        ), "{actual}");
+        assert!(!actual.contains("with_deserialize"));
+    }
 }
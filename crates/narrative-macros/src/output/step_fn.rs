@@ -1,10 +1,15 @@
 use proc_macro2::TokenStream;
 use quote::quote;
 
-use crate::{Asyncness, item_story::StoryStep};
-
-/// This does not emits `;` or body.
-pub(crate) fn generate(step: &StoryStep, asyncness: Asyncness) -> TokenStream {
+use crate::{Asyncness, item_story::StoryStep, push_generic_arg};
+
+/// This does not emits `;` or body. With `convert_errors`, sub story steps accept environments
+/// whose errors can be converted into the error of the story.
+pub(crate) fn generate(
+    step: &StoryStep,
+    asyncness: Asyncness,
+    convert_errors: bool,
+) -> TokenStream {
     let fn_name = &step.inner.sig.ident;
     let other_attrs = &step.other_attrs;
     let inputs_tokens = step
@@ -22,17 +27,21 @@ pub(crate) fn generate(step: &StoryStep, asyncness: Asyncness) -> TokenStream {
 
     // Check if this is a sub-story step
     if let Some(sub_story_path) = step.sub_story_path() {
-        let path = sub_story_path.path();
-        let async_path = sub_story_path.async_path();
         // Generate different outputs based on asyncness
         let trait_name = match asyncness {
-            Asyncness::Sync => quote!(#path),
-            Asyncness::Async => quote!(#async_path),
+            Asyncness::Sync => sub_story_path.path().clone(),
+            Asyncness::Async => sub_story_path.async_path(),
+        };
+        let error = if convert_errors {
+            syn::parse_quote!(Error: Into<Self::Error>)
+        } else {
+            syn::parse_quote!(Error = Self::Error)
         };
+        let trait_bound = push_generic_arg(&trait_name, error);
 
         quote! {
             #(#other_attrs)*
-            fn #fn_name(&mut self #(,#inputs_tokens)*) -> Result<impl #trait_name<Error = Self::Error> #bounds, Self::Error>
+            fn #fn_name(&mut self #(,#inputs_tokens)*) -> Result<impl #trait_bound #bounds, Self::Error>
         }
     } else {
         // Regular step function
@@ -61,7 +70,7 @@ mod tests {
             #[step("Step 1")]
             fn step1();
         };
-        let actual = generate(&item_story, Asyncness::Sync);
+        let actual = generate(&item_story, Asyncness::Sync, false);
         let expected = quote! {
             fn step1(&mut self) -> Result<(), Self::Error>
         };
@@ -74,7 +83,7 @@ mod tests {
             #[step("Step 1")]
             fn step1(a: i32, b: i32);
         };
-        let actual = generate(&item_story, Asyncness::Sync);
+        let actual = generate(&item_story, Asyncness::Sync, false);
         let expected = quote! {
             fn step1(&mut self, a: i32, b: i32) -> Result<(), Self::Error>
         };
@@ -87,7 +96,7 @@ mod tests {
             #[step("Step 1")]
             fn step1(&self, a: i32, b: i32);
         };
-        let actual = generate(&item_story, Asyncness::Sync);
+        let actual = generate(&item_story, Asyncness::Sync, false);
         let expected = quote! {
             fn step1(&mut self, a: i32, b: i32) -> Result<(), Self::Error>
         };
@@ -100,7 +109,7 @@ mod tests {
             #[step("Step 1")]
             fn step1();
         };
-        let actual = generate(&item_story, Asyncness::Async);
+        let actual = generate(&item_story, Asyncness::Async, false);
         let expected = quote! {
             fn step1(&mut self) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send
         };
@@ -113,20 +122,33 @@ mod tests {
             #[step(story: SubStory, "do sub story")]
             fn step_with_sub();
         };
-        let actual = generate(&item_story, Asyncness::Sync);
+        let actual = generate(&item_story, Asyncness::Sync, false);
         let expected = quote! {
             fn step_with_sub(&mut self) -> Result<impl SubStory<Error = Self::Error>, Self::Error>
         };
         assert_eq!(actual.to_string(), expected.to_string());
     }
 
+    #[test]
+    fn test_generate_substory_step_fn_converting_errors() {
+        let item_story = syn::parse_quote! {
+            #[step(story: SubStory, "do sub story")]
+            fn step_with_sub();
+        };
+        let actual = generate(&item_story, Asyncness::Sync, true);
+        let expected = quote! {
+            fn step_with_sub(&mut self) -> Result<impl SubStory<Error: Into<Self::Error> >, Self::Error>
+        };
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
     #[test]
     fn test_generate_substory_step_fn_with_inputs() {
         let item_story = syn::parse_quote! {
             #[step(story: SubStory, "do sub story")]
             fn step_with_sub(arg: i32);
         };
-        let actual = generate(&item_story, Asyncness::Sync);
+        let actual = generate(&item_story, Asyncness::Sync, false);
         let expected = quote! {
             fn step_with_sub(&mut self, arg: i32) -> Result<impl SubStory<Error = Self::Error>, Self::Error>
         };
@@ -139,13 +161,26 @@ mod tests {
             #[step(story: SubStory, "do sub story")]
             fn step_with_sub();
         };
-        let actual = generate(&item_story, Asyncness::Async);
+        let actual = generate(&item_story, Asyncness::Async, false);
         let expected = quote! {
             fn step_with_sub(&mut self) -> Result<impl AsyncSubStory<Error = Self::Error> + Send, Self::Error>
         };
         assert_eq!(actual.to_string(), expected.to_string());
     }
 
+    #[test]
+    fn test_generate_generic_substory_step_fn_async() {
+        let item_story = syn::parse_quote! {
+            #[step(story: sub::SubStory<T>, "do sub story")]
+            fn step_with_sub();
+        };
+        let actual = generate(&item_story, Asyncness::Async, false);
+        let expected = quote! {
+            fn step_with_sub(&mut self) -> Result<impl sub::AsyncSubStory<T, Error = Self::Error> + Send, Self::Error>
+        };
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
     #[test]
     fn test_generate_step_fn_with_other_attrs() {
         let item_story = syn::parse_quote! {
@@ -153,7 +188,7 @@ mod tests {
             #[step("Step 1")]
             fn step1();
         };
-        let actual = generate(&item_story, Asyncness::Sync);
+        let actual = generate(&item_story, Asyncness::Sync, false);
         let expected = quote! {
             /// This is a step
             fn step1(&mut self) -> Result<(), Self::Error>
@@ -168,7 +203,7 @@ mod tests {
             #[step("Step 1")]
             fn step1();
         };
-        let actual = generate(&item_story, Asyncness::Async);
+        let actual = generate(&item_story, Asyncness::Async, false);
         let expected = quote! {
             /// This is a step
             fn step1(&mut self) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send
@@ -4,72 +4,254 @@ use proc_macro2::TokenStream;
 use quote::{ToTokens, format_ident, quote};
 
 use crate::{
+    collect_format_args, expr_path, is_borrowed_value,
     item_story::{ItemStory, StoryStep, story_const::StoryConst},
-    output::MatchArms,
+    output::{MatchArms, bind_consts},
+    push_generic_arg,
+    step_attr_syntax::StepAttrArgs,
+    story_attr_syntax::StoryAttr,
+    template::{ArgFormat, TemplateSegment, is_rich, parse_template},
 };
 
-pub(crate) fn generate(story: &ItemStory) -> TokenStream {
+pub(crate) fn generate(attr: &StoryAttr, story: &ItemStory) -> TokenStream {
     let story_ident = &story.ident;
     let async_story_ident = format_ident!("Async{}", story_ident);
-    let step_names = story.steps().map(|step| &step.inner.sig.ident);
+    let b = story.internal_param("B");
+    let env = story.internal_param("T");
+    let impl_params = story.impl_params();
+    let type_params = story.type_params();
+    let type_args = story.type_args();
+    let where_clause = story.where_clause();
+    let phantom_data = story.phantom_data(&b);
+    // A story with a custom error is only implemented with the error, so the bounds of the error
+    // are known to hold in the sub stories.
+    let (error_param, error, story_bound, async_story_bound) = if let Some(error) = &attr.error {
+        (
+            None,
+            quote!(#error),
+            story.trait_bound_with_error(story_ident, error),
+            story.trait_bound_with_error(&async_story_ident, error),
+        )
+    } else if story.has_lifetimes() {
+        let error = story.internal_param("E");
+        (
+            Some(quote!(#error,)),
+            quote!(#error),
+            story.trait_bound_with_error(story_ident, &error),
+            story.trait_bound_with_error(&async_story_ident, &error),
+        )
+    } else {
+        (
+            None,
+            quote!(#env::Error),
+            story.trait_bound(story_ident),
+            story.trait_bound(&async_story_ident),
+        )
+    };
+    // Repeated steps hold the index of the item.
+    let step_variants = story.steps().map(|step| {
+        let ident = &step.inner.sig.ident;
+        let cfg_attrs = step.cfg_attrs();
+        if story.repeat_item(step).is_some() {
+            quote!(#(#cfg_attrs)* #ident(usize))
+        } else {
+            quote!(#(#cfg_attrs)* #ident)
+        }
+    });
+    // Steps of the parent story are wrapped in a variant named after the parent.
+    let parent = story.parent.as_ref().map(|parent| {
+        let variant = parent.variant_ident();
+        let context_path = parent.context_path();
+        (
+            variant,
+            quote!(<#context_path as narrative::story::StoryContext>::Step),
+        )
+    });
+    let parent_variant = parent
+        .as_ref()
+        .map(|(variant, step_ty)| quote!(#variant(#step_ty),));
+    let parent_arm =
+        |arm: fn(&syn::Ident) -> TokenStream| parent.as_ref().map(|(variant, _)| arm(variant));
     let steps: Vec<_> = story
         .steps()
-        .map(|step| generate_step(story, step))
+        .map(|step| generate_step(story, step, attr.converts_errors()))
         .collect();
+    // The pattern of the variant, and the index passed to the step functions if repeated.
+    // The pattern starts with the cfg attrs of the step, so the arm is gated with the variant.
+    let pattern = |StepSegments {
+                       ident,
+                       repeated,
+                       cfg,
+                       ..
+                   }: &StepSegments| {
+        if *repeated {
+            (quote!(#cfg StepInner::#ident(index)), Some(quote!(*index)))
+        } else {
+            (quote!(#cfg StepInner::#ident), None)
+        }
+    };
+    let wildcard_pattern = |StepSegments {
+                                ident,
+                                repeated,
+                                cfg,
+                                ..
+                            }: &StepSegments| {
+        let index = repeated.then(|| quote!((_)));
+        quote!(#cfg StepInner::#ident #index)
+    };
     let step_texts: MatchArms = steps
         .iter()
-        .map(
-            |StepSegments {
-                 ident, mod_ident, ..
-             }| quote!(Self::#ident => steps::#mod_ident::step_text(),),
-        )
-        .collect();
+        .map(|segments| {
+            let mod_ident = &segments.mod_ident;
+            let (pattern, index) = pattern(segments);
+            quote!(#pattern => steps::#mod_ident::step_text::<#b>(#index),)
+        })
+        .chain(parent_arm(
+            |variant| quote!(StepInner::#variant(step) => narrative::step::Step::step_text(step),),
+        ))
+        .collect::<MatchArms>()
+        .match_target(quote!(&self.0));
+    let step_texts_in: MatchArms = steps
+        .iter()
+        .map(|segments| {
+            let mod_ident = &segments.mod_ident;
+            let (pattern, index) = pattern(segments);
+            let index = index.map(|index| quote!(#index,));
+            quote!(#pattern => steps::#mod_ident::step_text_in::<#b>(#index locale),)
+        })
+        .chain(parent_arm(|variant| {
+            quote!(StepInner::#variant(step) => narrative::step::Step::step_text_in(step, locale),)
+        }))
+        .collect::<MatchArms>()
+        .match_target(quote!(&self.0));
+    let step_segments: MatchArms = steps
+        .iter()
+        .map(|segments| {
+            let mod_ident = &segments.mod_ident;
+            let (pattern, index) = pattern(segments);
+            quote!(#pattern => steps::#mod_ident::step_segments::<#b>(#index),)
+        })
+        .chain(parent_arm(|variant| {
+            quote!(StepInner::#variant(step) => narrative::step::Step::step_segments(step),)
+        }))
+        .collect::<MatchArms>()
+        .match_target(quote!(&self.0));
+    let text_patterns: MatchArms = steps
+        .iter()
+        .map(|segments| {
+            let mod_ident = &segments.mod_ident;
+            let pattern = wildcard_pattern(segments);
+            quote!(#pattern => steps::#mod_ident::__TEXT_PATTERN,)
+        })
+        .chain(parent_arm(|variant| {
+            quote!(StepInner::#variant(step) => narrative::step::Step::text_pattern(step),)
+        }))
+        .collect::<MatchArms>()
+        .match_target(quote!(&self.0));
     let step_idents: MatchArms = steps
         .iter()
-        .map(
-            |StepSegments {
-                 ident, mod_ident, ..
-             }| quote!(Self::#ident => steps::#mod_ident::__STEP_ID,),
-        )
-        .collect();
+        .map(|segments| {
+            let mod_ident = &segments.mod_ident;
+            let pattern = wildcard_pattern(segments);
+            quote!(#pattern => steps::#mod_ident::__STEP_ID,)
+        })
+        .chain(parent_arm(
+            |variant| quote!(StepInner::#variant(step) => narrative::step::Step::step_id(step),),
+        ))
+        .collect::<MatchArms>()
+        .match_target(quote!(&self.0));
+    // The args of repeated steps are not static, so all args are collected into a vec then.
+    let has_repeats = steps.iter().any(|segments| segments.repeated);
     let step_args = steps
         .iter()
-        .map(
-            |StepSegments {
-                 ident, mod_ident, ..
-             }| quote!(Self::#ident => steps::#mod_ident::__ARGS.iter().copied(),),
-        )
+        .map(|segments| {
+            let mod_ident = &segments.mod_ident;
+            let (pattern, index) = pattern(segments);
+            if index.is_some() {
+                quote!(#pattern => steps::#mod_ident::__args(#index).to_vec(),)
+            } else if has_repeats {
+                quote!(#pattern => steps::#mod_ident::__ARGS.to_vec(),)
+            } else {
+                quote!(#pattern => &steps::#mod_ident::__ARGS,)
+            }
+        })
+        .chain(parent_arm(|variant| {
+            quote!(StepInner::#variant(step) => return narrative::either::Either::Right(narrative::step::Step::args(step)),)
+        }))
         .collect::<MatchArms>()
-        .cast_as(quote!(std::iter::Empty<StepArg>));
+        .match_target(quote!(&self.0));
+    // The iterator of own steps is unreachable if all steps are of the parent story.
+    let allow_unreachable = parent.is_some().then(|| quote!(#[allow(unreachable_code)]));
+    let (args_ty, args_iter) = if has_repeats {
+        (quote!(Vec<StepArgInner>), quote!(args.into_iter()))
+    } else {
+        (
+            quote!(&'static [StepArgInner]),
+            quote!(args.iter().copied()),
+        )
+    };
+    let step_args = if steps.is_empty() && parent.is_none() {
+        quote!(std::iter::empty::<StepArg<#b>>())
+    } else if parent.is_some() {
+        quote! {
+            let args: #args_ty = #step_args;
+            narrative::either::Either::Left(#args_iter.map(StepArg::<#b>::new))
+        }
+    } else {
+        quote! {
+            let args: #args_ty = #step_args;
+            #args_iter.map(StepArg::<#b>::new)
+        }
+    };
     let step_runs: MatchArms = steps
         .iter()
-        .map(|StepSegments { ident, run, .. }| quote!(Self::#ident => { #run }))
-        .collect();
+        .map(|segments| {
+            let run = &segments.run;
+            let (pattern, _) = pattern(segments);
+            quote!(#pattern => { #run })
+        })
+        .chain(parent_arm(|variant| {
+            quote!(StepInner::#variant(step) => narrative::step::Run::run_with_runner(step, story, runner),)
+        }))
+        .collect::<MatchArms>()
+        .match_target(quote!(&self.0));
     let step_runs_async: MatchArms = steps
         .iter()
-        .map(
-            |StepSegments {
-                 ident, run_async, ..
-             }| quote!(Self::#ident => { #run_async }),
-        )
-        .collect();
+        .map(|segments| {
+            let run_async = &segments.run_async;
+            let (pattern, _) = pattern(segments);
+            quote!(#pattern => { #run_async })
+        })
+        .chain(parent_arm(|variant| {
+            quote!(StepInner::#variant(step) => narrative::step::RunAsync::run_with_runner_async(step, story, runner).await,)
+        }))
+        .collect::<MatchArms>()
+        .match_target(quote!(&self.0));
     let step_stories = steps
         .iter()
-        .map(
-            |StepSegments {
-                 ident, mod_ident, ..
-             }| quote!(Self::#ident => steps::#mod_ident::dyn_nested_story(),),
-        )
+        .map(|segments| {
+            let mod_ident = &segments.mod_ident;
+            let pattern = wildcard_pattern(segments);
+            quote!(#pattern => steps::#mod_ident::dyn_nested_story::<#type_args #b>(),)
+        })
+        .chain(parent_arm(
+            |variant| quote!(StepInner::#variant(step) => step.to_dyn().dyn_nested_story(),),
+        ))
         .collect::<MatchArms>()
+        .match_target(quote!(&self.0))
         .cast_as(quote!(Option<narrative::story::DynStoryContext>));
     let to_dyn_arms = steps
         .iter()
-        .map(
-            |StepSegments {
-                 ident, mod_ident, ..
-             }| quote!(Self::#ident => steps::#mod_ident::DYN_STEP,),
-        )
-        .collect::<MatchArms>();
+        .map(|segments| {
+            let mod_ident = &segments.mod_ident;
+            let (pattern, index) = pattern(segments);
+            quote!(#pattern => steps::#mod_ident::dyn_step::<#type_args #b>(#index),)
+        })
+        .chain(parent_arm(
+            |variant| quote!(StepInner::#variant(step) => step.to_dyn(),),
+        ))
+        .collect::<MatchArms>()
+        .match_target(quote!(&self.0));
     let steps_def = steps
         .iter()
         .map(|StepSegments { step_def, .. }| step_def)
@@ -83,60 +265,93 @@ pub(crate) fn generate(story: &ItemStory) -> TokenStream {
 
         #[derive(Clone, Copy)]
         #[allow(non_camel_case_types)]
-        pub enum Step {
-            #(#step_names),*
+        enum StepInner {
+            #parent_variant
+            #(#step_variants),*
+        }
+
+        pub struct Step<#type_params #b = narrative::story::DefaultBindings>(StepInner, #phantom_data);
+
+        impl<#impl_params #b> Step<#type_args #b> #where_clause {
+            const fn new(inner: StepInner) -> Self {
+                Self(inner, std::marker::PhantomData)
+            }
         }
-        impl narrative::step::Step for Step {
+
+        impl<#impl_params #b> Clone for Step<#type_args #b> #where_clause {
+            #[inline]
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl<#impl_params #b> Copy for Step<#type_args #b> #where_clause {}
+
+        impl<#impl_params #b: narrative::story::ConstBindings<ConstValues>> narrative::step::Step for Step<#type_args #b> #where_clause {
             #[inline]
             fn step_text(&self) -> String {
                 #step_texts
             }
             #[inline]
+            #[allow(unused_variables)]
+            fn step_text_in(&self, locale: &str) -> Option<String> {
+                #step_texts_in
+            }
+            #[inline]
+            fn step_segments(&self) -> Vec<narrative::text::StepTextSegment> {
+                #step_segments
+            }
+            #[inline]
+            fn text_pattern(&self) -> &'static [narrative::text::PatternSegment] {
+                #text_patterns
+            }
+            #[inline]
             fn step_id(&self) -> &'static str {
                 #step_idents
             }
             #[inline]
+            #allow_unreachable
             fn args(&self) -> impl Iterator<Item = impl narrative::step::StepArg + 'static> + 'static {
                 #step_args
             }
             #[inline]
             fn story(&self) -> impl narrative::story::StoryContext<Step = Self> + 'static {
-                StoryContext::default()
+                StoryContext::<#type_args #b>::new()
             }
             #[inline]
             fn nested_story(&self) -> Option<impl narrative::story::StoryContext + Send + 'static> {
                 #step_stories
             }
         }
-        impl Step {
+        impl<#impl_params #b: narrative::story::ConstBindings<ConstValues>> Step<#type_args #b> #where_clause {
             pub fn to_dyn(&self) -> narrative::step::DynStep {
                 #to_dyn_arms
             }
         }
 
-        impl <T: #story_ident> narrative::step::Run<T, T::Error> for Step {
+        impl <#impl_params #error_param #b: narrative::story::ConstBindings<ConstValues>, #env: #story_bound> narrative::step::Run<#env, #error> for Step<#type_args #b> #where_clause {
             #[inline]
-            fn run(&self, story: &mut T) -> Result<(), T::Error> {
+            fn run(&self, story: &mut #env) -> Result<(), #error> {
                 use narrative::runner::StoryRunner as _;
                 let mut runner = narrative::runner::DefaultStoryRunner;
                 self.run_with_runner(story, &mut runner)
             }
             #[inline]
-            fn run_with_runner(&self, story: &mut T, runner: &mut impl narrative::runner::StoryRunner<T::Error>) -> Result<(), T::Error> {
+            fn run_with_runner(&self, story: &mut #env, runner: &mut impl narrative::runner::StoryRunner<#error>) -> Result<(), #error> {
                 use narrative::runner::StoryRunner as _;
                 #step_runs
             }
         }
 
-        impl <T: #async_story_ident + Send> narrative::step::RunAsync<T, T::Error> for Step {
+        impl <#impl_params #error_param #b: narrative::story::ConstBindings<ConstValues>, #env: #async_story_bound + Send> narrative::step::RunAsync<#env, #error> for Step<#type_args #b> #where_clause {
             #[inline]
-            async fn run_async(&self, story: &mut T) -> Result<(), T::Error> {
+            async fn run_async(&self, story: &mut #env) -> Result<(), #error> {
                 use narrative::runner::AsyncStoryRunner as _;
                 let mut runner = narrative::runner::DefaultStoryRunner;
                 self.run_with_runner_async(story, &mut runner).await
             }
             #[inline]
-            async fn run_with_runner_async(&self, story: &mut T, runner: &mut impl narrative::runner::AsyncStoryRunner<T::Error>) -> Result<(), T::Error> {
+            async fn run_with_runner_async(&self, story: &mut #env, runner: &mut (impl narrative::runner::AsyncStoryRunner<#error> + Send)) -> Result<(), #error> {
                 use narrative::runner::AsyncStoryRunner as _;
                 #step_runs_async
             }
@@ -146,18 +361,25 @@ pub(crate) fn generate(story: &ItemStory) -> TokenStream {
 
 pub struct StepSegments<'a> {
     ident: &'a syn::Ident,
+    /// Whether the step is repeated, whose variant holds the index of the item.
+    repeated: bool,
     mod_ident: syn::Ident,
+    /// The `#[cfg(...)]` attrs of the step.
+    cfg: TokenStream,
     run: TokenStream,
     run_async: TokenStream,
     step_def: StepDef,
 }
 
 pub struct StepDef {
+    cfg: TokenStream,
     mod_ident: syn::Ident,
     step_text: TokenStream,
+    step_text_in: TokenStream,
+    step_segments: TokenStream,
+    text_pattern: TokenStream,
     step_id: TokenStream,
     args: TokenStream,
-    story: TokenStream,
     nested_story: TokenStream,
     dyn_step: TokenStream,
 }
@@ -165,21 +387,27 @@ pub struct StepDef {
 impl ToTokens for StepDef {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let StepDef {
+            cfg,
             mod_ident,
             step_text,
+            step_text_in,
+            step_segments,
+            text_pattern,
             step_id,
             args,
-            story,
             nested_story,
             dyn_step,
         } = &self;
         tokens.extend(quote! {
+            #cfg
             pub mod #mod_ident {
                 use super::*;
                 #step_text
+                #step_text_in
+                #step_segments
+                #text_pattern
                 #step_id
                 #args
-                #story
                 #nested_story
                 #dyn_step
             }
@@ -187,108 +415,259 @@ impl ToTokens for StepDef {
     }
 }
 
-fn generate_step<'a>(story: &'a ItemStory, step: &'a StoryStep) -> StepSegments<'a> {
+/// Binds the value of an attr arg with the type of the step arg if any.
+fn attr_arg_assignment(step: &StoryStep, arg: &StepAttrArgs) -> TokenStream {
+    let name = &arg.ident;
+    let ty = step
+        .fn_args()
+        .find(|(ident, _)| *ident == name)
+        .map(|(_, ty)| ty);
+    let value = &arg.value;
+    // A borrowed arg borrows the value, which can be owned like `vec![...]` for `&[T]`.
+    if let Some(ty) = ty.filter(|ty| is_borrowed_value(ty, value)) {
+        return quote! {
+            let #name = #value;
+            let #name: #ty = &*#name;
+        };
+    }
+    let ty = ty.map(|ty| quote!(:#ty));
+    quote! {
+        let #name #ty = #value;
+    }
+}
+
+fn generate_step<'a>(
+    story: &'a ItemStory,
+    step: &'a StoryStep,
+    convert_errors: bool,
+) -> StepSegments<'a> {
     let step_name = &step.inner.sig.ident;
+    let ident = &step.inner.sig.ident;
+    let mod_ident = format_ident!("mod_{}", ident);
     let step_text = &step.step_attr.text;
     // We don't filter out unused step args here to generate unused warnings.
     let step_args_assignments: Vec<_> = step
-        .step_attr
-        .args
-        .iter()
-        .map(|arg| {
-            let name = &arg.ident;
-            let ty = step
-                .fn_args()
-                .find(|(ident, _)| *ident == name)
-                .map(|(_, ty)| quote!(:#ty));
-            let value = &arg.value;
-            quote! {
-                let #name #ty = #value;
-            }
-        })
+        .run_attr_args()
+        .map(|arg| attr_arg_assignment(step, arg))
         .collect();
-    let extracted_format_args = step.extract_format_args();
-
-    // Collect format args from step attributes first
-    let attr_names = step
-        .step_attr
-        .args
-        .iter()
-        .filter(|arg| extracted_format_args.contains(&arg.ident.to_string()))
-        .map(|arg| arg.ident.to_string())
-        .collect::<BTreeSet<_>>();
-
-    let format_args_from_attr = step.step_attr.args.iter().filter_map(|arg| {
-        if extracted_format_args.contains(&arg.ident.to_string()) {
-            let value = &arg.value;
-            Some((&arg.ident, quote!(#value)))
-        } else {
-            None
-        }
+    // The arg taking the items of a repeated step is computed from the index.
+    let repeat_item = story.repeat_item(step).map(|ident| {
+        let args_mod = format_ident!("{}_args", step_name);
+        let arg_mod = format_ident!("mod_{}", ident);
+        (ident, quote!(args::#args_mod::#arg_mod))
     });
-    let format_args_from_global = story.consts().filter_map(|StoryConst { raw, default }| {
-        if extracted_format_args.contains(&raw.ident.to_string())
-            && !attr_names.contains(&raw.ident.to_string())
+    let (index_param, index_param_in, index_arg) = match &repeat_item {
+        Some(_) => (
+            Some(quote!(index: usize)),
+            Some(quote!(index: usize,)),
+            Some(quote!(index)),
+        ),
+        None => (None, None, None),
+    };
+    // Formats the text of the step, or a text in a locale, with the args used in it.
+    let format_text = |step_text: &syn::LitStr| {
+        // Texts with fields, formatters, or plural suffixes are joined from the segments.
+        if let Ok(segments) = parse_template(&step_text.value())
+            && is_rich(&segments)
         {
-            let expr = &default.1;
-            Some((&raw.ident, quote!(#expr)))
-        } else {
-            None
+            let segments = template_segments(story, step, &segments, repeat_item.as_ref());
+            return quote! {
+                let segments: Vec<narrative::text::StepTextSegment> = #segments;
+                segments.iter().map(narrative::text::StepTextSegment::text).collect()
+            };
         }
-    });
+        let extracted_format_args = collect_format_args(step_text);
+
+        // Collect format args from step attributes first
+        let attr_names = step
+            .step_attr
+            .args
+            .iter()
+            .filter(|arg| extracted_format_args.contains(&arg.ident.to_string()))
+            .map(|arg| arg.ident.to_string())
+            .collect::<BTreeSet<_>>();
+
+        let format_args_from_attr = step
+            .step_attr
+            .args
+            .iter()
+            .filter(|arg| extracted_format_args.contains(&arg.ident.to_string()))
+            .map(|arg| (&arg.ident, &arg.value))
+            .collect::<Vec<_>>();
+        // Consts in the step text are captured from the bindings.
+        let format_args_from_global = story.consts().filter_map(|StoryConst { raw, .. }| {
+            (extracted_format_args.contains(&raw.ident.to_string())
+                && !attr_names.contains(&raw.ident.to_string()))
+            .then_some(&raw.ident)
+        });
+        let attr_values = format_args_from_attr.iter().map(|(_, value)| value);
+        let step_text_consts = bind_consts(
+            story,
+            &format_ident!("B"),
+            quote!(#(#format_args_from_global)* #(#attr_values)*),
+        );
+        let step_text_item = repeat_item.as_ref().and_then(|(ident, arg_path)| {
+            extracted_format_args
+                .contains(&ident.to_string())
+                .then(|| quote!(let #ident = #arg_path::value::<B>(index);))
+        });
 
-    let format_args = format_args_from_attr
-        .chain(format_args_from_global)
-        .map(|(ident, expr)| {
+        let format_args = format_args_from_attr.iter().map(|(ident, expr)| {
             quote! {
                 , #ident = #expr
             }
         });
+        quote! {
+            #step_text_item
+            #step_text_consts
+            format!(#step_text #(#format_args)*)
+        }
+    };
+    let step_text_body = format_text(step_text);
+    // Invalid texts are reported by `format!` in the step text.
+    let step_segments_body = match parse_template(&step_text.value()) {
+        Ok(segments) => template_segments(story, step, &segments, repeat_item.as_ref()),
+        Err(_) => {
+            quote!(vec![narrative::text::StepTextSegment::Literal(step_text::<B>(#index_arg))])
+        }
+    };
+    let text_pattern = match parse_template(&step_text.value()) {
+        Ok(segments) => text_pattern(story, step, &segments),
+        Err(_) => quote!(&[]),
+    };
+    let localized_texts = step
+        .localized_texts()
+        .map(|(locale, text)| {
+            let locale = locale.to_string();
+            let body = format_text(text);
+            quote!(#locale => Some({ #body }),)
+        })
+        .collect::<Vec<_>>();
+    let step_text_in_body = if localized_texts.is_empty() {
+        quote!(None)
+    } else {
+        quote! {
+            match locale {
+                #(#localized_texts)*
+                _ => None,
+            }
+        }
+    };
     let args: Vec<_> = step.fn_args().map(|(ident, _)| ident).collect();
+    // Args without attr args are the consts with the same names.
+    let global_args = args
+        .iter()
+        .filter(|ident| step.find_attr_arg(ident).is_none());
+    let all_attr_values = step.run_attr_args().map(|arg| &arg.value);
+    let b = story.internal_param("B");
+    let env = story.internal_param("T");
+    let impl_params = story.impl_params();
+    let type_args = story.type_args();
+    let where_clause = story.where_clause();
+    let run_consts = bind_consts(story, &b, quote!(#(#global_args)* #(#all_attr_values)*));
+    let run_item = repeat_item.as_ref().map(|(ident, arg_path)| {
+        let ty = step
+            .fn_args()
+            .find(|(arg, _)| arg == ident)
+            .map(|(_, ty)| quote!(:#ty));
+        quote!(let #ident #ty = #arg_path::value::<#b>(*index);)
+    });
 
     let nested_story = if let Some(sub_story_path) = step.sub_story_path() {
         let context_path = &sub_story_path.context_path();
-        quote! {
-            pub fn nested_story() -> #context_path {
-                #context_path::default()
+        if let Some(bindings) = &sub_story_path.bindings {
+            let const_values =
+                quote!(<#context_path as narrative::story::BindableContext>::ConstValues);
+            let bound_consts = bindings.bindings.iter().map(|binding| {
+                let ident = &binding.ident;
+                let value = &binding.value;
+                let bind_consts = bind_consts(story, &b, value);
+                quote! {
+                    consts.#ident = || {
+                        #bind_consts
+                        #value
+                    };
+                }
+            });
+            let phantom_data = story.phantom_data(&b);
+            let bound_context_path =
+                push_generic_arg(context_path, syn::parse_quote!(Bindings<#type_args #b>));
+            let bound_context = expr_path(&bound_context_path);
+            quote! {
+                /// The consts of the sub story bound by this step.
+                pub struct Bindings<#impl_params #b>(#phantom_data);
+                impl<#impl_params #b: narrative::story::ConstBindings<ConstValues>> narrative::story::ConstBindings<#const_values> for Bindings<#type_args #b> #where_clause {
+                    const CONSTS: #const_values = {
+                        let mut consts = <narrative::story::DefaultBindings as narrative::story::ConstBindings<#const_values>>::CONSTS;
+                        #(#bound_consts)*
+                        consts
+                    };
+                }
+                pub fn nested_story<#impl_params #b: narrative::story::ConstBindings<ConstValues>>() -> #bound_context_path #where_clause {
+                    #bound_context::new()
+                }
+                pub fn dyn_nested_story<#impl_params #b: narrative::story::ConstBindings<ConstValues>>() -> Option<narrative::story::DynStoryContext> #where_clause {
+                    Some(nested_story::<#type_args #b>().to_dyn())
+                }
             }
-            pub fn dyn_nested_story() -> Option<narrative::story::DynStoryContext> {
-                Some(nested_story().to_dyn())
+        } else {
+            let context = expr_path(context_path);
+            quote! {
+                pub fn nested_story<#impl_params #b>() -> #context_path #where_clause {
+                    #context::default()
+                }
+                pub fn dyn_nested_story<#impl_params #b>() -> Option<narrative::story::DynStoryContext> #where_clause {
+                    Some(nested_story::<#type_args #b>().to_dyn())
+                }
             }
         }
     } else {
         quote! {
-            pub fn dyn_nested_story() -> Option<narrative::story::DynStoryContext> {
+            pub fn dyn_nested_story<#impl_params #b>() -> Option<narrative::story::DynStoryContext> #where_clause {
                 None
             }
         }
     };
     let (run, run_async) = if step.has_sub_story() {
+        // The errors of the sub story are converted into the error of this story.
+        let sub_story = if convert_errors {
+            quote!(narrative::environment::MapError::new(#env::#step_name(story #(,#args)*)?))
+        } else {
+            quote!(#env::#step_name(story #(,#args)*)?)
+        };
         (
             quote! {
+                #run_consts
+                #run_item
                 #(#step_args_assignments)*
-                let mut sub_story = T::#step_name(story #(,#args)*)?;
-                let story = sub_story.get_context();
-                runner.run_nested_story(Step::#step_name, story, &mut sub_story)?;
+                let mut sub_story = #sub_story;
+                let story = steps::#mod_ident::nested_story::<#type_args #b>();
+                runner.run_nested_story(*self, story, &mut sub_story)?;
                 Ok(())
             },
             quote! {
+                #run_consts
+                #run_item
                 #(#step_args_assignments)*
-                let mut sub_story = T::#step_name(story #(,#args)*)?;
-                let story = sub_story.get_context();
-                runner.run_nested_story_async(Step::#step_name, story, &mut sub_story).await?;
+                let mut sub_story = #sub_story;
+                let story = steps::#mod_ident::nested_story::<#type_args #b>();
+                runner.run_nested_story_async(*self, story, &mut sub_story).await?;
                 Ok(())
             },
         )
     } else {
         (
             quote! {
+                #run_consts
+                #run_item
                 #(#step_args_assignments)*
-                T::#step_name(story #(,#args)*)
+                #env::#step_name(story #(,#args)*)
             },
             quote! {
+                #run_consts
+                #run_item
                 #(#step_args_assignments)*
-                T::#step_name(story #(,#args)*).await
+                #env::#step_name(story #(,#args)*).await
             },
         )
     };
@@ -296,48 +675,238 @@ fn generate_step<'a>(story: &'a ItemStory, step: &'a StoryStep) -> StepSegments<
     let args_len = args.len();
     let dyn_args = if args.is_empty() {
         quote!(Box::new(std::iter::empty()))
+    } else if repeat_item.is_some() {
+        quote!(Box::new(__args(index).into_iter().map(|arg| StepArg::<#b>::new(arg).to_dyn())))
     } else {
-        quote!(Box::new(__ARGS.iter().map(|arg| arg.to_dyn())))
+        quote!(Box::new(__ARGS.iter().map(|arg| StepArg::<#b>::new(*arg).to_dyn())))
     };
+    let arg_values = args.iter().map(|ident| {
+        let index = repeat_item
+            .as_ref()
+            .filter(|(item, _)| item == ident)
+            .map(|_| quote!((index)));
+        quote!(StepArgInner::#step_name(args::#step_name::#ident #index))
+    });
+    let step_args = if repeat_item.is_some() {
+        quote! {
+            pub fn __args(index: usize) -> [StepArgInner; #args_len] {
+                [#(#arg_values),*]
+            }
+        }
+    } else {
+        quote!(pub const __ARGS: [StepArgInner; #args_len] = [#(#arg_values),*];)
+    };
+    let dyn_step = if let Some((_, arg_path)) = &repeat_item {
+        quote! {
+            pub fn dyn_step<#impl_params #b: narrative::story::ConstBindings<ConstValues>>(index: usize) -> narrative::step::DynStep #where_clause {
+                narrative::step::DynStep::new(
+                    String::new,
+                    __STEP_ID,
+                    || Box::new(std::iter::empty()),
+                    || StoryContext::<#type_args #b>::new().to_dyn(),
+                    dyn_nested_story::<#type_args #b>,
+                )
+                .with_text_pattern(__TEXT_PATTERN)
+                .with_repeat_index(
+                    index,
+                    step_text::<#b>,
+                    step_text_in::<#b>,
+                    step_segments::<#b>,
+                    |index| #dyn_args,
+                )
+            }
+            /// Returns the number of the repetitions of the step.
+            pub fn __len<B: narrative::story::ConstBindings<ConstValues>>() -> usize {
+                #arg_path::len::<B>()
+            }
+        }
+    } else {
+        quote! {
+            pub fn dyn_step<#impl_params #b: narrative::story::ConstBindings<ConstValues>>() -> narrative::step::DynStep #where_clause {
+                narrative::step::DynStep::new(
+                    step_text::<#b>,
+                    __STEP_ID,
+                    || #dyn_args,
+                    || StoryContext::<#type_args #b>::new().to_dyn(),
+                    dyn_nested_story::<#type_args #b>,
+                )
+                .with_step_text_in(step_text_in::<#b>)
+                .with_step_segments(step_segments::<#b>)
+                .with_text_pattern(__TEXT_PATTERN)
+            }
+        }
+    };
+    let when = step.when().map(|when| {
+        let bind_consts = bind_consts(story, &format_ident!("B"), when);
+        quote! {
+            /// Returns true if the step is included in the story.
+            pub fn __when<B: narrative::story::ConstBindings<ConstValues>>() -> bool {
+                #bind_consts
+                #when
+            }
+        }
+    });
 
-    let ident = &step.inner.sig.ident;
-    let mod_ident = format_ident!("mod_{}", ident);
-
+    let cfg_attrs = step.cfg_attrs();
+    let cfg = quote!(#(#cfg_attrs)*);
     let step_def = StepDef {
+        cfg: cfg.clone(),
         mod_ident: mod_ident.clone(),
         step_text: quote! {
-            pub fn step_text() -> String {
-                format!(#step_text #(#format_args)*)
+            pub fn step_text<B: narrative::story::ConstBindings<ConstValues>>(#index_param) -> String {
+                #step_text_body
             }
         },
+        step_segments: quote! {
+            pub fn step_segments<B: narrative::story::ConstBindings<ConstValues>>(#index_param) -> Vec<narrative::text::StepTextSegment> {
+                #step_segments_body
+            }
+        },
+        step_text_in: quote! {
+            #[allow(unused_variables)]
+            pub fn step_text_in<B: narrative::story::ConstBindings<ConstValues>>(#index_param_in locale: &str) -> Option<String> {
+                #step_text_in_body
+            }
+        },
+        text_pattern: quote! {
+            pub const __TEXT_PATTERN: &[narrative::text::PatternSegment] = #text_pattern;
+        },
         step_id: quote!(
             pub const __STEP_ID: &str = stringify!(#step_name);
         ),
-        args: quote!(pub const __ARGS: [StepArg; #args_len] = [#(StepArg(StepArgInner::#step_name(args::#step_name::#args))),*];),
-        story: quote!(
-            pub const __STORY: StoryContext = StoryContext;
-        ),
+        args: step_args,
         nested_story,
-        dyn_step: quote! {
-            pub const DYN_STEP: narrative::step::DynStep = narrative::step::DynStep::new(
-                step_text,
-                __STEP_ID,
-                || #dyn_args,
-                || __STORY.to_dyn(),
-                dyn_nested_story,
-            );
-        },
+        dyn_step: quote!(#dyn_step #when),
     };
 
     StepSegments {
         ident,
+        repeated: repeat_item.is_some(),
         mod_ident,
+        cfg,
         run,
         run_async,
         step_def,
     }
 }
 
+/// Generates the pattern of a step text, which matches any text to the args and the values to the
+/// consts.
+fn text_pattern(story: &ItemStory, step: &StoryStep, segments: &[TemplateSegment]) -> TokenStream {
+    let segments = segments.iter().map(|segment| match segment {
+        TemplateSegment::Literal(text) => quote!(narrative::text::PatternSegment::Literal(#text)),
+        TemplateSegment::Arg(arg) => {
+            let name = &arg.name;
+            let root = arg.root();
+            // Args of the step shadow the consts of the same name.
+            if story.consts().any(|item| item.raw.ident == root)
+                && !step.fn_args().any(|(ident, _)| ident == root)
+                && !step.attr_args().any(|(ident, _)| ident == root)
+            {
+                quote!(narrative::text::PatternSegment::Const(#name))
+            } else {
+                quote!(narrative::text::PatternSegment::Arg(#name))
+            }
+        }
+        TemplateSegment::Plural { suffix, .. } => {
+            quote!(narrative::text::PatternSegment::Plural(#suffix))
+        }
+    });
+    quote!(&[#(#segments),*])
+}
+
+/// Generates the segments of a step text, whose args are bound like in `step_text`.
+fn template_segments(
+    story: &ItemStory,
+    step: &StoryStep,
+    segments: &[TemplateSegment],
+    repeat_item: Option<&(&syn::Ident, TokenStream)>,
+) -> TokenStream {
+    let roots = segments
+        .iter()
+        .filter_map(|segment| match segment {
+            TemplateSegment::Arg(arg) => Some(arg.root().to_string()),
+            TemplateSegment::Plural { arg, .. } => Some(arg.clone()),
+            TemplateSegment::Literal(_) => None,
+        })
+        .collect::<BTreeSet<_>>();
+    let attr_args = step
+        .step_attr
+        .args
+        .iter()
+        .filter(|arg| roots.contains(&arg.ident.to_string()))
+        .collect::<Vec<_>>();
+    let attr_lets = attr_args.iter().map(|arg| attr_arg_assignment(step, arg));
+    // Roots that are not attr args are the consts or the item.
+    let global_roots = roots
+        .iter()
+        .filter(|root| !attr_args.iter().any(|arg| arg.ident == root))
+        .map(|root| format_ident!("{}", root));
+    let attr_values = attr_args.iter().map(|arg| &arg.value);
+    let consts = bind_consts(
+        story,
+        &format_ident!("B"),
+        quote!(#(#global_roots)* #(#attr_values)*),
+    );
+    let item = repeat_item.and_then(|(ident, arg_path)| {
+        (roots.contains(&ident.to_string()) && step.find_attr_arg(ident).is_none())
+            .then(|| quote!(let #ident = #arg_path::value::<B>(index);))
+    });
+
+    // Literals and plural suffixes in a row are joined into a literal segment.
+    let mut literal = Vec::new();
+    let mut exprs = Vec::new();
+    let flush = |literal: &mut Vec<TokenStream>, exprs: &mut Vec<TokenStream>| {
+        match literal.as_slice() {
+            [] => {}
+            [text] => {
+                exprs.push(quote!(narrative::text::StepTextSegment::Literal(#text.to_string())))
+            }
+            _ => exprs
+                .push(quote!(narrative::text::StepTextSegment::Literal([#(#literal),*].concat()))),
+        }
+        literal.clear();
+    };
+    for segment in segments {
+        match segment {
+            TemplateSegment::Literal(text) => literal.push(quote!(#text)),
+            TemplateSegment::Plural { arg, suffix } => {
+                let arg = format_ident!("{}", arg);
+                literal.push(quote!(narrative::text::plural_suffix(&#arg, #suffix)));
+            }
+            TemplateSegment::Arg(arg) => {
+                flush(&mut literal, &mut exprs);
+                let name = &arg.name;
+                let value: syn::Expr =
+                    syn::parse_str(name).expect("the name is validated as an expression");
+                let text = match &arg.format {
+                    ArgFormat::Spec(spec) if spec.is_empty() => quote!(#value.to_string()),
+                    ArgFormat::Spec(spec) => {
+                        let format = format!("{{:{spec}}}");
+                        quote!(format!(#format, #value))
+                    }
+                    ArgFormat::Formatter(formatter) => {
+                        let formatter = format_ident!("{}", formatter);
+                        quote!(#formatter(&#value).to_string())
+                    }
+                };
+                exprs.push(quote! {
+                    narrative::text::StepTextSegment::Arg { name: #name, text: #text }
+                });
+            }
+        }
+    }
+    flush(&mut literal, &mut exprs);
+    quote! {
+        {
+            #item
+            #consts
+            #(#attr_lets)*
+            vec![#(#exprs),*]
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use syn::parse_quote;
@@ -356,7 +925,7 @@ mod tests {
                 #step
             }
         };
-        let actual = generate_step(&story_syntax, &step);
+        let actual = generate_step(&story_syntax, &step, false);
         assert_eq!(
             actual.run.to_string(),
             quote! {
@@ -374,7 +943,7 @@ mod tests {
         assert_eq!(
             actual.step_def.step_text.to_string(),
             quote! {
-                pub fn step_text() -> String {
+                pub fn step_text<B: narrative::story::ConstBindings<ConstValues>>() -> String {
                     format!("Step 1")
                 }
             }
@@ -390,7 +959,7 @@ mod tests {
         assert_eq!(
             actual.step_def.args.to_string(),
             quote! {
-                pub const __ARGS: [StepArg; 0usize] = [];
+                pub const __ARGS: [StepArgInner; 0usize] = [];
             }
             .to_string()
         );
@@ -407,7 +976,7 @@ mod tests {
                 #step
             }
         };
-        let actual = generate_step(&story_syntax, &step);
+        let actual = generate_step(&story_syntax, &step, false);
         assert_eq!(
             actual.run.to_string(),
             quote! {
@@ -427,7 +996,7 @@ mod tests {
         assert_eq!(
             actual.step_def.step_text.to_string(),
             quote! {
-                pub fn step_text() -> String {
+                pub fn step_text<B: narrative::story::ConstBindings<ConstValues>>() -> String {
                     format!("Step 1: {name}", name = "ryo")
                 }
             }
@@ -436,7 +1005,122 @@ mod tests {
         assert_eq!(
             actual.step_def.args.to_string(),
             quote! {
-                pub const __ARGS: [StepArg; 1usize] = [StepArg(StepArgInner::my_step1(args::my_step1::name))];
+                pub const __ARGS: [StepArgInner; 1usize] = [StepArgInner::my_step1(args::my_step1::name)];
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_step_segments() {
+        let step = parse_quote! {
+            #[step("{name} has {count} orange{count:plural(s)}", name = "ryo", count = 2)]
+            fn my_step1(name: &str, count: u32);
+        };
+        let story_syntax = parse_quote! {
+            trait UserStory {
+                #step
+            }
+        };
+        let actual = generate_step(&story_syntax, &step, false);
+        assert_eq!(
+            actual.step_def.step_segments.to_string(),
+            quote! {
+                pub fn step_segments<B: narrative::story::ConstBindings<ConstValues>>() -> Vec<narrative::text::StepTextSegment> {
+                    {
+                        let name: &str = "ryo";
+                        let count: u32 = 2;
+                        vec![
+                            narrative::text::StepTextSegment::Arg { name: "name", text: name.to_string() },
+                            narrative::text::StepTextSegment::Literal(" has ".to_string()),
+                            narrative::text::StepTextSegment::Arg { name: "count", text: count.to_string() },
+                            narrative::text::StepTextSegment::Literal([" orange", narrative::text::plural_suffix(&count, "s")].concat())
+                        ]
+                    }
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_text_pattern() {
+        let step = parse_quote! {
+            #[step("{name} has {count} orange{count:plural(s)} at {SHOP} {{really}}", name = "ryo", count = 2)]
+            fn my_step1(name: &str, count: u32);
+        };
+        let story_syntax = parse_quote! {
+            trait UserStory {
+                const SHOP: &str = "Fruit Shop";
+                #step
+            }
+        };
+        let actual = generate_step(&story_syntax, &step, false);
+        assert_eq!(
+            actual.step_def.text_pattern.to_string(),
+            quote! {
+                pub const __TEXT_PATTERN: &[narrative::text::PatternSegment] = &[
+                    narrative::text::PatternSegment::Arg("name"),
+                    narrative::text::PatternSegment::Literal(" has "),
+                    narrative::text::PatternSegment::Arg("count"),
+                    narrative::text::PatternSegment::Literal(" orange"),
+                    narrative::text::PatternSegment::Plural("s"),
+                    narrative::text::PatternSegment::Literal(" at "),
+                    narrative::text::PatternSegment::Const("SHOP"),
+                    narrative::text::PatternSegment::Literal(" {really}")
+                ];
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_localized_step_text() {
+        let step = parse_quote! {
+            #[step("Step 1: {name}", name = "ryo", text(ja = "ステップ1: {name}", fr = "Étape 1"))]
+            fn my_step1(name: &str);
+        };
+        let story_syntax = parse_quote! {
+            trait UserStory {
+                #step
+            }
+        };
+        let actual = generate_step(&story_syntax, &step, false);
+        assert_eq!(
+            actual.step_def.step_text_in.to_string(),
+            quote! {
+                #[allow(unused_variables)]
+                pub fn step_text_in<B: narrative::story::ConstBindings<ConstValues>>(locale: &str) -> Option<String> {
+                    match locale {
+                        "ja" => Some({ format!("ステップ1: {name}", name = "ryo") }),
+                        "fr" => Some({ format!("Étape 1") }),
+                        _ => None,
+                    }
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    /// Owned values of borrowed args are borrowed while the step runs.
+    fn test_borrowed_step_attr_args() {
+        let step = parse_quote! {
+            #[step("list users", users = vec![UserId::new()])]
+            fn list(users: &[UserId]);
+        };
+        let story_syntax = parse_quote! {
+            trait UserStory {
+                #step
+            }
+        };
+        let actual = generate_step(&story_syntax, &step, false);
+        assert_eq!(
+            actual.run.to_string(),
+            quote! {
+                let users = vec![UserId::new()];
+                let users: &[UserId] = &*users;
+                T::list(story, users)
             }
             .to_string()
         );
@@ -454,7 +1138,7 @@ mod tests {
                 #step
             }
         };
-        let actual = generate_step(&story_syntax, &step);
+        let actual = generate_step(&story_syntax, &step, false);
         assert_eq!(
             actual.run.to_string(),
             quote! {
@@ -476,7 +1160,7 @@ mod tests {
         assert_eq!(
             actual.step_def.step_text.to_string(),
             quote! {
-                pub fn step_text() -> String {
+                pub fn step_text<B: narrative::story::ConstBindings<ConstValues>>() -> String {
                     format!("Step 1: {name}", name = "ryo")
                 }
             }
@@ -485,7 +1169,7 @@ mod tests {
         assert_eq!(
             actual.step_def.args.to_string(),
             quote! {
-                pub const __ARGS: [StepArg; 1usize] = [StepArg(StepArgInner::my_step1(args::my_step1::name))];
+                pub const __ARGS: [StepArgInner; 1usize] = [StepArgInner::my_step1(args::my_step1::name)];
             }
             .to_string()
         );
@@ -505,10 +1189,11 @@ mod tests {
                 #step
             }
         };
-        let actual = generate_step(&story_syntax, &step);
+        let actual = generate_step(&story_syntax, &step, false);
         assert_eq!(
             actual.run.to_string(),
             quote! {
+                let name = (B::CONSTS.name)();
                 T::my_step1(story, name)
             }
             .to_string()
@@ -516,6 +1201,7 @@ mod tests {
         assert_eq!(
             actual.run_async.to_string(),
             quote! {
+                let name = (B::CONSTS.name)();
                 T::my_step1(story, name).await
             }
             .to_string()
@@ -534,12 +1220,13 @@ mod tests {
                 #step
             }
         };
-        let actual = generate_step(&story_syntax, &step);
+        let actual = generate_step(&story_syntax, &step, false);
         assert_eq!(
             actual.step_def.step_text.to_string(),
             quote! {
-                pub fn step_text() -> String {
-                    format!("Step 1: {name}", name = "ryo")
+                pub fn step_text<B: narrative::story::ConstBindings<ConstValues>>() -> String {
+                    let name = (B::CONSTS.name)();
+                    format!("Step 1: {name}")
                 }
             }
             .to_string()
@@ -549,24 +1236,15 @@ mod tests {
     #[test]
     /// User can get insufficient format args error.
     fn test_format_arg_insufficient() {
-        let step = parse_quote! {
-            #[step("Step 1: {name} {age}", name = "ryo")]
-            fn my_step1(name: &str);
-        };
-        let story_syntax = parse_quote! {
+        let result = syn::parse2::<ItemStory>(quote! {
             trait UserStory {
-                #step
+                #[step("Step 1: {name} {age}", name = "ryo")]
+                fn my_step1(name: &str);
             }
-        };
-        let actual = generate_step(&story_syntax, &step);
+        });
         assert_eq!(
-            actual.step_def.step_text.to_string(),
-            quote! {
-                pub fn step_text() -> String {
-                    format!("Step 1: {name} {age}", name = "ryo")
-                }
-            }
-            .to_string()
+            result.err().unwrap().to_string(),
+            "`{age}` is not an argument or const of this story"
         );
     }
 
@@ -581,11 +1259,11 @@ mod tests {
                 #step
             }
         };
-        let actual = generate_step(&story_syntax, &step);
+        let actual = generate_step(&story_syntax, &step, false);
         assert_eq!(
             actual.step_def.step_text.to_string(),
             quote! {
-                pub fn step_text() -> String {
+                pub fn step_text<B: narrative::story::ConstBindings<ConstValues>>() -> String {
                     format!("Step 1: {name:?}", name = "ryo")
                 }
             }
@@ -604,14 +1282,14 @@ mod tests {
                 #step
             }
         };
-        let actual = generate_step(&story_syntax, &step);
+        let actual = generate_step(&story_syntax, &step, false);
 
         assert_eq!(
             actual.run.to_string(),
             quote! {
                 let mut sub_story = T::run_sub(story)?;
-                let story = sub_story.get_context();
-                runner.run_nested_story(Step::run_sub, story, &mut sub_story)?;
+                let story = steps::mod_run_sub::nested_story::<B>();
+                runner.run_nested_story(*self, story, &mut sub_story)?;
                 Ok(())
             }
             .to_string()
@@ -621,8 +1299,8 @@ mod tests {
             actual.run_async.to_string(),
             quote! {
                 let mut sub_story = T::run_sub(story)?;
-                let story = sub_story.get_context();
-                runner.run_nested_story_async(Step::run_sub, story, &mut sub_story).await?;
+                let story = steps::mod_run_sub::nested_story::<B>();
+                runner.run_nested_story_async(*self, story, &mut sub_story).await?;
                 Ok(())
             }
             .to_string()
@@ -631,7 +1309,7 @@ mod tests {
         assert_eq!(
             actual.step_def.step_text.to_string(),
             quote! {
-                pub fn step_text() -> String {
+                pub fn step_text<B: narrative::story::ConstBindings<ConstValues>>() -> String {
                     format!("run sub story")
                 }
             }
@@ -642,11 +1320,11 @@ mod tests {
         assert_eq!(
             actual.step_def.nested_story.to_string(),
             quote! {
-                pub fn nested_story() -> SubStoryContext {
+                pub fn nested_story<B>() -> SubStoryContext {
                     SubStoryContext::default()
                 }
-                pub fn dyn_nested_story() -> Option<narrative::story::DynStoryContext> {
-                    Some(nested_story().to_dyn())
+                pub fn dyn_nested_story<B>() -> Option<narrative::story::DynStoryContext> {
+                    Some(nested_story::<B>().to_dyn())
                 }
             }
             .to_string()
@@ -664,15 +1342,15 @@ mod tests {
                 #step
             }
         };
-        let actual = generate_step(&story_syntax, &step);
+        let actual = generate_step(&story_syntax, &step, false);
 
         assert_eq!(
             actual.run.to_string(),
             quote! {
                 let param: i32 = 42;
                 let mut sub_story = T::run_sub(story, param)?;
-                let story = sub_story.get_context();
-                runner.run_nested_story(Step::run_sub, story, &mut sub_story)?;
+                let story = steps::mod_run_sub::nested_story::<B>();
+                runner.run_nested_story(*self, story, &mut sub_story)?;
                 Ok(())
             }
             .to_string()
@@ -683,8 +1361,8 @@ mod tests {
             quote! {
                 let param: i32 = 42;
                 let mut sub_story = T::run_sub(story, param)?;
-                let story = sub_story.get_context();
-                runner.run_nested_story_async(Step::run_sub, story, &mut sub_story).await?;
+                let story = steps::mod_run_sub::nested_story::<B>();
+                runner.run_nested_story_async(*self, story, &mut sub_story).await?;
                 Ok(())
             }
             .to_string()
@@ -693,7 +1371,7 @@ mod tests {
         assert_eq!(
             actual.step_def.step_text.to_string(),
             quote! {
-                pub fn step_text() -> String {
+                pub fn step_text<B: narrative::story::ConstBindings<ConstValues>>() -> String {
                     format!("run sub story with {param}", param = 42)
                 }
             }
@@ -703,7 +1381,138 @@ mod tests {
         assert_eq!(
             actual.step_def.args.to_string(),
             quote! {
-                pub const __ARGS: [StepArg; 1usize] = [StepArg(StepArgInner::run_sub(args::run_sub::param))];
+                pub const __ARGS: [StepArgInner; 1usize] = [StepArgInner::run_sub(args::run_sub::param)];
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_repeated_step() {
+        let step = parse_quote! {
+            #[step(repeat = ITEMS, "add {item} to the cart", quantity = 2)]
+            fn add_item(item: &str, quantity: u32);
+        };
+        let story_syntax = parse_quote! {
+            trait Checkout {
+                const ITEMS: &[&str] = &["apple", "banana"];
+                #step
+            }
+        };
+        let actual = generate_step(&story_syntax, &step, false);
+        assert_eq!(
+            actual.run.to_string(),
+            quote! {
+                let item: &str = args::add_item_args::mod_item::value::<B>(*index);
+                let quantity: u32 = 2;
+                T::add_item(story, item, quantity)
+            }
+            .to_string()
+        );
+        assert_eq!(
+            actual.step_def.step_text.to_string(),
+            quote! {
+                pub fn step_text<B: narrative::story::ConstBindings<ConstValues>>(index: usize) -> String {
+                    let item = args::add_item_args::mod_item::value::<B>(index);
+                    format!("add {item} to the cart")
+                }
+            }
+            .to_string()
+        );
+        assert_eq!(
+            actual.step_def.args.to_string(),
+            quote! {
+                pub fn __args(index: usize) -> [StepArgInner; 2usize] {
+                    [StepArgInner::add_item(args::add_item::item(index)), StepArgInner::add_item(args::add_item::quantity)]
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_conditional_step() {
+        let step = parse_quote! {
+            #[step(when = COUPON_ENABLED, "apply a coupon")]
+            fn apply_coupon();
+        };
+        let story_syntax = parse_quote! {
+            trait Checkout {
+                const COUPON_ENABLED: bool = false;
+                #step
+            }
+        };
+        let actual = generate_step(&story_syntax, &step, false);
+        assert!(
+            actual.step_def.dyn_step.to_string().contains(
+                &quote! {
+                    pub fn __when<B: narrative::story::ConstBindings<ConstValues>>() -> bool {
+                        let COUPON_ENABLED = (B::CONSTS.COUPON_ENABLED)();
+                        COUPON_ENABLED
+                    }
+                }
+                .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_cfg_step() {
+        let story_syntax = parse_quote! {
+            trait Deploy {
+                #[cfg(feature = "migrations")]
+                #[step("migrate")]
+                fn migrate();
+            }
+        };
+        let attr = parse_quote!("Deploy the app");
+        let actual = generate(&attr, &story_syntax).to_string();
+        let cfg = quote!(#[cfg(feature = "migrations")]);
+        for gated in [
+            quote!(#cfg pub mod mod_migrate),
+            quote!(#cfg migrate),
+            quote!(#cfg StepInner::migrate => steps::mod_migrate::step_text::<B>(),),
+            quote!(#cfg StepInner::migrate => steps::mod_migrate::dyn_step::<B>(),),
+        ] {
+            assert!(actual.contains(&gated.to_string()), "{gated}");
+        }
+    }
+
+    #[test]
+    fn test_sub_story_step_with_bindings() {
+        let step = parse_quote! {
+            #[step(story: Login { USER = ADMIN }, "log in as admin")]
+            fn log_in_as_admin();
+        };
+        let story_syntax = parse_quote! {
+            trait UserStory {
+                const ADMIN: &str = "admin";
+                #step
+            }
+        };
+        let actual = generate_step(&story_syntax, &step, false);
+
+        assert_eq!(
+            actual.step_def.nested_story.to_string(),
+            quote! {
+                /// The consts of the sub story bound by this step.
+                pub struct Bindings<B>(std::marker::PhantomData<fn() -> B>);
+                impl<B: narrative::story::ConstBindings<ConstValues>> narrative::story::ConstBindings< <LoginContext as narrative::story::BindableContext>::ConstValues> for Bindings<B> {
+                    const CONSTS: <LoginContext as narrative::story::BindableContext>::ConstValues = {
+                        let mut consts = <narrative::story::DefaultBindings as narrative::story::ConstBindings< <LoginContext as narrative::story::BindableContext>::ConstValues>>::CONSTS;
+                        consts.USER = || {
+                            let ADMIN = (B::CONSTS.ADMIN)();
+                            ADMIN
+                        };
+                        consts
+                    };
+                }
+                pub fn nested_story<B: narrative::story::ConstBindings<ConstValues>>() -> LoginContext<Bindings<B> > {
+                    LoginContext::<Bindings<B> >::new()
+                }
+                pub fn dyn_nested_story<B: narrative::story::ConstBindings<ConstValues>>() -> Option<narrative::story::DynStoryContext> {
+                    Some(nested_story::<B>().to_dyn())
+                }
             }
             .to_string()
         );
@@ -722,12 +1531,13 @@ mod tests {
                 #step
             }
         };
-        let actual = generate_step(&story_syntax, &step);
+        let actual = generate_step(&story_syntax, &step, false);
 
         // run (sync)
         assert_eq!(
             actual.run.to_string(),
             quote! {
+                let MY_CONST = (B::CONSTS.MY_CONST)();
                 let param: i32 = MY_CONST * 2;
                 T::my_step(story, param)
             }
@@ -738,6 +1548,7 @@ mod tests {
         assert_eq!(
             actual.run_async.to_string(),
             quote! {
+                let MY_CONST = (B::CONSTS.MY_CONST)();
                 let param: i32 = MY_CONST * 2;
                 T::my_step(story, param).await
             }
@@ -757,13 +1568,13 @@ mod tests {
                 #step
             }
         };
-        let actual = generate_step(&story_syntax, &step);
+        let actual = generate_step(&story_syntax, &step, false);
 
         // Step attribute value should override global constant in format args
         assert_eq!(
             actual.step_def.step_text.to_string(),
             quote! {
-                pub fn step_text() -> String {
+                pub fn step_text<B: narrative::story::ConstBindings<ConstValues>>() -> String {
                     format!("Step 1: {name}", name = "override")
                 }
             }
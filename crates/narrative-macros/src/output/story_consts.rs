@@ -3,30 +3,42 @@
 // enum dispatched by const name
 
 use proc_macro2::TokenStream;
-use quote::quote;
+use quote::{format_ident, quote};
 
 use crate::{
-    item_story::ItemStory, make_static, output::MatchArms, pretty_print_expr, pretty_print_type,
+    item_story::ItemStory,
+    make_static,
+    output::{MatchArms, bind_consts, deserialize_fn},
+    pretty_print_expr, pretty_print_type,
+    story_attr_syntax::StoryAttr,
 };
 
-pub(crate) fn generate(story: &ItemStory) -> TokenStream {
-    let const_defs = story.consts().map(|item| item.to_pub_const());
+pub(crate) fn generate(attr: &StoryAttr, story: &ItemStory) -> TokenStream {
+    let const_defs = story.rust_consts().map(|item| item.to_pub_const());
     let const_names = story
         .consts()
         .map(|item| &item.raw.ident)
         .collect::<Vec<_>>();
-    if const_names.is_empty() {
-        return quote! {
-            /// This is placeholder and never be constructed.
-            pub type StoryConst = narrative::story::DynStoryConst;
-        };
-    }
+    // Story values are evaluated once per story run, instead of being Rust consts.
+    let default_bindings = story.consts().map(|item| {
+        let ident = &item.raw.ident;
+        if item.is_value() {
+            quote!(#ident: story_consts::#ident::__evaluate::<narrative::story::DefaultBindings>)
+        } else {
+            quote!(#ident: || consts::#ident)
+        }
+    });
     let const_variants = story.consts().map(|item| {
         let ident = &item.raw.ident;
         let ty = &item.raw.ty;
         let static_ty = make_static(ty);
         quote!(#ident(#static_ty))
     });
+    let const_value_fields = story.consts().map(|item| {
+        let ident = &item.raw.ident;
+        let static_ty = make_static(&item.raw.ty);
+        quote!(pub #ident: fn() -> #static_ty)
+    });
 
     let const_value_debug_arms = story
         .consts()
@@ -43,6 +55,37 @@ pub(crate) fn generate(story: &ItemStory) -> TokenStream {
         let expr_str = pretty_print_expr(expr);
 
         let static_ty = make_static(&item.raw.ty);
+        let (with_deserialize, deserialize_error) =
+            match attr.deserialize.is_some().then(|| deserialize_fn(&item.raw.ty)) {
+                Some(Ok(deserialize_fn)) => (Some(quote!(.with_deserialize(#deserialize_fn))), None),
+                Some(Err(err)) => (None, Some(err.to_compile_error())),
+                None => (None, None),
+            };
+        // The env var overrides the value bound by `B`, even if a parent story binds the const.
+        let (value, with_override) = match &item.env {
+            Some(env) => (
+                quote!(narrative::story::env_override(#env).unwrap_or_else(B::CONSTS.#ident)),
+                Some(quote!(.with_override(
+                    narrative::story::OverrideSource::Env(#env),
+                    || narrative::story::invalid_env_override::<#static_ty>(#env),
+                ))),
+            ),
+            None => (quote!((B::CONSTS.#ident)()), None),
+        };
+        let evaluate = item.is_value().then(|| {
+            let bind_consts = bind_consts(story, &format_ident!("B"), expr);
+            quote! {
+                /// The key of the value in a story run.
+                pub struct __Key<B>(std::marker::PhantomData<B>);
+                #[inline]
+                pub fn __evaluate<B: narrative::story::ConstBindings<ConstValues>>() -> #static_ty {
+                    narrative::story::story_value::<__Key<B>, _>(|| {
+                        #bind_consts
+                        #expr
+                    })
+                }
+            }
+        });
 
         quote! {
             pub mod #ident {
@@ -50,17 +93,21 @@ pub(crate) fn generate(story: &ItemStory) -> TokenStream {
                 pub const __NAME: &str = stringify!(#ident);
                 pub const __TY: &str = #ty;
                 pub const __EXPR: &str = #expr_str;
+                #evaluate
                 #[inline]
-                pub fn value() -> #static_ty {
-                    #expr
+                pub fn value<B: narrative::story::ConstBindings<ConstValues>>() -> #static_ty {
+                    #value
+                }
+                pub fn dyn_story_const<B: narrative::story::ConstBindings<ConstValues>>() -> narrative::story::DynStoryConst {
+                    narrative::story::DynStoryConst::new(
+                        __NAME,
+                        __TY,
+                        __EXPR,
+                        || narrative::value::BoxedValue::new(value::<B>()),
+                        || narrative::value::BoxedValue::new(StoryConst::<B>::new(StoryConstInner::#ident))
+                    )#with_deserialize #with_override
                 }
-                pub const DYN_STORY_CONST: narrative::story::DynStoryConst = narrative::story::DynStoryConst::new(
-                    __NAME,
-                    __TY,
-                    __EXPR,
-                    || narrative::value::BoxedValue::new(value()),
-                    || narrative::value::BoxedValue::new(StoryConst::#ident)
-                );
+                #deserialize_error
             }
         }
     });
@@ -69,35 +116,48 @@ pub(crate) fn generate(story: &ItemStory) -> TokenStream {
         .consts()
         .map(|item| {
             let ident = &item.raw.ident;
-            quote!(Self::#ident => story_consts::#ident::__NAME,)
+            quote!(StoryConstInner::#ident => story_consts::#ident::__NAME,)
         })
-        .collect::<MatchArms>();
+        .collect::<MatchArms>()
+        .match_target(quote!(self.0));
 
     let ty_arms = story
         .consts()
         .map(|item| {
             let ident = &item.raw.ident;
-            quote!(Self::#ident => story_consts::#ident::__TY,)
+            quote!(StoryConstInner::#ident => story_consts::#ident::__TY,)
         })
-        .collect::<MatchArms>();
+        .collect::<MatchArms>()
+        .match_target(quote!(self.0));
 
     let expr_arms = story
         .consts()
         .map(|item| {
             let ident = &item.raw.ident;
-            quote!(Self::#ident => story_consts::#ident::__EXPR,)
+            quote!(StoryConstInner::#ident => story_consts::#ident::__EXPR,)
         })
-        .collect::<MatchArms>();
+        .collect::<MatchArms>()
+        .match_target(quote!(self.0));
 
     let value_arms = story
         .consts()
         .map(|item| {
             let ident = &item.raw.ident;
-            quote!(Self::#ident => ConstValue::#ident(story_consts::#ident::value()),)
+            quote!(StoryConstInner::#ident => ConstValue::#ident(story_consts::#ident::value::<B>()),)
         })
         .collect::<MatchArms>()
+        .match_target(quote!(self.0))
         .cast_as(quote!(narrative::value::BoxedValue));
 
+    let to_dyn_arms = story
+        .consts()
+        .map(|item| {
+            let ident = &item.raw.ident;
+            quote!(StoryConstInner::#ident => story_consts::#ident::dyn_story_const::<B>(),)
+        })
+        .collect::<MatchArms>()
+        .match_target(quote!(self.0));
+
     let impl_body = quote! {
         #[inline]
         fn name(&self) -> &'static str {
@@ -116,16 +176,69 @@ pub(crate) fn generate(story: &ItemStory) -> TokenStream {
             #value_arms
         }
     };
+    let deserialize_value = attr.deserialize.is_some().then(|| {
+        quote! {
+            #[inline]
+            fn deserialize_value<'de, D: narrative::serde::Deserializer<'de>>(&self, deserializer: D) -> Result<narrative::value::BoxedValue, D::Error> {
+                narrative::story::StoryConst::deserialize_value(&self.to_dyn(), deserializer)
+            }
+        }
+    });
+
+    let overridden_by = story.consts().any(|item| item.env.is_some()).then(|| {
+        quote! {
+            #[inline]
+            fn overridden_by(&self) -> Option<narrative::story::OverrideSource> {
+                narrative::story::StoryConst::overridden_by(&self.to_dyn())
+            }
+            #[inline]
+            fn invalid_override(&self) -> Option<narrative::story::InvalidOverride> {
+                narrative::story::StoryConst::invalid_override(&self.to_dyn())
+            }
+        }
+    });
 
     quote! {
-        #(#const_defs)*
+        pub mod consts {
+            use super::*;
+            #(#const_defs)*
+        }
+
+        /// The values of the consts, which can be bound by a parent story.
+        #[derive(Clone, Copy)]
+        pub struct ConstValues {
+            #(#const_value_fields),*
+        }
+
+        impl narrative::story::ConstBindings<ConstValues> for narrative::story::DefaultBindings {
+            const CONSTS: ConstValues = ConstValues {
+                #(#default_bindings),*
+            };
+        }
 
         #[derive(Clone, Copy)]
         #[allow(non_camel_case_types)]
-        pub enum StoryConst {
+        enum StoryConstInner {
             #(#const_names),*
         }
 
+        pub struct StoryConst<B = narrative::story::DefaultBindings>(StoryConstInner, std::marker::PhantomData<fn() -> B>);
+
+        impl<B> StoryConst<B> {
+            const fn new(inner: StoryConstInner) -> Self {
+                Self(inner, std::marker::PhantomData)
+            }
+        }
+
+        impl<B> Clone for StoryConst<B> {
+            #[inline]
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl<B> Copy for StoryConst<B> {}
+
         #[derive(Clone, narrative::serde::Serialize)]
         #[allow(non_camel_case_types)]
         enum ConstValue {
@@ -144,19 +257,19 @@ pub(crate) fn generate(story: &ItemStory) -> TokenStream {
             #(#const_mod_defs)*
         }
 
-        impl StoryConst {
+        impl<B: narrative::story::ConstBindings<ConstValues>> StoryConst<B> {
             pub fn to_dyn(&self) -> narrative::story::DynStoryConst {
-                match self {
-                    #(Self::#const_names => story_consts::#const_names::DYN_STORY_CONST,)*
-                }
+                #to_dyn_arms
             }
         }
 
-        impl narrative::story::StoryConst for StoryConst {
+        impl<B: narrative::story::ConstBindings<ConstValues>> narrative::story::StoryConst for StoryConst<B> {
             #impl_body
+            #deserialize_value
+            #overridden_by
         }
 
-        impl std::fmt::Debug for StoryConst {
+        impl<B: narrative::story::ConstBindings<ConstValues>> std::fmt::Debug for StoryConst<B> {
             #[inline]
             fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
                 use narrative::story::StoryConst;
@@ -164,16 +277,20 @@ pub(crate) fn generate(story: &ItemStory) -> TokenStream {
             }
         }
 
-        impl narrative::serde::Serialize for StoryConst {
+        impl<B: narrative::story::ConstBindings<ConstValues>> narrative::serde::Serialize for StoryConst<B> {
             #[inline]
             fn serialize<T: narrative::serde::Serializer>(&self, serializer: T) -> Result<T::Ok, T::Error> {
                 use narrative::story::StoryConst;
                 use narrative::serde::ser::SerializeMap;
-                let mut map = serializer.serialize_map(Some(4))?;
+                let overridden_by = self.overridden_by();
+                let mut map = serializer.serialize_map(Some(4 + usize::from(overridden_by.is_some())))?;
                 map.serialize_entry("name", self.name())?;
                 map.serialize_entry("ty", self.ty())?;
                 map.serialize_entry("expr", self.expr())?;
                 map.serialize_entry("value", &self.value())?;
+                if let Some(source) = overridden_by {
+                    map.serialize_entry("overridden_by", &source.to_string())?;
+                }
                 map.end()
             }
         }
@@ -194,15 +311,114 @@ mod tests {
             }
         };
 
-        let actual = generate(&story);
+        let actual = generate(&parse_quote!("User"), &story).to_string();
 
-        assert_eq!(
-            actual.to_string(),
-            quote! {
-                /// This is placeholder and never be constructed.
-                pub type StoryConst = narrative::story::DynStoryConst;
+        assert!(
+            actual.contains(
+                &quote! {
+                    pub struct ConstValues {}
+                }
+                .to_string()
+            )
+        );
+        assert!(
+            actual.contains(
+                &quote! {
+                    enum StoryConstInner {}
+                }
+                .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn values() {
+        let story = parse_quote! {
+            trait User {
+                const DIR: &str = "data";
+                #[narrative::value]
+                const PATH: PathBuf = PathBuf::from(DIR);
+            }
+        };
+
+        let actual = generate(&parse_quote!("User"), &story).to_string();
+
+        assert!(
+            actual.contains(
+                &quote! {
+                    pub mod consts {
+                        use super::*;
+                        pub const DIR: &str = "data";
+                    }
+                }
+                .to_string()
+            )
+        );
+        assert!(
+            actual.contains(
+                &quote! {
+                    const CONSTS: ConstValues = ConstValues {
+                        DIR: || consts::DIR,
+                        PATH: story_consts::PATH::__evaluate::<narrative::story::DefaultBindings>
+                    };
+                }
+                .to_string()
+            )
+        );
+        assert!(actual.contains(
+            &quote! {
+                pub fn __evaluate<B: narrative::story::ConstBindings<ConstValues>>() -> PathBuf {
+                    narrative::story::story_value::<__Key<B>, _>(|| {
+                        let DIR = (B::CONSTS.DIR)();
+                        PathBuf::from(DIR)
+                    })
+                }
+            }
+            .to_string()
+        ));
+    }
+
+    #[test]
+    fn env_overrides() {
+        let story = parse_quote! {
+            trait User {
+                #[env("BASE_URL")]
+                const BASE_URL: &str = "http://localhost:8080";
+            }
+        };
+
+        let actual = generate(&parse_quote!("User"), &story).to_string();
+
+        assert!(actual.contains(
+            &quote! {
+                pub fn value<B: narrative::story::ConstBindings<ConstValues>>() -> &'static str {
+                    narrative::story::env_override("BASE_URL").unwrap_or_else(B::CONSTS.BASE_URL)
+                }
             }
             .to_string()
+        ));
+        assert!(
+            actual.contains(
+                &quote!(.with_override(
+                    narrative::story::OverrideSource::Env("BASE_URL"),
+                    || narrative::story::invalid_env_override::<&'static str>("BASE_URL"),
+                ))
+                .to_string()
+            )
+        );
+        assert!(
+            actual.contains(
+                &quote! {
+                    fn overridden_by(&self) -> Option<narrative::story::OverrideSource> {
+                        narrative::story::StoryConst::overridden_by(&self.to_dyn())
+                    }
+                    #[inline]
+                    fn invalid_override(&self) -> Option<narrative::story::InvalidOverride> {
+                        narrative::story::StoryConst::invalid_override(&self.to_dyn())
+                    }
+                }
+                .to_string()
+            )
         );
     }
 
@@ -214,19 +430,51 @@ mod tests {
             }
         };
 
-        let actual = generate(&story);
+        let actual = generate(&parse_quote!("User"), &story);
 
         assert_eq!(
             actual.to_string(),
             quote! {
-                pub const NUMBER: u32 = 42;
+                pub mod consts {
+                    use super::*;
+                    pub const NUMBER: u32 = 42;
+                }
+
+                /// The values of the consts, which can be bound by a parent story.
+                #[derive(Clone, Copy)]
+                pub struct ConstValues {
+                    pub NUMBER: fn() -> u32
+                }
+
+                impl narrative::story::ConstBindings<ConstValues> for narrative::story::DefaultBindings {
+                    const CONSTS: ConstValues = ConstValues {
+                        NUMBER: || consts::NUMBER
+                    };
+                }
 
                 #[derive(Clone, Copy)]
                 #[allow(non_camel_case_types)]
-                pub enum StoryConst {
+                enum StoryConstInner {
                     NUMBER
                 }
 
+                pub struct StoryConst<B = narrative::story::DefaultBindings>(StoryConstInner, std::marker::PhantomData<fn() -> B>);
+
+                impl<B> StoryConst<B> {
+                    const fn new(inner: StoryConstInner) -> Self {
+                        Self(inner, std::marker::PhantomData)
+                    }
+                }
+
+                impl<B> Clone for StoryConst<B> {
+                    #[inline]
+                    fn clone(&self) -> Self {
+                        *self
+                    }
+                }
+
+                impl<B> Copy for StoryConst<B> {}
+
                 #[derive(Clone, narrative::serde::Serialize)]
                 #[allow(non_camel_case_types)]
                 enum ConstValue {
@@ -250,55 +498,57 @@ mod tests {
                         pub const __TY: &str = "u32";
                         pub const __EXPR: &str = "42";
                         #[inline]
-                        pub fn value() -> u32 {
-                            42
+                        pub fn value<B: narrative::story::ConstBindings<ConstValues>>() -> u32 {
+                            (B::CONSTS.NUMBER)()
+                        }
+                        pub fn dyn_story_const<B: narrative::story::ConstBindings<ConstValues>>() -> narrative::story::DynStoryConst {
+                            narrative::story::DynStoryConst::new(
+                                __NAME,
+                                __TY,
+                                __EXPR,
+                                || narrative::value::BoxedValue::new(value::<B>()),
+                                || narrative::value::BoxedValue::new(StoryConst::<B>::new(StoryConstInner::NUMBER))
+                            )
                         }
-                        pub const DYN_STORY_CONST: narrative::story::DynStoryConst = narrative::story::DynStoryConst::new(
-                            __NAME,
-                            __TY,
-                            __EXPR,
-                            || narrative::value::BoxedValue::new(value()),
-                            || narrative::value::BoxedValue::new(StoryConst::NUMBER)
-                        );
                     }
                 }
 
-                impl StoryConst {
+                impl<B: narrative::story::ConstBindings<ConstValues>> StoryConst<B> {
                     pub fn to_dyn(&self) -> narrative::story::DynStoryConst {
-                        match self {
-                            Self::NUMBER => story_consts::NUMBER::DYN_STORY_CONST,
+                        match self.0 {
+                            StoryConstInner::NUMBER => story_consts::NUMBER::dyn_story_const::<B>(),
                         }
                     }
                 }
 
-                impl narrative::story::StoryConst for StoryConst {
+                impl<B: narrative::story::ConstBindings<ConstValues>> narrative::story::StoryConst for StoryConst<B> {
                     #[inline]
                     fn name(&self) -> &'static str {
-                        match self {
-                            Self::NUMBER => story_consts::NUMBER::__NAME,
+                        match self.0 {
+                            StoryConstInner::NUMBER => story_consts::NUMBER::__NAME,
                         }
                     }
                     #[inline]
                     fn ty(&self) -> &'static str {
-                        match self {
-                            Self::NUMBER => story_consts::NUMBER::__TY,
+                        match self.0 {
+                            StoryConstInner::NUMBER => story_consts::NUMBER::__TY,
                         }
                     }
                     #[inline]
                     fn expr(&self) -> &'static str {
-                        match self {
-                            Self::NUMBER => story_consts::NUMBER::__EXPR,
+                        match self.0 {
+                            StoryConstInner::NUMBER => story_consts::NUMBER::__EXPR,
                         }
                     }
                     #[inline]
                     fn value(&self) -> impl narrative::value::Value {
-                        match self {
-                            Self::NUMBER => ConstValue::NUMBER(story_consts::NUMBER::value()),
+                        match self.0 {
+                            StoryConstInner::NUMBER => ConstValue::NUMBER(story_consts::NUMBER::value::<B>()),
                         }
                     }
                 }
 
-                impl std::fmt::Debug for StoryConst {
+                impl<B: narrative::story::ConstBindings<ConstValues>> std::fmt::Debug for StoryConst<B> {
                     #[inline]
                     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
                         use narrative::story::StoryConst;
@@ -306,16 +556,20 @@ mod tests {
                     }
                 }
 
-                impl narrative::serde::Serialize for StoryConst {
+                impl<B: narrative::story::ConstBindings<ConstValues>> narrative::serde::Serialize for StoryConst<B> {
                     #[inline]
                     fn serialize<T: narrative::serde::Serializer>(&self, serializer: T) -> Result<T::Ok, T::Error> {
                         use narrative::story::StoryConst;
                         use narrative::serde::ser::SerializeMap;
-                        let mut map = serializer.serialize_map(Some(4))?;
+                        let overridden_by = self.overridden_by();
+                        let mut map = serializer.serialize_map(Some(4 + usize::from(overridden_by.is_some())))?;
                         map.serialize_entry("name", self.name())?;
                         map.serialize_entry("ty", self.ty())?;
                         map.serialize_entry("expr", self.expr())?;
                         map.serialize_entry("value", &self.value())?;
+                        if let Some(source) = overridden_by {
+                            map.serialize_entry("overridden_by", &source.to_string())?;
+                        }
                         map.end()
                     }
                 }
@@ -10,115 +10,262 @@ use crate::{
 
 pub(crate) fn generate(attr: &StoryAttr, input: &ItemStory) -> TokenStream {
     let title = &attr.title;
+    let titles = attr.titles.iter().map(|(locale, title)| {
+        let locale = locale.to_string();
+        quote!((#locale, #title))
+    });
     let ident = &input.ident;
+    let b = input.internal_param("B");
+    let impl_params = input.impl_params();
+    let impl_generics = input.impl_generics();
+    let type_params = input.type_params();
+    let type_args = input.type_args();
+    let type_generics = input.type_generics();
+    let where_clause = input.where_clause();
+    let phantom_data = input.phantom_data(&b);
     let steps = input.steps().map(|step| {
         let step_name = &step.inner.sig.ident;
-        quote! {
-            #[inline]
-            pub fn #step_name(&self) -> Step {
-                Step::#step_name
+        let cfg_attrs = step.cfg_attrs();
+        if input.repeat_item(step).is_some() {
+            quote! {
+                #(#cfg_attrs)*
+                /// Returns the step for the item at the index.
+                #[inline]
+                pub fn #step_name(&self, index: usize) -> Step<#type_args #b> {
+                    Step::new(StepInner::#step_name(index))
+                }
+            }
+        } else {
+            quote! {
+                #(#cfg_attrs)*
+                #[inline]
+                pub fn #step_name(&self) -> Step<#type_args #b> {
+                    Step::new(StepInner::#step_name)
+                }
             }
         }
     });
-    let step_names = input.steps().map(|step| &step.inner.sig.ident);
     let consts = input.consts().map(|item| &item.raw.ident);
-    let consts_defs = input.consts().map(
-        |StoryConst {
-             raw,
-             default: (eq, default),
-         }| {
-            let ident = &raw.ident;
-            let ty = &raw.ty;
-            Some(quote! {
-                pub const #ident: #ty #eq #default;
-            })
-        },
-    );
+    let consts_defs = input.rust_consts().map(|StoryConst { raw, .. }| {
+        let ident = &raw.ident;
+        let ty = &raw.ty;
+        Some(quote! {
+            pub const #ident: #ty = consts::#ident;
+        })
+    });
     let steps_len = input.steps().count();
     let const_len = input.consts().count();
-    let dyn_consts = if const_len == 0 {
-        quote!(Box::new(std::iter::empty()))
+    let mut dyn_consts = if const_len == 0 {
+        quote!(std::iter::empty())
+    } else {
+        quote!(__CONSTS.into_iter().map(|c| StoryConst::<#b>::new(c).to_dyn()))
+    };
+    // Repeated and conditional steps are listed by evaluating the consts bound by `B`.
+    let (steps_def, steps_inners) = if input.has_dynamic_steps() {
+        let pushes = input.steps().map(|step| {
+            let step_name = &step.inner.sig.ident;
+            let mod_ident = format_ident!("mod_{}", step_name);
+            let cfg_attrs = step.cfg_attrs();
+            let push = if input.repeat_item(step).is_some() {
+                quote!(inners.extend((0..steps::#mod_ident::__len::<B>()).map(StepInner::#step_name));)
+            } else {
+                quote!(inners.push(StepInner::#step_name);)
+            };
+            if step.when().is_some() {
+                quote! {
+                    #(#cfg_attrs)*
+                    if steps::#mod_ident::__when::<B>() {
+                        #push
+                    }
+                }
+            } else {
+                quote!(#(#cfg_attrs)* #push)
+            }
+        });
+        (
+            quote! {
+                pub fn __steps<B: narrative::story::ConstBindings<ConstValues>>() -> Vec<StepInner> {
+                    let mut inners = Vec::new();
+                    #(#pushes)*
+                    inners
+                }
+            },
+            quote!(__steps::<#b>().into_iter()),
+        )
     } else {
-        quote!(Box::new(__CONSTS.into_iter().map(|c| c.to_dyn())))
+        // A slice rather than an array, since the steps gated by cfg attrs are not counted.
+        let step_inners = input.steps().map(|step| {
+            let step_name = &step.inner.sig.ident;
+            let cfg_attrs = step.cfg_attrs();
+            quote!(#(#cfg_attrs)* StepInner::#step_name)
+        });
+        (
+            quote!(pub const __STEPS: &[StepInner] = &[#(#step_inners),*];),
+            quote!(__STEPS.iter().copied()),
+        )
     };
-    let dyn_steps = if steps_len == 0 {
-        quote!(Box::new(std::iter::empty()))
+    let mut dyn_steps = if steps_len == 0 {
+        quote!(std::iter::empty())
     } else {
-        quote!(Box::new(__STEPS.into_iter().map(|s| s.to_dyn())))
+        quote!(#steps_inners.map(|s| Step::<#type_args #b>::new(s).to_dyn()))
     };
+    let mut steps_iter = quote!(#steps_inners.map(Step::new));
+    let mut consts_iter = quote!(__CONSTS.into_iter().map(StoryConst::<#b>::new));
+    // The steps and consts of the parent story come first. Borrowing the const of the parent
+    // context gives a `'static` reference that the returned iterators can capture.
+    if let Some(parent) = &input.parent {
+        let context_path = parent.context_path();
+        let context = quote!(<#context_path>::__NEW);
+        let variant = parent.variant_ident();
+        dyn_consts = quote!(#context.to_dyn().dyn_consts().chain(#dyn_consts));
+        dyn_steps = quote! {
+            narrative::story::StoryContext::steps(&#context)
+                .map(|s| s.to_dyn())
+                .chain(#dyn_steps)
+        };
+        steps_iter = quote! {
+            narrative::story::StoryContext::steps(&#context)
+                .map(|step| Step::new(StepInner::#variant(step)))
+                .chain(#steps_iter)
+        };
+        consts_iter = quote! {
+            narrative::story::StoryContext::consts(&#context)
+                .map(narrative::either::Either::Right)
+                .chain(#consts_iter.map(narrative::either::Either::Left))
+        };
+    }
+    // A generic story has no unit-like value of the context.
+    let unit_value = type_args.is_empty().then(|| {
+        quote! {
+            #[allow(non_upper_case_globals)]
+            pub const StoryContext: StoryContext = StoryContext::new();
+        }
+    });
     quote! {
-        #[derive(Default, Clone, Copy)]
-        pub struct StoryContext;
+        pub struct StoryContext<#type_params #b = narrative::story::DefaultBindings> {
+            bindings: #phantom_data,
+        }
+        #unit_value
+        impl<#impl_params #b> Clone for StoryContext<#type_args #b> #where_clause {
+            #[inline]
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+        impl<#impl_params #b> Copy for StoryContext<#type_args #b> #where_clause {}
+        impl #impl_generics Default for StoryContext #type_generics #where_clause {
+            #[inline]
+            fn default() -> Self {
+                Self::new()
+            }
+        }
         pub const __STORY_TITLE: &str = #title;
+        pub const __STORY_TITLES: &[(&str, &str)] = &[#(#titles),*];
         pub const __STORY_ID: &str = stringify!(#ident);
-        pub const __STEPS: [Step; #steps_len] = [#(Step::#step_names),*];
-        pub const __CONSTS: [StoryConst; #const_len] = [#(StoryConst::#consts),*];
-        impl StoryContext {
+        #steps_def
+        pub const __CONSTS: [StoryConstInner; #const_len] = [#(StoryConstInner::#consts),*];
+        impl #impl_generics StoryContext #type_generics #where_clause {
             #(#consts_defs)*
+        }
+        impl<#impl_params #b> StoryContext<#type_args #b> #where_clause {
+            #[doc(hidden)]
+            pub const __NEW: Self = Self::new();
+
+            /// Returns the context with the consts bound by `B`.
+            pub const fn new() -> Self {
+                Self {
+                    bindings: std::marker::PhantomData,
+                }
+            }
+        }
+        impl<#impl_params #b: narrative::story::ConstBindings<ConstValues>> StoryContext<#type_args #b> #where_clause {
             #(#steps)*
 
             pub fn to_dyn(&self) -> narrative::story::DynStoryContext {
                 narrative::story::DynStoryContext::new(
                     __STORY_TITLE,
                     __STORY_ID,
-                    || #dyn_consts,
-                    || #dyn_steps,
+                    || Box::new(#dyn_consts),
+                    || Box::new(#dyn_steps),
                 )
+                .with_story_titles(__STORY_TITLES)
+            }
+        }
+        impl<#impl_params #b: narrative::story::ConstBindings<ConstValues>> From<StoryContext<#type_args #b>> for narrative::story::DynStoryContext #where_clause {
+            fn from(context: StoryContext<#type_args #b>) -> Self {
+                context.to_dyn()
             }
         }
-        impl narrative::story::StoryContext for StoryContext {
-            type Step = Step;
+        impl<#impl_params #b: narrative::story::ConstBindings<ConstValues>> narrative::story::StoryContext for StoryContext<#type_args #b> #where_clause {
+            type Step = Step<#type_args #b>;
 
             #[inline]
             fn story_title(&self) -> String {
                 __STORY_TITLE.to_string()
             }
             #[inline]
+            fn story_title_in(&self, locale: &str) -> Option<String> {
+                __STORY_TITLES
+                    .iter()
+                    .find(|(title_locale, _)| *title_locale == locale)
+                    .map(|(_, title)| title.to_string())
+            }
+            #[inline]
             fn story_id(&self) -> &'static str {
                 __STORY_ID
             }
             #[inline]
             fn steps(&self) -> impl Iterator<Item = Self::Step> + 'static + Send {
-                __STEPS.into_iter()
+                #steps_iter
             }
             #[inline]
             fn consts(&self) -> impl Iterator<Item = impl narrative::story::StoryConst + 'static> + 'static
             {
-                __CONSTS.into_iter()
+                #consts_iter
             }
         }
+        impl<#impl_params #b: narrative::story::ConstBindings<ConstValues>> narrative::story::BindableContext for StoryContext<#type_args #b> #where_clause {
+            type ConstValues = ConstValues;
+        }
     }
 }
 
 pub(crate) fn generate_ext(input: &ItemStory) -> TokenStream {
     let ident = &input.ident;
     let async_ident = format_ident!("Async{}", input.ident);
+    let impl_params = input.impl_params();
+    let impl_generics = input.impl_generics();
+    let type_generics = input.type_generics();
+    let where_clause = input.where_clause();
+    let env = input.internal_param("T");
+    let story_bound = input.trait_bound(ident);
+    let async_story_bound = input.trait_bound(&async_ident);
     quote! {
-        pub trait ContextExt {
-            fn context() -> StoryContext;
-            fn get_context(&self) -> StoryContext;
+        pub trait ContextExt #impl_generics #where_clause {
+            fn context() -> StoryContext #type_generics;
+            fn get_context(&self) -> StoryContext #type_generics;
         }
-        pub trait AsyncContextExt {
-            fn context() -> StoryContext;
-            fn get_context(&self) -> StoryContext;
+        pub trait AsyncContextExt #impl_generics #where_clause {
+            fn context() -> StoryContext #type_generics;
+            fn get_context(&self) -> StoryContext #type_generics;
         }
-        impl <T: #ident> ContextExt for T {
+        impl <#impl_params #env: #story_bound> ContextExt #type_generics for #env #where_clause {
             #[inline]
-            fn context() -> StoryContext {
+            fn context() -> StoryContext #type_generics {
                 StoryContext::default()
             }
             #[inline]
-            fn get_context(&self) -> StoryContext {
+            fn get_context(&self) -> StoryContext #type_generics {
                 StoryContext::default()
             }
         }
-        impl <T: #async_ident> AsyncContextExt for T {
+        impl <#impl_params #env: #async_story_bound> AsyncContextExt #type_generics for #env #where_clause {
             #[inline]
-            fn context() -> StoryContext {
+            fn context() -> StoryContext #type_generics {
                 StoryContext::default()
             }
             #[inline]
-            fn get_context(&self) -> StoryContext {
+            fn get_context(&self) -> StoryContext #type_generics {
                 StoryContext::default()
             }
         }
@@ -148,56 +295,164 @@ mod tests {
         };
         let actual = generate(&attr, &story_syntax);
         let expected = quote! {
-            #[derive(Default, Clone, Copy)]
-            pub struct StoryContext;
+            pub struct StoryContext<B = narrative::story::DefaultBindings> {
+                bindings: std::marker::PhantomData<fn() -> B>,
+            }
+            #[allow(non_upper_case_globals)]
+            pub const StoryContext: StoryContext = StoryContext::new();
+            impl<B> Clone for StoryContext<B> {
+                #[inline]
+                fn clone(&self) -> Self {
+                    *self
+                }
+            }
+            impl<B> Copy for StoryContext<B> {}
+            impl Default for StoryContext {
+                #[inline]
+                fn default() -> Self {
+                    Self::new()
+                }
+            }
             pub const __STORY_TITLE: &str = "Story Title";
+            pub const __STORY_TITLES: &[(&str, &str)] = &[];
             pub const __STORY_ID: &str = stringify!(UserStory);
-            pub const __STEPS: [Step; 2usize] = [Step::step1, Step::step2];
-            pub const __CONSTS: [StoryConst; 2usize] = [StoryConst::NAME, StoryConst::AGE];
+            pub const __STEPS: &[StepInner] = &[StepInner::step1, StepInner::step2];
+            pub const __CONSTS: [StoryConstInner; 2usize] = [StoryConstInner::NAME, StoryConstInner::AGE];
             impl StoryContext {
-                pub const NAME: &str = "Ryo";
-                pub const AGE: u32 = 20;
+                pub const NAME: &str = consts::NAME;
+                pub const AGE: u32 = consts::AGE;
+            }
+            impl<B> StoryContext<B> {
+                #[doc(hidden)]
+                pub const __NEW: Self = Self::new();
 
+                /// Returns the context with the consts bound by `B`.
+                pub const fn new() -> Self {
+                    Self {
+                        bindings: std::marker::PhantomData,
+                    }
+                }
+            }
+            impl<B: narrative::story::ConstBindings<ConstValues>> StoryContext<B> {
                 #[inline]
-                pub fn step1(&self) -> Step {
-                    Step::step1
+                pub fn step1(&self) -> Step<B> {
+                    Step::new(StepInner::step1)
                 }
                 #[inline]
-                pub fn step2(&self) -> Step {
-                    Step::step2
+                pub fn step2(&self) -> Step<B> {
+                    Step::new(StepInner::step2)
                 }
 
                 pub fn to_dyn(&self) -> narrative::story::DynStoryContext {
                     narrative::story::DynStoryContext::new(
                         __STORY_TITLE,
                         __STORY_ID,
-                        || Box::new(__CONSTS.into_iter().map(|c| c.to_dyn())),
-                        || Box::new(__STEPS.into_iter().map(|s| s.to_dyn())),
+                        || Box::new(__CONSTS.into_iter().map(|c| StoryConst::<B>::new(c).to_dyn())),
+                        || Box::new(__STEPS.iter().copied().map(|s| Step::<B>::new(s).to_dyn())),
                     )
+                    .with_story_titles(__STORY_TITLES)
+                }
+            }
+            impl<B: narrative::story::ConstBindings<ConstValues>> From<StoryContext<B>> for narrative::story::DynStoryContext {
+                fn from(context: StoryContext<B>) -> Self {
+                    context.to_dyn()
                 }
             }
-            impl narrative::story::StoryContext for StoryContext {
-                type Step = Step;
+            impl<B: narrative::story::ConstBindings<ConstValues>> narrative::story::StoryContext for StoryContext<B> {
+                type Step = Step<B>;
 
                 #[inline]
                 fn story_title(&self) -> String {
                     __STORY_TITLE.to_string()
                 }
                 #[inline]
+                fn story_title_in(&self, locale: &str) -> Option<String> {
+                    __STORY_TITLES
+                        .iter()
+                        .find(|(title_locale, _)| *title_locale == locale)
+                        .map(|(_, title)| title.to_string())
+                }
+                #[inline]
                 fn story_id(&self) -> &'static str {
                     __STORY_ID
                 }
                 #[inline]
                 fn steps(&self) -> impl Iterator<Item = Self::Step> + 'static + Send {
-                    __STEPS.into_iter()
+                    __STEPS.iter().copied().map(Step::new)
                 }
                 #[inline]
                 fn consts(&self) -> impl Iterator<Item = impl narrative::story::StoryConst + 'static> + 'static
                 {
-                    __CONSTS.into_iter()
+                    __CONSTS.into_iter().map(StoryConst::<B>::new)
                 }
             }
+            impl<B: narrative::story::ConstBindings<ConstValues>> narrative::story::BindableContext for StoryContext<B> {
+                type ConstValues = ConstValues;
+            }
         };
         assert_eq!(actual.to_string(), expected.to_string());
     }
+
+    #[test]
+    fn test_cfg_steps() {
+        let story_syntax = syn::parse_quote! {
+            trait Deploy {
+                #[step("build")]
+                fn build();
+                #[cfg(feature = "migrations")]
+                #[step("migrate")]
+                fn migrate();
+            }
+        };
+        let attr = syn::parse_quote! {
+            "Deploy the app"
+        };
+        let actual = generate(&attr, &story_syntax).to_string();
+        let gated = [
+            quote! {
+                pub const __STEPS: &[StepInner] = &[StepInner::build, #[cfg(feature = "migrations")] StepInner::migrate];
+            },
+            quote! {
+                #[cfg(feature = "migrations")]
+                #[inline]
+                pub fn migrate(&self) -> Step<B> {
+                    Step::new(StepInner::migrate)
+                }
+            },
+        ];
+        for gated in gated {
+            assert!(actual.contains(&gated.to_string()), "{gated}");
+        }
+    }
+
+    #[test]
+    fn test_generate_generic() {
+        let attr = syn::parse_quote! {
+            "Story Title"
+        };
+        let story_syntax = syn::parse_quote! {
+            trait UserStory<B: Backend, const N: usize> {
+                #[step("step1")]
+                fn step1();
+            }
+        };
+        let actual = generate(&attr, &story_syntax).to_string();
+        let expected_struct = quote! {
+            pub struct StoryContext<B, const N: usize, B_ = narrative::story::DefaultBindings> {
+                bindings: std::marker::PhantomData<fn() -> (B, [(); N], B_)>,
+            }
+        };
+        assert!(actual.starts_with(&expected_struct.to_string()));
+        assert!(!actual.contains("pub const StoryContext"));
+        let expected_default = quote! {
+            impl<B: Backend + 'static, const N: usize,> Default for StoryContext<B, N,>
+        };
+        assert!(actual.contains(&expected_default.to_string()));
+        let expected_step = quote! {
+            pub fn step1(&self) -> Step<B, N, B_> {
+                Step::new(StepInner::step1)
+            }
+        };
+        assert!(actual.contains(&expected_step.to_string()));
+    }
 }
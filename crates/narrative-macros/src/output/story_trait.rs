@@ -1,23 +1,46 @@
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
 
-use crate::{Asyncness, item_story::ItemStory, output::step_fn};
+use crate::{Asyncness, item_story::ItemStory, output::step_fn, story_attr_syntax::StoryAttr};
 
-pub(crate) fn generate(input: &ItemStory, asyncness: Asyncness) -> TokenStream {
+pub(crate) fn generate(attr: &StoryAttr, input: &ItemStory, asyncness: Asyncness) -> TokenStream {
     let ident = match asyncness {
         Asyncness::Sync => input.ident.clone(),
         Asyncness::Async => format_ident!("Async{}", input.ident),
     };
     let steps = input.items.iter().filter_map(|item| match item {
-        crate::item_story::StoryItem::Step(step) => Some(step_fn::generate(step, asyncness)),
+        crate::item_story::StoryItem::Step(step) => {
+            Some(step_fn::generate(step, asyncness, attr.converts_errors()))
+        }
         _ => None,
     });
     let attrs = &input.attrs;
+    let generics = &input.generics;
+    let where_clause = &input.generics.where_clause;
+    // An extending story shares the error type of its parent.
+    let (supertrait, error) = match &input.parent {
+        Some(parent) => {
+            let path = match asyncness {
+                Asyncness::Sync => parent.path.clone(),
+                Asyncness::Async => parent.async_path(),
+            };
+            (Some(quote!(: #path)), None)
+        }
+        None => {
+            // no std::error::Error bound here by default for flexibility in use
+            let bounds = attr.error_bounds().map(|bounds| quote!(: #bounds));
+            (
+                None,
+                Some(quote! {
+                    type Error #bounds;
+                }),
+            )
+        }
+    };
     quote! {
         #(#attrs)*
-        pub trait #ident {
-            // no std::error::Error bound here for flexibility in use
-            type Error;
+        pub trait #ident #generics #supertrait #where_clause {
+            #error
             #(#steps;)*
         }
     }
@@ -38,7 +61,7 @@ mod tests {
                 fn step2(user_id: UserId);
             }
         };
-        let actual = generate(&input, Asyncness::Sync);
+        let actual = generate(&syn::parse_quote!("User Story"), &input, Asyncness::Sync);
         let expected = quote! {
             pub trait UserStory {
                 type Error;
@@ -59,7 +82,7 @@ mod tests {
                 fn step2(user_id: UserId);
             }
         };
-        let actual = generate(&input, Asyncness::Async);
+        let actual = generate(&syn::parse_quote!("User Story"), &input, Asyncness::Async);
         let expected = quote! {
             pub trait AsyncUserStory {
                 type Error;
@@ -70,6 +93,24 @@ mod tests {
         assert_eq!(actual.to_string(), expected.to_string());
     }
 
+    #[test]
+    fn test_generic_trait() {
+        let input = syn::parse_quote! {
+            trait UserStory<T: Backend, const N: usize> where T: Clone {
+                #[step("Step 1")]
+                fn step1();
+            }
+        };
+        let actual = generate(&syn::parse_quote!("User Story"), &input, Asyncness::Async);
+        let expected = quote! {
+            pub trait AsyncUserStory<T: Backend, const N: usize> where T: Clone {
+                type Error;
+                fn step1(&mut self) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send;
+            }
+        };
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
     #[test]
     fn test_trait_with_doc_attr() {
         let input = syn::parse_quote! {
@@ -79,7 +120,7 @@ mod tests {
                 fn step1();
             }
         };
-        let actual = generate(&input, Asyncness::Sync);
+        let actual = generate(&syn::parse_quote!("User Story"), &input, Asyncness::Sync);
         let expected = quote! {
             /// This is a my first story.
             pub trait MyFirstStory {
@@ -89,4 +130,28 @@ mod tests {
         };
         assert_eq!(actual.to_string(), expected.to_string());
     }
+
+    #[test]
+    fn test_extends() {
+        let input = syn::parse_quote! {
+            trait Checkout: crate::Login {
+                #[step("Step 1")]
+                fn step1();
+            }
+        };
+        let actual = generate(&syn::parse_quote!("User Story"), &input, Asyncness::Sync);
+        let expected = quote! {
+            pub trait Checkout: crate::Login {
+                fn step1(&mut self) -> Result<(), Self::Error>;
+            }
+        };
+        assert_eq!(actual.to_string(), expected.to_string());
+        let actual = generate(&syn::parse_quote!("User Story"), &input, Asyncness::Async);
+        let expected = quote! {
+            pub trait AsyncCheckout: crate::AsyncLogin {
+                fn step1(&mut self) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send;
+            }
+        };
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
 }
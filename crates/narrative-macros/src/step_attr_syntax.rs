@@ -1,10 +1,15 @@
 use proc_macro2::TokenStream;
 use quote::{ToTokens, format_ident};
-use syn::parse::Parse;
+use syn::{parse::Parse, punctuated::Punctuated};
+
+use crate::{is_locale, map_last_segment};
 
 mod kw {
     syn::custom_keyword!(step);
     syn::custom_keyword!(story);
+    syn::custom_keyword!(repeat);
+    syn::custom_keyword!(when);
+    syn::custom_keyword!(text);
 }
 
 pub struct StepAttr {
@@ -13,17 +18,43 @@ pub struct StepAttr {
     pub step: kw::step,
     pub paren: syn::token::Paren,
     pub story_type: Option<StoryType>,
+    /// The items to repeat the step for, like `repeat = ITEMS`.
+    pub repeat: Option<StepModifier>,
+    /// The condition to run the step, like `when = FEATURE_FLAG`.
+    pub when: Option<StepModifier>,
     pub text: syn::LitStr,
     pub args: Vec<StepAttrArgs>,
+    /// The texts in locales like `text(ja = "...")`.
+    pub texts: Option<LocalizedTexts>,
 }
 
 pub struct StoryType {
     pub story_kw: kw::story,
     pub colon_token: syn::Token![:],
     pub path: syn::Path,
+    /// Values bound to the consts of the sub story like `Login { USER = ADMIN }`.
+    pub bindings: Option<ConstBindings>,
+    pub comma_token: syn::Token![,],
+}
+
+pub struct StepModifier {
+    pub ident: syn::Ident,
+    pub equal_token: syn::Token![=],
+    pub value: syn::Expr,
     pub comma_token: syn::Token![,],
 }
 
+pub struct ConstBindings {
+    pub brace: syn::token::Brace,
+    pub bindings: Punctuated<ConstBinding, syn::Token![,]>,
+}
+
+pub struct ConstBinding {
+    pub ident: syn::Ident,
+    pub equal_token: syn::Token![=],
+    pub value: syn::Expr,
+}
+
 pub struct StepAttrArgs {
     pub comma_token: Option<syn::Token![,]>,
     pub ident: syn::Ident,
@@ -31,25 +62,30 @@ pub struct StepAttrArgs {
     pub value: syn::Expr,
 }
 
+pub struct LocalizedTexts {
+    pub comma_token: syn::Token![,],
+    pub text_kw: kw::text,
+    pub paren: syn::token::Paren,
+    pub texts: Punctuated<LocalizedText, syn::Token![,]>,
+}
+
+pub struct LocalizedText {
+    pub locale: syn::Ident,
+    pub equal_token: syn::Token![=],
+    pub text: syn::LitStr,
+}
+
 impl StoryType {
     pub fn path(&self) -> &syn::Path {
         &self.path
     }
 
     pub fn async_path(&self) -> syn::Path {
-        let mut cloned = self.path.clone();
-        if let Some(seg) = cloned.segments.last_mut() {
-            seg.ident = format_ident!("Async{}", seg.ident);
-        }
-        cloned
+        map_last_segment(&self.path, |ident| format_ident!("Async{}", ident))
     }
 
     pub fn context_path(&self) -> syn::Path {
-        let mut cloned = self.path.clone();
-        if let Some(seg) = cloned.segments.last_mut() {
-            seg.ident = format_ident!("{}Context", seg.ident);
-        }
-        cloned
+        crate::context_path(&self.path)
     }
 }
 
@@ -59,25 +95,81 @@ impl Parse for StepAttr {
         let attr_content;
         let bracket = syn::bracketed!(attr_content in input);
         let step = attr_content.parse::<kw::step>()?;
+        if !attr_content.peek(syn::token::Paren) {
+            return Err(syn::Error::new(
+                step.span,
+                "expected the text of the step; add it like `#[step(\"...\")]`",
+            ));
+        }
         let step_content;
         let paren = syn::parenthesized!(step_content in attr_content);
 
-        // Try to parse a story type if available
-        let story_type = if step_content.peek(kw::story) {
-            Some(StoryType {
-                story_kw: step_content.parse()?,
-                colon_token: step_content.parse()?,
-                path: step_content.parse()?,
-                comma_token: step_content.parse()?,
-            })
-        } else {
-            None
-        };
+        // A story type and modifiers come before the text in any order.
+        let mut story_type = None;
+        let mut repeat = None;
+        let mut when = None;
+        loop {
+            if step_content.peek(kw::story) {
+                if story_type.is_some() {
+                    return Err(step_content.error("duplicate `story` in the step attribute"));
+                }
+                story_type = Some(StoryType {
+                    story_kw: step_content.parse()?,
+                    colon_token: step_content.parse()?,
+                    path: step_content.parse()?,
+                    bindings: if step_content.peek(syn::token::Brace) {
+                        Some(step_content.parse()?)
+                    } else {
+                        None
+                    },
+                    comma_token: step_content.parse()?,
+                });
+            } else if step_content.peek(kw::repeat) {
+                if repeat.is_some() {
+                    return Err(step_content.error("duplicate `repeat` in the step attribute"));
+                }
+                repeat = Some(step_content.parse()?);
+            } else if step_content.peek(kw::when) {
+                if when.is_some() {
+                    return Err(step_content.error("duplicate `when` in the step attribute"));
+                }
+                when = Some(step_content.parse()?);
+            } else {
+                break;
+            }
+        }
 
+        if !step_content.peek(syn::LitStr) {
+            return Err(
+                step_content.error("expected the text of the step; add it like `#[step(\"...\")]`")
+            );
+        }
         let text = step_content.parse()?;
         let mut args = Vec::new();
+        let mut texts = None::<LocalizedTexts>;
         while !step_content.is_empty() {
-            args.push(step_content.parse()?);
+            let fork = step_content.fork();
+            let _ = fork.parse::<Option<syn::Token![,]>>();
+            if fork.peek(kw::text) && fork.peek2(syn::token::Paren) {
+                let localized: LocalizedTexts = step_content.parse()?;
+                if let Some(texts) = &texts {
+                    return Err(syn::Error::new(
+                        localized.text_kw.span,
+                        format!(
+                            "duplicate `text(...)` in the step attribute; put all locales in the first one after `{}`",
+                            texts
+                                .texts
+                                .iter()
+                                .map(|text| text.locale.to_string())
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ),
+                    ));
+                }
+                texts = Some(localized);
+            } else {
+                args.push(step_content.parse()?);
+            }
         }
 
         Ok(Self {
@@ -86,8 +178,57 @@ impl Parse for StepAttr {
             step,
             paren,
             story_type,
+            repeat,
+            when,
             text,
             args,
+            texts,
+        })
+    }
+}
+
+impl Parse for LocalizedTexts {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let comma_token = input.parse()?;
+        let text_kw = input.parse()?;
+        let content;
+        let paren = syn::parenthesized!(content in input);
+        let texts: Punctuated<LocalizedText, syn::Token![,]> =
+            content.parse_terminated(LocalizedText::parse, syn::Token![,])?;
+        for (index, text) in texts.iter().enumerate() {
+            if texts
+                .iter()
+                .take(index)
+                .any(|other| other.locale == text.locale)
+            {
+                return Err(syn::Error::new(
+                    text.locale.span(),
+                    format!("duplicate text in the locale `{}`", text.locale),
+                ));
+            }
+        }
+        Ok(Self {
+            comma_token,
+            text_kw,
+            paren,
+            texts,
+        })
+    }
+}
+
+impl Parse for LocalizedText {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let locale = input.parse::<syn::Ident>()?;
+        if !is_locale(&locale) {
+            return Err(syn::Error::new(
+                locale.span(),
+                "expected a locale like `ja` or `pt_BR` for the text",
+            ));
+        }
+        Ok(Self {
+            locale,
+            equal_token: input.parse()?,
+            text: input.parse()?,
         })
     }
 }
@@ -107,19 +248,52 @@ impl Parse for StepAttrArgs {
     }
 }
 
+impl Parse for StepModifier {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            ident: input.parse()?,
+            equal_token: input.parse()?,
+            value: input.parse()?,
+            comma_token: input.parse()?,
+        })
+    }
+}
+
+impl Parse for ConstBindings {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let content;
+        let brace = syn::braced!(content in input);
+        Ok(Self {
+            brace,
+            bindings: content.parse_terminated(ConstBinding::parse, syn::Token![,])?,
+        })
+    }
+}
+
+impl Parse for ConstBinding {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            ident: input.parse()?,
+            equal_token: input.parse()?,
+            value: input.parse()?,
+        })
+    }
+}
+
 impl ToTokens for StepAttr {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         self.pound_symbol.to_tokens(tokens);
         self.bracket.surround(tokens, |tokens| {
             self.step.to_tokens(tokens);
             self.paren.surround(tokens, |tokens| {
-                if let Some(story_type) = &self.story_type {
-                    story_type.to_tokens(tokens);
-                }
+                self.story_type.to_tokens(tokens);
+                self.repeat.to_tokens(tokens);
+                self.when.to_tokens(tokens);
                 self.text.to_tokens(tokens);
                 for arg in &self.args {
                     arg.to_tokens(tokens);
                 }
+                self.texts.to_tokens(tokens);
             });
         });
     }
@@ -130,10 +304,35 @@ impl ToTokens for StoryType {
         self.story_kw.to_tokens(tokens);
         self.colon_token.to_tokens(tokens);
         self.path.to_tokens(tokens);
+        self.bindings.to_tokens(tokens);
+        self.comma_token.to_tokens(tokens);
+    }
+}
+
+impl ToTokens for StepModifier {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.ident.to_tokens(tokens);
+        self.equal_token.to_tokens(tokens);
+        self.value.to_tokens(tokens);
         self.comma_token.to_tokens(tokens);
     }
 }
 
+impl ToTokens for ConstBindings {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.brace
+            .surround(tokens, |tokens| self.bindings.to_tokens(tokens));
+    }
+}
+
+impl ToTokens for ConstBinding {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.ident.to_tokens(tokens);
+        self.equal_token.to_tokens(tokens);
+        self.value.to_tokens(tokens);
+    }
+}
+
 impl ToTokens for StepAttrArgs {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         self.comma_token.to_tokens(tokens);
@@ -143,6 +342,23 @@ impl ToTokens for StepAttrArgs {
     }
 }
 
+impl ToTokens for LocalizedTexts {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.comma_token.to_tokens(tokens);
+        self.text_kw.to_tokens(tokens);
+        self.paren
+            .surround(tokens, |tokens| self.texts.to_tokens(tokens));
+    }
+}
+
+impl ToTokens for LocalizedText {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.locale.to_tokens(tokens);
+        self.equal_token.to_tokens(tokens);
+        self.text.to_tokens(tokens);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -213,6 +429,48 @@ mod tests {
         assert!(story_type.path.is_ident("SubStory"));
     }
 
+    #[test]
+    fn test_step_attr_with_story_bindings() {
+        let input: StepAttr = syn::parse_quote! {
+            #[step(story: Login { USER = ADMIN, PASSWORD = "pass" }, "log in as admin")]
+        };
+        assert_eq!(input.text.value(), "log in as admin".to_string());
+        let story_type = input.story_type.as_ref().unwrap();
+        assert!(story_type.path.is_ident("Login"));
+        let bindings = &story_type.bindings.as_ref().unwrap().bindings;
+        assert_eq!(bindings.len(), 2);
+        assert_eq!(bindings[0].ident, "USER".to_string());
+        assert_eq!(bindings[1].ident, "PASSWORD".to_string());
+    }
+
+    #[test]
+    fn test_step_attr_with_modifiers() {
+        let input: StepAttr = syn::parse_quote! {
+            #[step(when = CHECKOUT_ENABLED, repeat = ITEMS, "add {item} to cart")]
+        };
+        assert_eq!(input.text.value(), "add {item} to cart".to_string());
+        assert!(matches!(
+            &input.repeat.as_ref().unwrap().value,
+            syn::Expr::Path(syn::ExprPath { path, .. }) if path.is_ident("ITEMS")
+        ));
+        assert!(matches!(
+            &input.when.as_ref().unwrap().value,
+            syn::Expr::Path(syn::ExprPath { path, .. }) if path.is_ident("CHECKOUT_ENABLED")
+        ));
+        assert!(input.story_type.is_none());
+    }
+
+    #[test]
+    fn test_step_attr_with_duplicate_modifier() {
+        let input = quote! {
+            #[step(repeat = ITEMS, repeat = ITEMS, "add {item} to cart")]
+        };
+        let Err(err) = syn::parse2::<StepAttr>(input) else {
+            panic!("expected an error");
+        };
+        assert_eq!(err.to_string(), "duplicate `repeat` in the step attribute");
+    }
+
     #[test]
     fn test_to_tokens() {
         let input: StepAttr = syn::parse_quote! {
@@ -240,4 +498,18 @@ mod tests {
         };
         assert_eq!(actual.to_string(), expected.to_string());
     }
+
+    #[test]
+    fn test_to_tokens_with_story_bindings() {
+        let input: StepAttr = syn::parse_quote! {
+            #[step(story: Login { USER = ADMIN }, "log in")]
+        };
+        let actual = quote! {
+            #input
+        };
+        let expected = quote! {
+            #[step(story: Login { USER = ADMIN }, "log in")]
+        };
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
 }
@@ -1,17 +1,135 @@
-use syn::parse::{Parse, ParseStream};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+};
+
+use crate::is_locale;
 
 mod kw {
     syn::custom_keyword!(story);
+    syn::custom_keyword!(deserialize);
+    syn::custom_keyword!(background);
+    syn::custom_keyword!(error);
+    syn::custom_keyword!(error_bound);
 }
 
 pub struct StoryAttr {
     pub title: syn::LitStr,
+    /// Whether step args and consts can be deserialized, which requires them to be owned types.
+    pub deserialize: Option<kw::deserialize>,
+    /// The background whose steps come first, like `background = CommonSetup`.
+    pub background: Option<syn::Path>,
+    /// The error type of the story, like `error = MyError`.
+    pub error: Option<syn::Type>,
+    /// The bounds of the error type of the story, like `error_bound = std::error::Error`.
+    pub error_bound: Option<Punctuated<syn::TypeParamBound, syn::Token![+]>>,
+    /// The titles in locales like `ja = "..."`.
+    pub titles: Vec<(syn::Ident, syn::LitStr)>,
+}
+
+/// The attr of a background, whose title defaults to the name of the trait.
+pub struct BackgroundAttr {
+    pub title: Option<syn::LitStr>,
 }
 
 impl Parse for StoryAttr {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let title = input.parse()?;
-        Ok(Self { title })
+        let mut deserialize = None;
+        let mut background = None;
+        let mut error = None;
+        let mut error_bound = None;
+        let mut titles = Vec::<(syn::Ident, syn::LitStr)>::new();
+        while !input.is_empty() {
+            input.parse::<syn::Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            let lookahead = input.lookahead1();
+            if lookahead.peek(kw::deserialize) {
+                deserialize = Some(input.parse()?);
+            } else if lookahead.peek(kw::background) {
+                input.parse::<kw::background>()?;
+                input.parse::<syn::Token![=]>()?;
+                background = Some(input.parse()?);
+            } else if lookahead.peek(kw::error) {
+                input.parse::<kw::error>()?;
+                input.parse::<syn::Token![=]>()?;
+                error = Some(input.parse()?);
+            } else if lookahead.peek(kw::error_bound) {
+                input.parse::<kw::error_bound>()?;
+                input.parse::<syn::Token![=]>()?;
+                error_bound = Some(Punctuated::parse_separated_nonempty(input)?);
+            } else if input.peek(syn::Ident) && input.peek2(syn::Token![=]) {
+                let locale = input.parse::<syn::Ident>()?;
+                if !is_locale(&locale) {
+                    return Err(syn::Error::new(
+                        locale.span(),
+                        "expected a locale like `ja` or `pt_BR` for the title",
+                    ));
+                }
+                if titles
+                    .iter()
+                    .any(|(title_locale, _)| *title_locale == locale)
+                {
+                    return Err(syn::Error::new(
+                        locale.span(),
+                        format!("duplicate title in the locale `{locale}`"),
+                    ));
+                }
+                input.parse::<syn::Token![=]>()?;
+                titles.push((locale, input.parse()?));
+            } else {
+                return Err(lookahead.error());
+            }
+        }
+        Ok(Self {
+            title,
+            deserialize,
+            background,
+            error,
+            error_bound,
+            titles,
+        })
+    }
+}
+
+impl StoryAttr {
+    /// Returns the bounds of the error type of the story if customized.
+    pub fn error_bounds(&self) -> Option<TokenStream> {
+        let error = self
+            .error
+            .as_ref()
+            .map(|error| quote!(narrative::story::IsError<#error>));
+        let error_bound = self.error_bound.as_ref().map(|bound| quote!(#bound));
+        match (error, error_bound) {
+            (Some(error), Some(bound)) => Some(quote!(#error + #bound)),
+            (error, bound) => error.or(bound),
+        }
+    }
+
+    /// Returns the tokens of the error customization for diagnostics.
+    pub fn error_tokens(&self) -> Option<TokenStream> {
+        self.error
+            .as_ref()
+            .map(|error| quote!(#error))
+            .or_else(|| self.error_bound.as_ref().map(|bound| quote!(#bound)))
+    }
+
+    /// Whether the story customizes its error type, which makes sub story steps accept
+    /// environments whose errors can be converted into the error of the story.
+    pub fn converts_errors(&self) -> bool {
+        self.error.is_some() || self.error_bound.is_some()
+    }
+}
+
+impl Parse for BackgroundAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            title: input.parse()?,
+        })
     }
 }
 
@@ -25,5 +143,65 @@ mod tests {
             "Hello, world!"
         };
         assert_eq!(input.title.value(), "Hello, world!".to_string());
+        assert!(input.deserialize.is_none());
+    }
+
+    #[test]
+    fn test_story_attr_deserialize() {
+        let input: StoryAttr = syn::parse_quote! {
+            "Hello, world!", deserialize
+        };
+        assert_eq!(input.title.value(), "Hello, world!".to_string());
+        assert!(input.deserialize.is_some());
+    }
+
+    #[test]
+    fn test_story_attr_background() {
+        let input: StoryAttr = syn::parse_quote! {
+            "Hello, world!", background = setup::CommonSetup
+        };
+        let background = input.background.unwrap();
+        assert_eq!(background.segments.len(), 2);
+        assert_eq!(background.segments[1].ident, "CommonSetup");
+    }
+
+    #[test]
+    fn test_story_attr_error() {
+        let input: StoryAttr = syn::parse_quote! {
+            "Hello, world!", error = MyError, error_bound = std::error::Error + Send
+        };
+        assert!(input.converts_errors());
+        assert_eq!(
+            input.error_bounds().unwrap().to_string(),
+            quote!(narrative::story::IsError<MyError> + std::error::Error + Send).to_string()
+        );
+    }
+
+    #[test]
+    fn test_story_attr_titles() {
+        let input: StoryAttr = syn::parse_quote! {
+            "Hello, world!", deserialize, ja = "こんにちは、世界！", zh_Hant = "你好，世界！"
+        };
+        let titles: Vec<_> = input
+            .titles
+            .iter()
+            .map(|(locale, title)| (locale.to_string(), title.value()))
+            .collect();
+        assert_eq!(
+            titles,
+            vec![
+                ("ja".to_string(), "こんにちは、世界！".to_string()),
+                ("zh_Hant".to_string(), "你好，世界！".to_string()),
+            ]
+        );
+        assert!(
+            syn::parse_str::<StoryAttr>(r#""Hello, world!", japanese = "こんにちは""#).is_err()
+        );
+    }
+
+    #[test]
+    fn test_story_attr_unknown() {
+        let result = syn::parse_str::<StoryAttr>(r#""Hello, world!", unknown"#);
+        assert!(result.is_err());
     }
 }
@@ -0,0 +1,210 @@
+/// A segment of a step text like `I have {count} orange{count:plural(s)}`.
+#[derive(Debug, PartialEq)]
+pub(crate) enum TemplateSegment {
+    Literal(String),
+    Arg(TemplateArg),
+    /// A plural suffix like `{count:plural(s)}`, which is `s` unless `count` is one.
+    Plural {
+        arg: String,
+        suffix: String,
+    },
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct TemplateArg {
+    /// The name in the text like `user.name`.
+    pub name: String,
+    pub format: ArgFormat,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum ArgFormat {
+    /// A format spec of `format!` like `?` or `>5`, which is empty for `Display`.
+    Spec(String),
+    /// A function that formats the value, like `currency` in `{amount:currency}`.
+    Formatter(String),
+}
+
+impl TemplateArg {
+    /// The arg, const, or item that the name starts with, like `user` in `user.name`.
+    pub(crate) fn root(&self) -> &str {
+        self.name
+            .split_once('.')
+            .map_or(self.name.as_str(), |(root, _)| root)
+    }
+}
+
+/// Whether the text needs more than `format!`, like fields, formatters, or plural suffixes.
+pub(crate) fn is_rich(segments: &[TemplateSegment]) -> bool {
+    segments.iter().any(|segment| match segment {
+        TemplateSegment::Literal(_) => false,
+        TemplateSegment::Arg(arg) => {
+            arg.name.contains('.') || matches!(arg.format, ArgFormat::Formatter(_))
+        }
+        TemplateSegment::Plural { .. } => true,
+    })
+}
+
+pub(crate) fn parse_template(text: &str) -> Result<Vec<TemplateSegment>, String> {
+    let mut segments = Vec::new();
+    let mut literal = String::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '{' if chars.next_if(|(_, c)| *c == '{').is_some() => literal.push('{'),
+            '}' if chars.next_if(|(_, c)| *c == '}').is_some() => literal.push('}'),
+            '{' => {
+                let rest = &text[index + 1..];
+                let Some(end) = rest.find('}') else {
+                    return Err("unclosed `{` in the step text, escape it like `{{`".to_string());
+                };
+                for _ in 0..rest[..end].chars().count() + 1 {
+                    chars.next();
+                }
+                if !literal.is_empty() {
+                    segments.push(TemplateSegment::Literal(std::mem::take(&mut literal)));
+                }
+                segments.push(parse_arg(&rest[..end])?);
+            }
+            '}' => {
+                return Err("unmatched `}` in the step text, escape it like `}}`".to_string());
+            }
+            c => literal.push(c),
+        }
+    }
+    if !literal.is_empty() {
+        segments.push(TemplateSegment::Literal(literal));
+    }
+    Ok(segments)
+}
+
+fn parse_arg(arg: &str) -> Result<TemplateSegment, String> {
+    let (name, spec) = arg.split_once(':').unwrap_or((arg, ""));
+    let name = name.trim();
+    let mut parts = name.split('.');
+    let is_ident = |part: &str| {
+        part.chars()
+            .next()
+            .is_some_and(|c| c.is_alphabetic() || c == '_')
+            && part.chars().all(|c| c.is_alphanumeric() || c == '_')
+    };
+    let is_field = |part: &str| is_ident(part) || part.chars().all(|c| c.is_ascii_digit());
+    if !parts.next().is_some_and(is_ident) || !parts.all(|part| !part.is_empty() && is_field(part))
+    {
+        return Err(format!(
+            "expected the name of an arg or a const like `{{count}}` or `{{user.name}}`, found `{{{arg}}}`"
+        ));
+    }
+    if let Some(suffix) = spec
+        .strip_prefix("plural(")
+        .and_then(|rest| rest.strip_suffix(')'))
+    {
+        if name.contains('.') || suffix.is_empty() || suffix.contains(['(', ')']) {
+            return Err(format!(
+                "expected a plural suffix of an arg or a const like `{{count:plural(s)}}`, found `{{{arg}}}`"
+            ));
+        }
+        return Ok(TemplateSegment::Plural {
+            arg: name.to_string(),
+            suffix: suffix.to_string(),
+        });
+    }
+    // Single letters are the format traits like `x` for `LowerHex`.
+    let format = if is_ident(spec) && !matches!(spec, "x" | "X" | "o" | "b" | "e" | "E") {
+        ArgFormat::Formatter(spec.to_string())
+    } else {
+        ArgFormat::Spec(spec.to_string())
+    };
+    Ok(TemplateSegment::Arg(TemplateArg {
+        name: name.to_string(),
+        format,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn arg(name: &str, format: ArgFormat) -> TemplateSegment {
+        TemplateSegment::Arg(TemplateArg {
+            name: name.to_string(),
+            format,
+        })
+    }
+
+    #[test]
+    fn test_plain_text() {
+        let segments = parse_template("I have {count:>3} {{oranges}}").unwrap();
+        assert_eq!(
+            segments,
+            vec![
+                TemplateSegment::Literal("I have ".to_string()),
+                arg("count", ArgFormat::Spec(">3".to_string())),
+                TemplateSegment::Literal(" {oranges}".to_string()),
+            ]
+        );
+        assert!(!is_rich(&segments));
+    }
+
+    #[test]
+    fn test_fields_and_formatters() {
+        let segments = parse_template("{user.name} pays {amount:currency} in {amount:x}").unwrap();
+        assert_eq!(
+            segments,
+            vec![
+                arg("user.name", ArgFormat::Spec(String::new())),
+                TemplateSegment::Literal(" pays ".to_string()),
+                arg("amount", ArgFormat::Formatter("currency".to_string())),
+                TemplateSegment::Literal(" in ".to_string()),
+                arg("amount", ArgFormat::Spec("x".to_string())),
+            ]
+        );
+        assert!(is_rich(&segments));
+    }
+
+    #[test]
+    fn test_plural() {
+        let segments = parse_template("buy {count} box{count:plural(es)} (today)").unwrap();
+        assert_eq!(
+            segments,
+            vec![
+                TemplateSegment::Literal("buy ".to_string()),
+                arg("count", ArgFormat::Spec(String::new())),
+                TemplateSegment::Literal(" box".to_string()),
+                TemplateSegment::Plural {
+                    arg: "count".to_string(),
+                    suffix: "es".to_string()
+                },
+                TemplateSegment::Literal(" (today)".to_string()),
+            ]
+        );
+        assert!(is_rich(&segments));
+    }
+
+    #[test]
+    fn test_parens_after_args_are_literal() {
+        let segments = parse_template("call {name}(s) and open {file}(rw)").unwrap();
+        assert_eq!(
+            segments,
+            vec![
+                TemplateSegment::Literal("call ".to_string()),
+                arg("name", ArgFormat::Spec(String::new())),
+                TemplateSegment::Literal("(s) and open ".to_string()),
+                arg("file", ArgFormat::Spec(String::new())),
+                TemplateSegment::Literal("(rw)".to_string()),
+            ]
+        );
+        assert!(!is_rich(&segments));
+    }
+
+    #[test]
+    fn test_invalid_args() {
+        assert!(parse_template("{}").is_err());
+        assert!(parse_template("{user.}").is_err());
+        assert!(parse_template("{count").is_err());
+        assert!(parse_template("count}").is_err());
+        assert!(parse_template("{user.count:plural(s)}").is_err());
+        assert!(parse_template("{count:plural()}").is_err());
+    }
+}
@@ -0,0 +1,138 @@
+// Used to mix the items of a story with the items inherited from its parent story, which have
+// different types.
+
+use crate::{
+    step::StepArg,
+    story::{InvalidOverride, OverrideSource, StoryConst},
+    value::{BoxedValue, Value},
+};
+
+/// One of two types, which implements the traits implemented by both.
+#[derive(Clone, Copy)]
+pub enum Either<L, R> {
+    Left(L),
+    Right(R),
+}
+
+impl<L: std::fmt::Debug, R: std::fmt::Debug> std::fmt::Debug for Either<L, R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Either::Left(left) => left.fmt(f),
+            Either::Right(right) => right.fmt(f),
+        }
+    }
+}
+
+impl<L: serde::Serialize, R: serde::Serialize> serde::Serialize for Either<L, R> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Either::Left(left) => left.serialize(serializer),
+            Either::Right(right) => right.serialize(serializer),
+        }
+    }
+}
+
+impl<L: Iterator, R: Iterator> Iterator for Either<L, R> {
+    type Item = Either<L::Item, R::Item>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Either::Left(left) => left.next().map(Either::Left),
+            Either::Right(right) => right.next().map(Either::Right),
+        }
+    }
+}
+
+impl<L: StepArg, R: StepArg> StepArg for Either<L, R> {
+    fn name(&self) -> &'static str {
+        match self {
+            Either::Left(left) => left.name(),
+            Either::Right(right) => right.name(),
+        }
+    }
+
+    fn ty(&self) -> &'static str {
+        match self {
+            Either::Left(left) => left.ty(),
+            Either::Right(right) => right.ty(),
+        }
+    }
+
+    fn expr(&self) -> &'static str {
+        match self {
+            Either::Left(left) => left.expr(),
+            Either::Right(right) => right.expr(),
+        }
+    }
+
+    fn value(&self) -> impl Value {
+        match self {
+            Either::Left(left) => BoxedValue::new(left.value()),
+            Either::Right(right) => BoxedValue::new(right.value()),
+        }
+    }
+
+    fn deserialize_value<'de, D: serde::Deserializer<'de>>(
+        &self,
+        deserializer: D,
+    ) -> Result<BoxedValue, D::Error> {
+        match self {
+            Either::Left(left) => left.deserialize_value(deserializer),
+            Either::Right(right) => right.deserialize_value(deserializer),
+        }
+    }
+}
+
+impl<L: StoryConst, R: StoryConst> StoryConst for Either<L, R> {
+    fn name(&self) -> &'static str {
+        match self {
+            Either::Left(left) => left.name(),
+            Either::Right(right) => right.name(),
+        }
+    }
+
+    fn ty(&self) -> &'static str {
+        match self {
+            Either::Left(left) => left.ty(),
+            Either::Right(right) => right.ty(),
+        }
+    }
+
+    fn expr(&self) -> &'static str {
+        match self {
+            Either::Left(left) => left.expr(),
+            Either::Right(right) => right.expr(),
+        }
+    }
+
+    fn value(&self) -> impl Value {
+        match self {
+            Either::Left(left) => BoxedValue::new(left.value()),
+            Either::Right(right) => BoxedValue::new(right.value()),
+        }
+    }
+
+    fn overridden_by(&self) -> Option<OverrideSource> {
+        match self {
+            Either::Left(left) => left.overridden_by(),
+            Either::Right(right) => right.overridden_by(),
+        }
+    }
+
+    fn invalid_override(&self) -> Option<InvalidOverride> {
+        match self {
+            Either::Left(left) => left.invalid_override(),
+            Either::Right(right) => right.invalid_override(),
+        }
+    }
+
+    fn deserialize_value<'de, D: serde::Deserializer<'de>>(
+        &self,
+        deserializer: D,
+    ) -> Result<BoxedValue, D::Error> {
+        match self {
+            Either::Left(left) => left.deserialize_value(deserializer),
+            Either::Right(right) => right.deserialize_value(deserializer),
+        }
+    }
+}
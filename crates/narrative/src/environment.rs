@@ -1,3 +1,5 @@
+use std::marker::PhantomData;
+
 /// A dummy environment that does nothing on each step. Every story can be run on this environment.
 pub struct DummyEnvironment<E>(std::marker::PhantomData<E>);
 
@@ -12,3 +14,68 @@ impl<E> std::fmt::Debug for DummyEnvironment<E> {
         f.debug_tuple("DummyEnvironment").finish()
     }
 }
+
+/// An environment that runs a story on the inner environment, converting its errors by `C`.
+/// Stories with `error` or `error_bound` in the story attribute run sub stories on this, so the
+/// environment of a sub story can have a different error type.
+pub struct MapError<T, C>(T, PhantomData<C>);
+
+impl<T, E> MapError<T, IntoError<E>> {
+    /// Wraps the environment, converting its errors with `Into`.
+    pub fn new(inner: T) -> Self {
+        Self(inner, PhantomData)
+    }
+}
+
+impl<T, C> MapError<T, C> {
+    /// Wraps the environment of a sub story, whose errors are converted into `F`, the error of
+    /// the inner environment, then by `C`.
+    pub fn chain<U, F>(inner: U) -> MapError<U, ChainError<F, C>> {
+        MapError(inner, PhantomData)
+    }
+
+    /// Returns the inner environment.
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+
+    /// Unwraps the inner environment.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T, C> std::fmt::Debug for MapError<T, C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("MapError").finish_non_exhaustive()
+    }
+}
+
+/// A conversion of errors from `F`, which is resolved by types to keep the environments
+/// zero-cost.
+pub trait ErrorConversion<F> {
+    type To;
+    fn convert(error: F) -> Self::To;
+}
+
+/// Converts errors into `E` with `Into`.
+pub struct IntoError<E>(PhantomData<fn() -> E>);
+
+impl<F: Into<E>, E> ErrorConversion<F> for IntoError<E> {
+    type To = E;
+
+    fn convert(error: F) -> E {
+        error.into()
+    }
+}
+
+/// Converts errors into `F` with `Into`, then by `C`.
+pub struct ChainError<F, C>(PhantomData<fn(F) -> C>);
+
+impl<G: Into<F>, F, C: ErrorConversion<F>> ErrorConversion<G> for ChainError<F, C> {
+    type To = C::To;
+
+    fn convert(error: G) -> C::To {
+        C::convert(error.into())
+    }
+}
@@ -0,0 +1,68 @@
+//! Newtypes of `http` types, which serialize by `Display` as `http` has no serde support, so
+//! they can be used in stories without `#[foreign_type_for]`.
+
+use crate::independent_type::private::{SealedIndependentLeaf, SealedIndependentType};
+
+macro_rules! display_newtype {
+    ($($(#[$attr:meta])* $name:ident($foreign:ty);)*) => {
+        $(
+            $(#[$attr])*
+            #[derive(Clone, PartialEq, Eq, Hash)]
+            pub struct $name(pub $foreign);
+
+            impl std::fmt::Debug for $name {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    std::fmt::Debug::fmt(&self.0, f)
+                }
+            }
+
+            impl std::fmt::Display for $name {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    std::fmt::Display::fmt(&self.0, f)
+                }
+            }
+
+            impl serde::Serialize for $name {
+                fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.collect_str(&self.0)
+                }
+            }
+
+            impl<'de> serde::Deserialize<'de> for $name {
+                fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let text = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+                    text.parse().map(Self).map_err(serde::de::Error::custom)
+                }
+            }
+
+            impl std::ops::Deref for $name {
+                type Target = $foreign;
+                fn deref(&self) -> &Self::Target {
+                    &self.0
+                }
+            }
+
+            impl From<$foreign> for $name {
+                fn from(value: $foreign) -> Self {
+                    Self(value)
+                }
+            }
+
+            impl From<$name> for $foreign {
+                fn from(value: $name) -> Self {
+                    value.0
+                }
+            }
+
+            impl SealedIndependentType for $name {}
+            impl SealedIndependentLeaf for $name {}
+        )*
+    };
+}
+
+display_newtype! {
+    /// `http::StatusCode` serialized like `404`.
+    StatusCode(::http::StatusCode);
+    /// `http::Method` serialized like `GET`.
+    Method(::http::Method);
+}
@@ -1,34 +1,58 @@
 // important! this must be sealed to prevent downstream crates from implementing it.
-mod private {
+pub(crate) mod private {
     // Theorically, supertraits are not required, but it helps to maintain the list of supported types.
     // This is a trait implemented for types that can be used without dependencies.
     // Debug is required to format step arguments.
     // Clone is required to ensure the semantics of shared arguments are replicated.
     // Serialize is required to send arguments to external runners.
     pub trait SealedIndependentType: std::fmt::Debug + Clone + serde::Serialize {}
+
+    // Independent types that are not made of other types. Local type traits resolve tuples,
+    // arrays, and containers by their elements instead, or assertions of `&str` would be
+    // ambiguous between `&str` and `&T` with `str`.
+    pub trait SealedIndependentLeaf: SealedIndependentType {}
+
+    pub trait SealedIndependentContainer {}
 }
-use private::SealedIndependentType;
+use private::{SealedIndependentContainer, SealedIndependentLeaf, SealedIndependentType};
 
 pub trait IndependentType: SealedIndependentType {}
 
 impl<T: SealedIndependentType> IndependentType for T {}
 
+/// An independent type that is not made of other types, like `u32` or `String`, which local type
+/// traits use instead of `IndependentType` to resolve tuples and containers by their elements.
+pub trait IndependentLeaf: SealedIndependentLeaf {}
+
+impl<T: SealedIndependentLeaf> IndependentLeaf for T {}
+
+/// A container like `Vec<T>` or `Arc<[T]>`, which can be used in a story if its elements can.
+/// Containers are resolved by the type system rather than by their names, so type aliases and
+/// user types named like containers are asserted correctly.
+pub trait IndependentContainer: SealedIndependentContainer {
+    /// The contained type, which is a tuple for containers of multiple types like `(K, V)` of maps.
+    type Element: ?Sized;
+}
+
+/// The marker that distinguishes the impls of the local type traits for containers and generic
+/// local types from the ones for independent types. It is inferred in assertions.
+pub struct Contains<M>(std::marker::PhantomData<M>);
+
 macro_rules! local {
     ($($ty:ty),*) => {
         $(
             impl SealedIndependentType for $ty {}
+            impl SealedIndependentLeaf for $ty {}
         )*
     };
+}
+
+macro_rules! local_composite {
     ($gen1:tt; $($ty:ty),*) => {
         $(
             impl<$gen1: SealedIndependentType> SealedIndependentType for $ty {}
         )*
     };
-    ($gen1:ident, $gen2:ident; $($ty:ty),*) => {
-        $(
-            impl<$gen1: SealedIndependentType, $gen2: SealedIndependentType> SealedIndependentType for $ty {}
-        )*
-    };
 }
 
 macro_rules! local_tuple {
@@ -45,9 +69,43 @@ macro_rules! local_array {
     };
 }
 
+macro_rules! container {
+    (?Sized $gen:ident => $elem:ty; $($ty:ty),*) => {
+        $(
+            impl<$gen: ?Sized> SealedIndependentContainer for $ty {}
+            impl<$gen: ?Sized> IndependentContainer for $ty {
+                type Element = $elem;
+            }
+        )*
+    };
+    ($gen:ident => $elem:ty; $($ty:ty),*) => {
+        $(
+            impl<$gen> SealedIndependentContainer for $ty {}
+            impl<$gen> IndependentContainer for $ty {
+                type Element = $elem;
+            }
+        )*
+    };
+    ($gen1:ident, $gen2:ident => $elem:ty; $($ty:ty),*) => {
+        $(
+            impl<$gen1, $gen2> SealedIndependentContainer for $ty {}
+            impl<$gen1, $gen2> IndependentContainer for $ty {
+                type Element = $elem;
+            }
+        )*
+    };
+    ($gen1:ident, $gen2:ident, $gen3:ident => $elem:ty; $($ty:ty),*) => {
+        $(
+            impl<$gen1, $gen2, $gen3> SealedIndependentContainer for $ty {}
+            impl<$gen1, $gen2, $gen3> IndependentContainer for $ty {
+                type Element = $elem;
+            }
+        )*
+    };
+}
+
 local!(
     String,
-    &str,
     (),
     bool,
     char,
@@ -95,7 +153,10 @@ local!(
     std::net::SocketAddrV6
 );
 
-local!(T; std::ops::Range<T>, std::ops::RangeFrom<T>, std::ops::RangeTo<T>, std::ops::RangeInclusive<T>, std::ops::Bound<T>);
+// Composite types are independent types too, and local type traits resolve them by their elements.
+impl SealedIndependentType for &str {}
+
+local_composite!(T; std::ops::Range<T>, std::ops::RangeFrom<T>, std::ops::RangeTo<T>, std::ops::RangeInclusive<T>, std::ops::Bound<T>);
 
 local_tuple!(A);
 local_tuple!(A, B);
@@ -117,20 +178,112 @@ local_array!(
     26, 27, 28, 29, 30, 31, 32
 );
 
+container!(
+    ?Sized T => T;
+    &T,
+    Box<T>,
+    std::rc::Rc<T>,
+    std::sync::Arc<T>,
+    std::marker::PhantomData<T>
+);
+container!(
+    T => T;
+    [T],
+    Vec<T>,
+    Option<T>,
+    std::collections::VecDeque<T>,
+    std::collections::LinkedList<T>,
+    std::collections::BinaryHeap<T>,
+    std::collections::BTreeSet<T>,
+    std::num::Wrapping<T>,
+    std::num::Saturating<T>,
+    std::cmp::Reverse<T>,
+    std::ops::Range<T>,
+    std::ops::RangeFrom<T>,
+    std::ops::RangeTo<T>,
+    std::ops::RangeInclusive<T>,
+    std::ops::Bound<T>
+);
+container!(T, S => T; std::collections::HashSet<T, S>);
+container!(T, E => (T, E); Result<T, E>);
+container!(K, V => (K, V); std::collections::BTreeMap<K, V>);
+container!(K, V, S => (K, V); std::collections::HashMap<K, V, S>);
+
+impl<T, const N: usize> SealedIndependentContainer for [T; N] {}
+impl<T, const N: usize> IndependentContainer for [T; N] {
+    type Element = T;
+}
+
+impl<B: ToOwned + ?Sized> SealedIndependentContainer for std::borrow::Cow<'_, B> {}
+impl<B: ToOwned + ?Sized> IndependentContainer for std::borrow::Cow<'_, B> {
+    type Element = B;
+}
+
 #[cfg(feature = "serde_json")]
-impl SealedIndependentType for serde_json::Value {}
+local!(serde_json::Value);
 
 #[cfg(feature = "chrono")]
-mod chrono_impls {
+local!(
+    chrono::NaiveDate,
+    chrono::NaiveTime,
+    chrono::NaiveDateTime,
+    chrono::DateTime<chrono::Utc>,
+    chrono::DateTime<chrono::Local>,
+    chrono::DateTime<chrono::FixedOffset>,
+    chrono::Duration
+);
+
+#[cfg(feature = "uuid")]
+local!(uuid::Uuid);
+
+#[cfg(feature = "time")]
+local!(
+    time::Date,
+    time::Time,
+    time::PrimitiveDateTime,
+    time::OffsetDateTime,
+    time::UtcOffset,
+    time::Duration,
+    time::Month,
+    time::Weekday
+);
+
+#[cfg(feature = "jiff")]
+local!(
+    jiff::Timestamp,
+    jiff::Zoned,
+    jiff::Span,
+    jiff::SignedDuration,
+    jiff::civil::Date,
+    jiff::civil::Time,
+    jiff::civil::DateTime
+);
+
+#[cfg(feature = "rust_decimal")]
+local!(rust_decimal::Decimal);
+
+#[cfg(feature = "url")]
+local!(url::Url);
+
+#[cfg(feature = "bytes")]
+local!(bytes::Bytes, bytes::BytesMut);
+
+#[cfg(feature = "semver")]
+local!(semver::Version, semver::VersionReq);
+
+#[cfg(feature = "ulid")]
+local!(ulid::Ulid);
+
+#[cfg(feature = "indexmap")]
+mod indexmap_impls {
     use super::*;
-    impl SealedIndependentType for chrono::NaiveDate {}
-    impl SealedIndependentType for chrono::NaiveTime {}
-    impl SealedIndependentType for chrono::NaiveDateTime {}
-    impl SealedIndependentType for chrono::DateTime<chrono::Utc> {}
-    impl SealedIndependentType for chrono::DateTime<chrono::Local> {}
-    impl SealedIndependentType for chrono::DateTime<chrono::FixedOffset> {}
-    impl SealedIndependentType for chrono::Duration {}
+    container!(T, S => T; indexmap::IndexSet<T, S>);
+    container!(K, V, S => (K, V); indexmap::IndexMap<K, V, S>);
 }
 
-#[cfg(feature = "uuid")]
-impl SealedIndependentType for uuid::Uuid {}
+#[cfg(feature = "smallvec")]
+impl<A: smallvec::Array> SealedIndependentContainer for smallvec::SmallVec<A> {}
+#[cfg(feature = "smallvec")]
+impl<A: smallvec::Array> IndependentContainer for smallvec::SmallVec<A> {
+    type Element = A::Item;
+}
@@ -1,18 +1,27 @@
+pub mod either;
 pub mod environment;
+#[cfg(feature = "http")]
+pub mod http;
 mod independent_type;
+#[cfg(feature = "record")]
+pub mod record;
+#[cfg(feature = "remote")]
+pub mod remote;
 pub mod runner;
 pub mod step;
 pub mod story;
+pub mod text;
 pub mod value;
 
-pub use independent_type::IndependentType;
+pub use independent_type::{Contains, IndependentContainer, IndependentLeaf, IndependentType};
 pub use narrative_macros::*;
 
-/// Marker trait for types owned by a single story.
+/// Marker trait for types owned by a single story, or by the set of stories listed in
+/// `#[local_type_for(StoryA, StoryB)]`.
 /// Due to the Orphan Rule, using `#[local_type_for]` on remote types (types from other crates)
 /// will result in a compilation error.
-/// Additionally, attempting to use `#[local_type_for]` on the same type for multiple stories
-/// will cause conflicting trait implementations.
+/// Additionally, attempting to use `#[local_type_for]` on the same type more than once will cause
+/// conflicting trait implementations.
 pub trait StoryOwnedType: std::fmt::Debug + Clone + serde::Serialize {}
 
 pub mod serde {
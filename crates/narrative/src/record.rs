@@ -0,0 +1,445 @@
+// Recordings are plain data of serialized values, so they can be saved to a file in CI and loaded
+// later without the story definitions that produced them.
+
+use std::{
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    runner::{AsyncStoryRunner, StoryRunner},
+    step::{Run, RunAsync, Step, StepArg},
+    story::{RunStory, RunStoryAsync, StoryConst as _, StoryContext},
+};
+
+/// A recorded run of a story including its nested stories.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Recording {
+    pub events: Vec<RecordedEvent>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RecordedEvent {
+    StartStory {
+        story_id: String,
+        story_title: String,
+        /// The consts whose values are overridden in this run, like by env vars.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        overrides: Vec<RecordedOverride>,
+    },
+    Step(RecordedStep),
+    EndStory {
+        story_id: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct RecordedStep {
+    pub story_id: String,
+    pub step_id: String,
+    pub step_text: String,
+    pub args: Vec<RecordedArg>,
+    /// The time taken by the step, including its nested story.
+    pub duration: Duration,
+    pub outcome: Outcome,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct RecordedArg {
+    pub name: String,
+    pub ty: String,
+    pub expr: String,
+    pub value: serde_json::Value,
+}
+
+/// A const whose value is overridden, like by `#[env("BASE_URL")]`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct RecordedOverride {
+    pub name: String,
+    /// Where the value comes from, like `env BASE_URL`.
+    pub source: String,
+    pub value: serde_json::Value,
+    /// Why the value of the source is ignored, in which case `value` is the default value.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum Outcome {
+    Passed,
+    /// The step returned an error, formatted with `Debug`.
+    Failed {
+        error: String,
+    },
+}
+
+impl Recording {
+    /// Returns the recorded steps in the order they started.
+    pub fn steps(&self) -> impl Iterator<Item = &RecordedStep> {
+        self.events.iter().filter_map(|event| match event {
+            RecordedEvent::Step(step) => Some(step),
+            _ => None,
+        })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        serde_json::from_reader(std::io::BufReader::new(file)).map_err(std::io::Error::other)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let file = std::fs::File::create(path)?;
+        serde_json::to_writer_pretty(std::io::BufWriter::new(file), self)
+            .map_err(std::io::Error::other)
+    }
+}
+
+impl RecordedStep {
+    fn start(step: &impl Step) -> Self {
+        Self {
+            story_id: step.story().story_id().to_string(),
+            step_id: step.step_id().to_string(),
+            step_text: step.step_text(),
+            args: step
+                .args()
+                .map(|arg| RecordedArg {
+                    name: arg.name().to_string(),
+                    ty: arg.ty().to_string(),
+                    expr: arg.expr().to_string(),
+                    value: to_json(arg.value()),
+                })
+                .collect(),
+            duration: Duration::ZERO,
+            outcome: Outcome::Passed,
+        }
+    }
+}
+
+impl RecordedOverride {
+    fn of(story: &impl StoryContext) -> Vec<Self> {
+        story
+            .consts()
+            .filter_map(|story_const| {
+                let (source, error) = match story_const.overridden_by() {
+                    Some(source) => (source, None),
+                    None => {
+                        let invalid = story_const.invalid_override()?;
+                        (invalid.source, Some(invalid.to_string()))
+                    }
+                };
+                Some(Self {
+                    name: story_const.name().to_string(),
+                    source: source.to_string(),
+                    value: to_json(story_const.value()),
+                    error,
+                })
+            })
+            .collect()
+    }
+}
+
+fn to_json(value: impl serde::Serialize) -> serde_json::Value {
+    serde_json::to_value(value).unwrap_or_else(|err| serde_json::Value::String(err.to_string()))
+}
+
+/// A runner that records every story and step it runs.
+///
+/// # Example
+/// ```rust,ignore
+/// let mut runner = RecordingRunner::default();
+/// let result = MyFirstStoryContext.run_story_with_runner(&mut env, &mut runner);
+/// runner.into_recording().save("target/my_first_story.json")?;
+/// ```
+#[derive(Debug, Default)]
+pub struct RecordingRunner {
+    recording: Recording,
+}
+
+impl RecordingRunner {
+    pub fn recording(&self) -> &Recording {
+        &self.recording
+    }
+
+    pub fn into_recording(self) -> Recording {
+        self.recording
+    }
+
+    fn push_story_event(&mut self, event: RecordedEvent) {
+        self.recording.events.push(event);
+    }
+
+    // The step is pushed before it runs to keep the order of the steps in its nested story.
+    fn start_step(&mut self, step: &impl Step) -> (usize, Instant) {
+        self.recording
+            .events
+            .push(RecordedEvent::Step(RecordedStep::start(step)));
+        (self.recording.events.len() - 1, Instant::now())
+    }
+
+    fn end_step<E: std::fmt::Debug>(
+        &mut self,
+        (index, started): (usize, Instant),
+        result: &Result<(), E>,
+    ) {
+        if let RecordedEvent::Step(step) = &mut self.recording.events[index] {
+            step.duration = started.elapsed();
+            if let Err(err) = result {
+                step.outcome = Outcome::Failed {
+                    error: format!("{err:?}"),
+                };
+            }
+        }
+    }
+}
+
+impl<E: std::fmt::Debug> StoryRunner<E> for RecordingRunner {
+    fn start_story(&mut self, story: impl StoryContext) -> Result<(), E> {
+        self.push_story_event(RecordedEvent::StartStory {
+            story_id: story.story_id().to_string(),
+            story_title: story.story_title(),
+            overrides: RecordedOverride::of(&story),
+        });
+        Ok(())
+    }
+
+    fn end_story(&mut self, story: impl StoryContext) -> Result<(), E> {
+        self.push_story_event(RecordedEvent::EndStory {
+            story_id: story.story_id().to_string(),
+        });
+        Ok(())
+    }
+
+    fn run_step<T, S>(&mut self, step: T, state: &mut S) -> Result<(), E>
+    where
+        T: Step + Run<S, E>,
+    {
+        let started = self.start_step(&step);
+        let result = step.run_with_runner(state, self);
+        self.end_step(started, &result);
+        result
+    }
+
+    fn run_nested_story<S, Env>(
+        &mut self,
+        _step: impl Step,
+        nested_story: S,
+        env: &mut Env,
+    ) -> Result<(), E>
+    where
+        S::Step: Run<Env, E>,
+        S: StoryContext + RunStory<S, Env, E>,
+    {
+        nested_story.run_story_with_runner(env, self)
+    }
+}
+
+impl<E: std::fmt::Debug> AsyncStoryRunner<E> for RecordingRunner {
+    fn start_story(&mut self, story: impl StoryContext) -> Result<(), E> {
+        StoryRunner::<E>::start_story(self, story)
+    }
+
+    fn end_story(&mut self, story: impl StoryContext) -> Result<(), E> {
+        StoryRunner::<E>::end_story(self, story)
+    }
+
+    async fn run_step_async<T, Env>(&mut self, step: T, state: &mut Env) -> Result<(), E>
+    where
+        T: Step + RunAsync<Env, E> + Send + Sync,
+        Env: Send,
+    {
+        let started = self.start_step(&step);
+        let result = step.run_with_runner_async(state, self).await;
+        self.end_step(started, &result);
+        result
+    }
+
+    async fn run_nested_story_async<S, Env>(
+        &mut self,
+        _step: impl Step + Send,
+        nested_story: S,
+        env: &mut Env,
+    ) -> Result<(), E>
+    where
+        S: StoryContext + RunStoryAsync<S, Env, E> + Send + Sync,
+        Env: Send,
+        S::Step: RunAsync<Env, E> + Send + Sync,
+    {
+        nested_story.run_story_with_runner_async(env, self).await
+    }
+}
+
+/// A difference between a recorded step and the step of a new run, which are aligned by their
+/// story ids and step ids so an inserted or removed step doesn't shift the steps after it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StepDiff {
+    /// The step has different args or a different outcome. `index` is the position in the
+    /// recording.
+    Changed {
+        index: usize,
+        expected: RecordedStep,
+        actual: RecordedStep,
+    },
+    /// The step was recorded but not run this time. `index` is the position in the recording.
+    Missing {
+        index: usize,
+        expected: RecordedStep,
+    },
+    /// The step was run but not recorded. `index` is the position in the new run.
+    Added { index: usize, actual: RecordedStep },
+}
+
+/// A runner that records a new run and compares it with a previous recording.
+/// Durations are not compared.
+///
+/// # Example
+/// ```rust,ignore
+/// let mut runner = DiffingRunner::new(Recording::load("target/my_first_story.json")?);
+/// let _ = MyFirstStoryContext.run_story_with_runner(&mut DummyEnvironment::<MyError>::default(), &mut runner);
+/// for diff in runner.diff() {
+///     eprintln!("{diff:?}");
+/// }
+/// ```
+#[derive(Debug)]
+pub struct DiffingRunner {
+    expected: Recording,
+    actual: RecordingRunner,
+}
+
+impl DiffingRunner {
+    pub fn new(expected: Recording) -> Self {
+        Self {
+            expected,
+            actual: RecordingRunner::default(),
+        }
+    }
+
+    /// Returns the recording of the new run.
+    pub fn recording(&self) -> &Recording {
+        self.actual.recording()
+    }
+
+    /// Returns the differences of the new run from the recording so far.
+    pub fn diff(&self) -> Vec<StepDiff> {
+        let expected: Vec<_> = self.expected.steps().collect();
+        let actual: Vec<_> = self.actual.recording().steps().collect();
+        // The lengths of the longest common subsequences of the suffixes, by the step ids.
+        let mut lengths = vec![vec![0usize; actual.len() + 1]; expected.len() + 1];
+        for i in (0..expected.len()).rev() {
+            for j in (0..actual.len()).rev() {
+                lengths[i][j] = if same_id(expected[i], actual[j]) {
+                    lengths[i + 1][j + 1] + 1
+                } else {
+                    lengths[i + 1][j].max(lengths[i][j + 1])
+                };
+            }
+        }
+        let mut diffs = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < expected.len() || j < actual.len() {
+            if i < expected.len() && j < actual.len() && same_id(expected[i], actual[j]) {
+                if !same_step(expected[i], actual[j]) {
+                    diffs.push(StepDiff::Changed {
+                        index: i,
+                        expected: expected[i].clone(),
+                        actual: actual[j].clone(),
+                    });
+                }
+                i += 1;
+                j += 1;
+            } else if j == actual.len()
+                || (i < expected.len() && lengths[i + 1][j] >= lengths[i][j + 1])
+            {
+                diffs.push(StepDiff::Missing {
+                    index: i,
+                    expected: expected[i].clone(),
+                });
+                i += 1;
+            } else {
+                diffs.push(StepDiff::Added {
+                    index: j,
+                    actual: actual[j].clone(),
+                });
+                j += 1;
+            }
+        }
+        diffs
+    }
+}
+
+fn same_id(expected: &RecordedStep, actual: &RecordedStep) -> bool {
+    expected.story_id == actual.story_id && expected.step_id == actual.step_id
+}
+
+fn same_step(expected: &RecordedStep, actual: &RecordedStep) -> bool {
+    expected.args == actual.args && expected.outcome == actual.outcome
+}
+
+impl<E: std::fmt::Debug> StoryRunner<E> for DiffingRunner {
+    fn start_story(&mut self, story: impl StoryContext) -> Result<(), E> {
+        StoryRunner::<E>::start_story(&mut self.actual, story)
+    }
+
+    fn end_story(&mut self, story: impl StoryContext) -> Result<(), E> {
+        StoryRunner::<E>::end_story(&mut self.actual, story)
+    }
+
+    fn run_step<T, S>(&mut self, step: T, state: &mut S) -> Result<(), E>
+    where
+        T: Step + Run<S, E>,
+    {
+        let started = self.actual.start_step(&step);
+        let result = step.run_with_runner(state, self);
+        self.actual.end_step(started, &result);
+        result
+    }
+
+    fn run_nested_story<S, Env>(
+        &mut self,
+        _step: impl Step,
+        nested_story: S,
+        env: &mut Env,
+    ) -> Result<(), E>
+    where
+        S::Step: Run<Env, E>,
+        S: StoryContext + RunStory<S, Env, E>,
+    {
+        nested_story.run_story_with_runner(env, self)
+    }
+}
+
+impl<E: std::fmt::Debug> AsyncStoryRunner<E> for DiffingRunner {
+    fn start_story(&mut self, story: impl StoryContext) -> Result<(), E> {
+        StoryRunner::<E>::start_story(&mut self.actual, story)
+    }
+
+    fn end_story(&mut self, story: impl StoryContext) -> Result<(), E> {
+        StoryRunner::<E>::end_story(&mut self.actual, story)
+    }
+
+    async fn run_step_async<T, Env>(&mut self, step: T, state: &mut Env) -> Result<(), E>
+    where
+        T: Step + RunAsync<Env, E> + Send + Sync,
+        Env: Send,
+    {
+        let started = self.actual.start_step(&step);
+        let result = step.run_with_runner_async(state, self).await;
+        self.actual.end_step(started, &result);
+        result
+    }
+
+    async fn run_nested_story_async<S, Env>(
+        &mut self,
+        _step: impl Step + Send,
+        nested_story: S,
+        env: &mut Env,
+    ) -> Result<(), E>
+    where
+        S: StoryContext + RunStoryAsync<S, Env, E> + Send + Sync,
+        Env: Send,
+        S::Step: RunAsync<Env, E> + Send + Sync,
+    {
+        nested_story.run_story_with_runner_async(env, self).await
+    }
+}
@@ -0,0 +1,308 @@
+// The protocol is line-delimited JSON: the driver writes one request per line and waits for
+// exactly one reply line before sending the next request. Every request has a reply, so a remote
+// implementation can fail not only on steps but also on the start or the end of a story.
+// The same messages are used as HTTP request and response bodies by `HttpTransport`.
+
+mod http;
+mod process;
+
+use std::io::{BufRead, Write};
+
+use crate::{
+    step::{Step, StepArg},
+    story::{DynStoryContext, StoryContext},
+};
+
+pub use http::HttpTransport;
+pub use process::ChildProcess;
+
+/// A message sent from a driver to a remote step implementation.
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Request {
+    /// Sent when the root or a nested story starts.
+    StartStory {
+        story_id: &'static str,
+        story_title: String,
+    },
+    /// Sent for each step of a story.
+    /// If `nested_story` is set, the steps of the nested story follow this request.
+    Step {
+        story_id: &'static str,
+        step_id: &'static str,
+        step_text: String,
+        args: Vec<RemoteArg>,
+        nested_story: Option<RemoteStory>,
+    },
+    /// Sent when the root or a nested story ends.
+    EndStory { story_id: &'static str },
+}
+
+/// A step argument with its value serialized as JSON.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RemoteArg {
+    pub name: &'static str,
+    pub ty: &'static str,
+    pub expr: &'static str,
+    pub value: serde_json::Value,
+}
+
+/// A reference to a nested story.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RemoteStory {
+    pub story_id: &'static str,
+    pub story_title: String,
+}
+
+/// A message sent back from a remote step implementation for each request.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum Reply {
+    #[serde(alias = "pass")]
+    Ok {
+        #[serde(default)]
+        message: Option<String>,
+    },
+    /// Skips the step and its nested story, or the whole story if replied to `StartStory`.
+    Skip {
+        #[serde(default)]
+        message: Option<String>,
+    },
+    #[serde(alias = "fail")]
+    Error { message: String },
+}
+
+/// The reply of a remote implementation to a step or a story.
+/// `step_id` is `None` for the replies to the start and the end of a story.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteResult {
+    pub story_id: &'static str,
+    pub step_id: Option<&'static str>,
+    pub reply: Reply,
+}
+
+#[derive(Debug)]
+pub enum RemoteError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    /// The remote implementation closed the connection before replying.
+    Closed,
+    /// The HTTP endpoint returned a malformed or non-successful response.
+    Http(String),
+    /// The remote implementation reported a failure on the start or the end of a story.
+    StoryFailed {
+        story_id: &'static str,
+        message: String,
+    },
+    /// The remote implementation reported a failure of a step.
+    StepFailed {
+        story_id: &'static str,
+        step_id: &'static str,
+        message: String,
+    },
+}
+
+impl std::fmt::Display for RemoteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RemoteError::Io(err) => write!(f, "io error: {err}"),
+            RemoteError::Json(err) => write!(f, "json error: {err}"),
+            RemoteError::Closed => write!(f, "the remote implementation closed the connection"),
+            RemoteError::Http(message) => write!(f, "http error: {message}"),
+            RemoteError::StoryFailed { story_id, message } => {
+                write!(f, "story {story_id} failed: {message}")
+            }
+            RemoteError::StepFailed {
+                story_id,
+                step_id,
+                message,
+            } => write!(f, "step {story_id}::{step_id} failed: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for RemoteError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RemoteError::Io(err) => Some(err),
+            RemoteError::Json(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for RemoteError {
+    fn from(err: std::io::Error) -> Self {
+        RemoteError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for RemoteError {
+    fn from(err: serde_json::Error) -> Self {
+        RemoteError::Json(err)
+    }
+}
+
+impl Request {
+    /// Creates a step request by serializing the arguments of the step.
+    pub fn step(step: &impl Step) -> Result<Self, serde_json::Error> {
+        let args = step
+            .args()
+            .map(|arg| {
+                Ok(RemoteArg {
+                    name: arg.name(),
+                    ty: arg.ty(),
+                    expr: arg.expr(),
+                    value: serde_json::to_value(arg.value())?,
+                })
+            })
+            .collect::<Result<_, serde_json::Error>>()?;
+        Ok(Request::Step {
+            story_id: step.story().story_id(),
+            step_id: step.step_id(),
+            step_text: step.step_text(),
+            args,
+            nested_story: step.nested_story().map(|story| RemoteStory {
+                story_id: story.story_id(),
+                story_title: story.story_title(),
+            }),
+        })
+    }
+}
+
+/// A channel to a remote step implementation.
+pub trait Transport {
+    /// Sends a request and waits for its reply.
+    fn request(&mut self, request: &Request) -> Result<Reply, RemoteError>;
+}
+
+/// A transport that writes requests to a writer and reads replies from a reader, one JSON per line.
+pub struct JsonLines<R, W> {
+    reader: R,
+    writer: W,
+    line: String,
+}
+
+impl<R: BufRead, W: Write> JsonLines<R, W> {
+    pub fn new(reader: R, writer: W) -> Self {
+        Self {
+            reader,
+            writer,
+            line: String::new(),
+        }
+    }
+
+    pub fn into_inner(self) -> (R, W) {
+        (self.reader, self.writer)
+    }
+}
+
+impl<R: BufRead, W: Write> Transport for JsonLines<R, W> {
+    fn request(&mut self, request: &Request) -> Result<Reply, RemoteError> {
+        serde_json::to_writer(&mut self.writer, request)?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()?;
+        self.line.clear();
+        if self.reader.read_line(&mut self.line)? == 0 {
+            return Err(RemoteError::Closed);
+        }
+        Ok(serde_json::from_str(self.line.trim_end())?)
+    }
+}
+
+/// Runs stories by sending each step to a remote implementation instead of a story env.
+///
+/// # Example
+/// ```rust,ignore
+/// let process = ChildProcess::spawn(std::process::Command::new("python3").arg("steps.py"))?;
+/// let mut runner = RemoteRunner::new(process);
+/// runner.run_story(MyFirstStoryContext)?;
+/// // Stories from a registry or a filter are run without knowing their types.
+/// for story in stories {
+///     runner.run_dyn_story(story)?;
+/// }
+/// runner.into_inner().wait()?;
+/// ```
+pub struct RemoteRunner<T> {
+    transport: T,
+    results: Vec<RemoteResult>,
+}
+
+impl<T: Transport> RemoteRunner<T> {
+    pub fn new(transport: T) -> Self {
+        Self {
+            transport,
+            results: Vec::new(),
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.transport
+    }
+
+    /// The replies received so far, including passed and skipped ones with their messages.
+    pub fn results(&self) -> &[RemoteResult] {
+        &self.results
+    }
+
+    /// Runs the story and its nested stories, stopping at the first failure.
+    pub fn run_story(&mut self, story: impl Into<DynStoryContext>) -> Result<(), RemoteError> {
+        self.run_dyn_story(story.into())
+    }
+
+    /// Runs the story whose concrete type is unknown, like the ones from a registry.
+    pub fn run_dyn_story(&mut self, story: DynStoryContext) -> Result<(), RemoteError> {
+        crate::story::with_story_values(|| {
+            let story_id = story.story_id();
+            let start = Request::StartStory {
+                story_id,
+                story_title: story.story_title(),
+            };
+            match self.send(story_id, None, &start)? {
+                Reply::Ok { .. } => {}
+                Reply::Skip { .. } => return Ok(()),
+                Reply::Error { message } => {
+                    return Err(RemoteError::StoryFailed { story_id, message });
+                }
+            }
+            for step in story.steps() {
+                let step_id = step.step_id();
+                match self.send(story_id, Some(step_id), &Request::step(&step)?)? {
+                    Reply::Ok { .. } => {}
+                    Reply::Skip { .. } => continue,
+                    Reply::Error { message } => {
+                        return Err(RemoteError::StepFailed {
+                            story_id,
+                            step_id,
+                            message,
+                        });
+                    }
+                }
+                if let Some(nested_story) = step.dyn_nested_story() {
+                    self.run_dyn_story(nested_story)?;
+                }
+            }
+            if let Reply::Error { message } =
+                self.send(story_id, None, &Request::EndStory { story_id })?
+            {
+                return Err(RemoteError::StoryFailed { story_id, message });
+            }
+            Ok(())
+        })
+    }
+
+    fn send(
+        &mut self,
+        story_id: &'static str,
+        step_id: Option<&'static str>,
+        request: &Request,
+    ) -> Result<Reply, RemoteError> {
+        let reply = self.transport.request(request)?;
+        self.results.push(RemoteResult {
+            story_id,
+            step_id,
+            reply: reply.clone(),
+        });
+        Ok(reply)
+    }
+}
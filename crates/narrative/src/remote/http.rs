@@ -0,0 +1,88 @@
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpStream,
+};
+
+use super::{RemoteError, Reply, Request, Transport};
+
+/// A transport that POSTs each request as a JSON body to an HTTP endpoint and reads the reply
+/// from the response body. A new connection is opened for each request.
+pub struct HttpTransport {
+    host: String,
+    path: String,
+}
+
+impl HttpTransport {
+    /// `host` is the address with the port like `127.0.0.1:8080`, and `path` is the path of the
+    /// endpoint like `/steps`.
+    pub fn new(host: impl Into<String>, path: impl Into<String>) -> Self {
+        Self {
+            host: host.into(),
+            path: path.into(),
+        }
+    }
+}
+
+impl Transport for HttpTransport {
+    fn request(&mut self, request: &Request) -> Result<Reply, RemoteError> {
+        let body = serde_json::to_vec(request)?;
+        let mut stream = TcpStream::connect(&self.host)?;
+        write!(
+            stream,
+            "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            self.path,
+            self.host,
+            body.len()
+        )?;
+        stream.write_all(&body)?;
+        stream.flush()?;
+
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(RemoteError::Closed);
+        }
+        let status = line
+            .split_whitespace()
+            .nth(1)
+            .and_then(|status| status.parse::<u16>().ok())
+            .ok_or_else(|| {
+                RemoteError::Http(format!("malformed status line: {}", line.trim_end()))
+            })?;
+        let mut content_length = None;
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                return Err(RemoteError::Closed);
+            }
+            let header = line.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':')
+                && name.eq_ignore_ascii_case("content-length")
+            {
+                content_length = Some(value.trim().parse::<usize>().map_err(|_| {
+                    RemoteError::Http(format!("malformed content-length: {}", value.trim()))
+                })?);
+            }
+        }
+        let mut body = Vec::new();
+        match content_length {
+            Some(len) => {
+                body.resize(len, 0);
+                reader.read_exact(&mut body)?;
+            }
+            None => {
+                reader.read_to_end(&mut body)?;
+            }
+        }
+        if !(200..300).contains(&status) {
+            return Err(RemoteError::Http(format!(
+                "unexpected status {status}: {}",
+                String::from_utf8_lossy(&body)
+            )));
+        }
+        Ok(serde_json::from_slice(&body)?)
+    }
+}
@@ -0,0 +1,42 @@
+use std::{
+    io::BufReader,
+    process::{Child, ChildStdin, ChildStdout, Command, ExitStatus, Stdio},
+};
+
+use super::{JsonLines, RemoteError, Reply, Request, Transport};
+
+/// A child process that implements steps by reading requests from its stdin and writing replies
+/// to its stdout. Its stderr is inherited, so it can be used for logging.
+pub struct ChildProcess {
+    child: Child,
+    lines: JsonLines<BufReader<ChildStdout>, ChildStdin>,
+}
+
+impl ChildProcess {
+    /// Spawns the command with piped stdin and stdout.
+    pub fn spawn(command: &mut Command) -> std::io::Result<Self> {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+        Ok(Self {
+            child,
+            lines: JsonLines::new(BufReader::new(stdout), stdin),
+        })
+    }
+
+    /// Closes the stdin of the process and waits for it to exit.
+    pub fn wait(self) -> std::io::Result<ExitStatus> {
+        let Self { mut child, lines } = self;
+        drop(lines);
+        child.wait()
+    }
+}
+
+impl Transport for ChildProcess {
+    fn request(&mut self, request: &Request) -> Result<Reply, RemoteError> {
+        self.lines.request(request)
+    }
+}
@@ -1,13 +1,29 @@
 use crate::{
     runner::{AsyncStoryRunner, StoryRunner},
     story::{DynStoryContext, StoryContext},
-    value::{BoxedValue, Value},
+    text::{PatternSegment, StepTextSegment},
+    value::{BoxedValue, DeserializeFn, Value, deserialize_with, not_deserializable},
 };
 
 // T and E can be any type by implementing the story trait in any way.
 pub trait Step {
     /// Returns the text representation of the step.
     fn step_text(&self) -> String;
+    /// Returns the text of the step split into the literal parts and the formatted args.
+    fn step_segments(&self) -> Vec<StepTextSegment> {
+        vec![StepTextSegment::Literal(self.step_text())]
+    }
+    /// Returns the text representation of the step in the locale like `ja`, if the step has a
+    /// text for the locale.
+    fn step_text_in(&self, locale: &str) -> Option<String> {
+        let _ = locale;
+        None
+    }
+    /// Returns the pattern of the step text to find the step from a text. Steps without a
+    /// pattern match no text.
+    fn text_pattern(&self) -> &'static [PatternSegment] {
+        &[]
+    }
     /// Returns the id, which is the method name, of the step.
     fn step_id(&self) -> &'static str;
     /// Returns the arguments of the step.
@@ -47,14 +63,71 @@ pub trait StepArg: Clone + std::fmt::Debug {
     fn expr(&self) -> &'static str;
     /// Returns the actual value of the argument.
     fn value(&self) -> impl Value;
+    /// Deserializes a value of the argument's type, which is only supported for stories with
+    /// `deserialize` in the story attribute.
+    fn deserialize_value<'de, D: serde::Deserializer<'de>>(
+        &self,
+        deserializer: D,
+    ) -> Result<BoxedValue, D::Error> {
+        let _ = deserializer;
+        Err(not_deserializable(self.name()))
+    }
     // TODO: fn schema() -> Schema;
 }
 
+/// A function pointer that optionally takes the index of a repeated step.
+enum IndexedFn<T> {
+    Plain(fn() -> T),
+    Indexed(fn(usize) -> T, usize),
+}
+
+impl<T> Clone for IndexedFn<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for IndexedFn<T> {}
+
+impl<T> IndexedFn<T> {
+    fn call(&self) -> T {
+        match self {
+            IndexedFn::Plain(f) => f(),
+            IndexedFn::Indexed(f, index) => f(*index),
+        }
+    }
+}
+
+/// A function pointer of localized texts that optionally takes the index of a repeated step.
+#[derive(Clone, Copy)]
+enum LocalizedFn {
+    Plain(fn(&str) -> Option<String>),
+    Indexed(fn(usize, &str) -> Option<String>, usize),
+}
+
+impl LocalizedFn {
+    fn call(&self, locale: &str) -> Option<String> {
+        match self {
+            LocalizedFn::Plain(f) => f(locale),
+            LocalizedFn::Indexed(f, index) => f(*index, locale),
+        }
+    }
+}
+
+fn no_text_in(_locale: &str) -> Option<String> {
+    None
+}
+
+type DynArgs = Box<dyn Iterator<Item = DynStepArg> + Send + Sync>;
+
 #[derive(Clone, Copy)]
 pub struct DynStep {
-    step_text: fn() -> String,
+    step_text: IndexedFn<String>,
+    step_text_in: LocalizedFn,
+    step_segments: Option<IndexedFn<Vec<StepTextSegment>>>,
+    text_pattern: &'static [PatternSegment],
     step_id: &'static str,
-    args: fn() -> Box<dyn Iterator<Item = DynStepArg> + Send + Sync>,
+    args: IndexedFn<DynArgs>,
     story: fn() -> DynStoryContext,
     nested_story: fn() -> Option<DynStoryContext>,
 }
@@ -63,18 +136,69 @@ impl DynStep {
     pub const fn new(
         step_text: fn() -> String,
         step_id: &'static str,
-        args: fn() -> Box<dyn Iterator<Item = DynStepArg> + Send + Sync>,
+        args: fn() -> DynArgs,
         story: fn() -> DynStoryContext,
         nested_story: fn() -> Option<DynStoryContext>,
     ) -> Self {
         Self {
-            step_text,
+            step_text: IndexedFn::Plain(step_text),
+            step_text_in: LocalizedFn::Plain(no_text_in),
+            step_segments: None,
+            text_pattern: &[],
             step_id,
-            args,
+            args: IndexedFn::Plain(args),
             story,
             nested_story,
         }
     }
+
+    /// Sets the texts of the step in locales like `ja`.
+    pub const fn with_step_text_in(self, step_text_in: fn(&str) -> Option<String>) -> Self {
+        Self {
+            step_text_in: LocalizedFn::Plain(step_text_in),
+            ..self
+        }
+    }
+
+    /// Sets the segments of the step text, which is a single literal segment by default.
+    pub const fn with_step_segments(self, step_segments: fn() -> Vec<StepTextSegment>) -> Self {
+        Self {
+            step_segments: Some(IndexedFn::Plain(step_segments)),
+            ..self
+        }
+    }
+
+    /// Sets the pattern of the step text.
+    pub const fn with_text_pattern(self, text_pattern: &'static [PatternSegment]) -> Self {
+        Self {
+            text_pattern,
+            ..self
+        }
+    }
+
+    /// Makes the step the one at the index of the items of a repeated step, whose texts and args
+    /// are given by the functions taking the index instead of the ones set before.
+    pub const fn with_repeat_index(
+        self,
+        index: usize,
+        step_text: fn(usize) -> String,
+        step_text_in: fn(usize, &str) -> Option<String>,
+        step_segments: fn(usize) -> Vec<StepTextSegment>,
+        args: fn(usize) -> DynArgs,
+    ) -> Self {
+        Self {
+            step_text: IndexedFn::Indexed(step_text, index),
+            step_text_in: LocalizedFn::Indexed(step_text_in, index),
+            step_segments: Some(IndexedFn::Indexed(step_segments, index)),
+            args: IndexedFn::Indexed(args, index),
+            ..self
+        }
+    }
+
+    /// Returns the nested story without hiding its type.
+    pub fn dyn_nested_story(&self) -> Option<DynStoryContext> {
+        (self.nested_story)()
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -82,8 +206,9 @@ pub struct DynStepArg {
     name: &'static str,
     ty: &'static str,
     expr: &'static str,
-    value: fn() -> BoxedValue,
-    step_value: fn() -> BoxedValue,
+    value: IndexedFn<BoxedValue>,
+    step_value: IndexedFn<BoxedValue>,
+    deserialize: Option<DeserializeFn>,
 }
 
 impl DynStepArg {
@@ -98,21 +223,64 @@ impl DynStepArg {
             name,
             ty,
             expr,
-            value,
-            step_value,
+            value: IndexedFn::Plain(value),
+            step_value: IndexedFn::Plain(step_value),
+            deserialize: None,
+        }
+    }
+
+    /// Creates the arg that takes the item at the index of the items of a repeated step.
+    pub const fn new_repeated(
+        index: usize,
+        name: &'static str,
+        ty: &'static str,
+        expr: &'static str,
+        value: fn(usize) -> BoxedValue,
+        step_value: fn(usize) -> BoxedValue,
+    ) -> Self {
+        Self {
+            name,
+            ty,
+            expr,
+            value: IndexedFn::Indexed(value, index),
+            step_value: IndexedFn::Indexed(step_value, index),
+            deserialize: None,
+        }
+    }
+
+    /// Makes the argument deserializable.
+    pub const fn with_deserialize(self, deserialize: DeserializeFn) -> Self {
+        Self {
+            deserialize: Some(deserialize),
+            ..self
         }
     }
 }
 
 impl std::fmt::Debug for DynStepArg {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        (self.step_value)().fmt(f)
+        self.step_value.call().fmt(f)
     }
 }
 
 impl Step for DynStep {
     fn step_text(&self) -> String {
-        (self.step_text)()
+        self.step_text.call()
+    }
+
+    fn step_text_in(&self, locale: &str) -> Option<String> {
+        self.step_text_in.call(locale)
+    }
+
+    fn step_segments(&self) -> Vec<StepTextSegment> {
+        match &self.step_segments {
+            Some(step_segments) => step_segments.call(),
+            None => vec![StepTextSegment::Literal(self.step_text())],
+        }
+    }
+
+    fn text_pattern(&self) -> &'static [PatternSegment] {
+        self.text_pattern
     }
 
     fn step_id(&self) -> &'static str {
@@ -123,7 +291,7 @@ impl Step for DynStep {
         &self,
     ) -> impl Iterator<Item = impl crate::step::StepArg + Send + Sync + 'static> + Send + Sync + 'static
     {
-        (self.args)()
+        self.args.call()
     }
 
     fn story(&self) -> impl StoryContext<Step = Self> + Send + Sync + 'static {
@@ -149,6 +317,13 @@ impl StepArg for DynStepArg {
     }
 
     fn value(&self) -> impl Value {
-        (self.value)()
+        self.value.call()
+    }
+
+    fn deserialize_value<'de, D: serde::Deserializer<'de>>(
+        &self,
+        deserializer: D,
+    ) -> Result<BoxedValue, D::Error> {
+        deserialize_with(self.deserialize, self.name, deserializer)
     }
 }
@@ -1,17 +1,30 @@
+mod overrides;
+mod values;
+
 use std::future::Future;
 
 use crate::{
     runner::{AsyncStoryRunner, DefaultStoryRunner, StoryRunner},
     step::{DynStep, Run, RunAsync, Step},
-    value::{BoxedValue, Value},
+    text::StepMatch,
+    value::{BoxedValue, DeserializeFn, Value, deserialize_with, not_deserializable},
 };
 
+pub use overrides::{InvalidOverride, OverrideSource, env_override, invalid_env_override};
+pub use values::{story_value, with_story_values, with_story_values_async};
+
 /// A trait for handing a story in general.
 // `&self` is not actually used, and is for future compatibility and friendly API.
 pub trait StoryContext {
     type Step: Step + 'static;
     /// Returns the title of the story.
     fn story_title(&self) -> String;
+    /// Returns the title of the story in the locale like `ja`, if the story has a title for the
+    /// locale.
+    fn story_title_in(&self, locale: &str) -> Option<String> {
+        let _ = locale;
+        None
+    }
     /// Returns the identifier of the story.
     fn story_id(&self) -> &'static str;
     fn consts(
@@ -19,6 +32,41 @@ pub trait StoryContext {
     ) -> impl Iterator<Item = impl StoryConst + Send + Sync + 'static> + Send + Sync + 'static;
     /// Returns the steps of the story.
     fn steps(&self) -> impl Iterator<Item = Self::Step> + Send + Sync + 'static;
+    /// Finds the first step whose text pattern matches the text, like `I have 5 oranges` for the
+    /// step `I have {count} orange{count:plural(s)}`.
+    fn match_step(&self, text: &str) -> Option<StepMatch<Self::Step>> {
+        self.steps().find_map(|step| StepMatch::new(step, text))
+    }
+}
+
+/// Provides the values of the consts of a story. `C` is a struct generated for each story, which
+/// has a field of `fn() -> T` for each const.
+///
+/// This is implemented for [`DefaultBindings`] with the values in the story definition, and for
+/// the types generated for sub story steps that bind consts like `story: Login { USER = ADMIN }`.
+pub trait ConstBindings<C>: 'static {
+    const CONSTS: C;
+}
+
+/// The bindings of the values written in story definitions.
+pub struct DefaultBindings;
+
+/// A story context whose consts can be bound by a parent story.
+pub trait BindableContext: StoryContext {
+    /// The struct that has the values of the consts.
+    type ConstValues: 'static;
+}
+
+/// Implemented only for `E` itself. A story with `error = E` in the story attribute requires its
+/// error type to be `E` by this, and generic code can convert the error into `E` by `Into`.
+pub trait IsError<E>: Into<E> + sealed::Sealed<E> {}
+
+impl<E> IsError<E> for E {}
+
+mod sealed {
+    pub trait Sealed<E> {}
+
+    impl<E> Sealed<E> for E {}
 }
 
 pub trait StoryConst: Clone + std::fmt::Debug {
@@ -30,11 +78,31 @@ pub trait StoryConst: Clone + std::fmt::Debug {
     fn expr(&self) -> &'static str;
     /// Returns the value of the constant.
     fn value(&self) -> impl Value;
+    /// Returns the source that overrides the value now, like the env var of `#[env("BASE_URL")]`
+    /// if it is set.
+    fn overridden_by(&self) -> Option<OverrideSource> {
+        None
+    }
+    /// Returns the value of the override source that is ignored now, like the env var of
+    /// `#[env("PORT")]` set to a text that is not a number.
+    fn invalid_override(&self) -> Option<InvalidOverride> {
+        None
+    }
+    /// Deserializes a value of the constant's type, which is only supported for stories with
+    /// `deserialize` in the story attribute.
+    fn deserialize_value<'de, D: serde::Deserializer<'de>>(
+        &self,
+        deserializer: D,
+    ) -> Result<BoxedValue, D::Error> {
+        let _ = deserializer;
+        Err(not_deserializable(self.name()))
+    }
 }
 
 #[derive(Clone, Copy)]
 pub struct DynStoryContext {
     story_title: &'static str,
+    story_titles: &'static [(&'static str, &'static str)],
     story_id: &'static str,
     consts: fn() -> Box<dyn Iterator<Item = DynStoryConst> + Send + Sync>,
     steps: fn() -> Box<dyn Iterator<Item = DynStep> + Send + Sync>,
@@ -49,11 +117,28 @@ impl DynStoryContext {
     ) -> Self {
         Self {
             story_title,
+            story_titles: &[],
             story_id,
             consts,
             steps,
         }
     }
+
+    /// Sets the titles of the story in locales like `("ja", "...")`.
+    pub const fn with_story_titles(
+        self,
+        story_titles: &'static [(&'static str, &'static str)],
+    ) -> Self {
+        Self {
+            story_titles,
+            ..self
+        }
+    }
+
+    /// Returns the consts without hiding their type.
+    pub fn dyn_consts(&self) -> Box<dyn Iterator<Item = DynStoryConst> + Send + Sync> {
+        (self.consts)()
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -63,6 +148,9 @@ pub struct DynStoryConst {
     expr: &'static str,
     value: fn() -> BoxedValue,
     obj_value: fn() -> BoxedValue,
+    deserialize: Option<DeserializeFn>,
+    override_source: Option<OverrideSource>,
+    invalid_override: fn() -> Option<InvalidOverride>,
 }
 
 impl DynStoryConst {
@@ -79,6 +167,31 @@ impl DynStoryConst {
             expr,
             value,
             obj_value,
+            deserialize: None,
+            override_source: None,
+            invalid_override: || None,
+        }
+    }
+
+    /// Makes the constant deserializable.
+    pub const fn with_deserialize(self, deserialize: DeserializeFn) -> Self {
+        Self {
+            deserialize: Some(deserialize),
+            ..self
+        }
+    }
+
+    /// Makes the value of the constant overridden by the source at run time, unless
+    /// `invalid_override` returns the value of the source that cannot be the value.
+    pub const fn with_override(
+        self,
+        source: OverrideSource,
+        invalid_override: fn() -> Option<InvalidOverride>,
+    ) -> Self {
+        Self {
+            override_source: Some(source),
+            invalid_override,
+            ..self
         }
     }
 }
@@ -96,6 +209,13 @@ impl StoryContext for DynStoryContext {
         self.story_title.to_string()
     }
 
+    fn story_title_in(&self, locale: &str) -> Option<String> {
+        self.story_titles
+            .iter()
+            .find(|(title_locale, _)| *title_locale == locale)
+            .map(|(_, title)| title.to_string())
+    }
+
     fn story_id(&self) -> &'static str {
         self.story_id
     }
@@ -127,6 +247,25 @@ impl StoryConst for DynStoryConst {
     fn value(&self) -> impl Value {
         (self.value)()
     }
+
+    fn overridden_by(&self) -> Option<OverrideSource> {
+        let source = self.override_source?.if_set()?;
+        match (self.invalid_override)() {
+            Some(_) => None,
+            None => Some(source),
+        }
+    }
+
+    fn invalid_override(&self) -> Option<InvalidOverride> {
+        (self.invalid_override)()
+    }
+
+    fn deserialize_value<'de, D: serde::Deserializer<'de>>(
+        &self,
+        deserializer: D,
+    ) -> Result<BoxedValue, D::Error> {
+        deserialize_with(self.deserialize, self.name, deserializer)
+    }
 }
 
 pub trait RunStory<T, S, E> {
@@ -149,12 +288,14 @@ where
         env: &mut S,
         runner: &mut impl StoryRunner<E>,
     ) -> Result<(), E> {
-        runner.start_story(*self)?;
-        for step in self.steps() {
-            runner.run_step(step, env)?;
-        }
-        runner.end_story(*self)?;
-        Ok(())
+        with_story_values(|| {
+            runner.start_story(*self)?;
+            for step in self.steps() {
+                runner.run_step(step, env)?;
+            }
+            runner.end_story(*self)?;
+            Ok(())
+        })
     }
 }
 
@@ -182,11 +323,14 @@ where
         env: &mut Env,
         runner: &mut (impl AsyncStoryRunner<E> + Send),
     ) -> Result<(), E> {
-        runner.start_story(*self)?;
-        for step in self.steps() {
-            runner.run_step_async(step, env).await?;
-        }
-        runner.end_story(*self)?;
-        Ok(())
+        with_story_values_async(async move {
+            runner.start_story(*self)?;
+            for step in self.steps() {
+                runner.run_step_async(step, env).await?;
+            }
+            runner.end_story(*self)?;
+            Ok(())
+        })
+        .await
     }
 }
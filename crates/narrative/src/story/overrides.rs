@@ -0,0 +1,94 @@
+use std::{
+    collections::HashMap,
+    sync::{Mutex, OnceLock},
+};
+
+use serde::de;
+
+use crate::text::TextDeserializer;
+
+/// The source that overrides the value of a const at run time, like `#[env("BASE_URL")]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverrideSource {
+    /// The env var of the name.
+    Env(&'static str),
+}
+
+impl OverrideSource {
+    /// Returns the source if it has a value now.
+    pub fn if_set(self) -> Option<Self> {
+        match self {
+            OverrideSource::Env(name) => env_var(name).map(|_| self),
+        }
+    }
+}
+
+impl std::fmt::Display for OverrideSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OverrideSource::Env(name) => write!(f, "env {name}"),
+        }
+    }
+}
+
+/// A value of an override source that cannot be the value of the const, so the default value is
+/// used instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidOverride {
+    pub source: OverrideSource,
+    /// The text of the source, like the value of the env var.
+    pub text: String,
+    /// Why the text cannot be the value of the const.
+    pub error: String,
+}
+
+impl std::fmt::Display for InvalidOverride {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}={:?} is ignored: {}",
+            self.source, self.text, self.error
+        )
+    }
+}
+
+impl std::error::Error for InvalidOverride {}
+
+/// Returns the value of the env var for the const with `#[env("NAME")]`, parsed like `8080` for
+/// numbers and as is for strings.
+///
+/// If the env var cannot be parsed as the type of the const, `None` is returned and the default
+/// value is used. [`invalid_env_override`] tells why.
+pub fn env_override<T: de::Deserialize<'static>>(env_name: &'static str) -> Option<T> {
+    T::deserialize(TextDeserializer(env_var(env_name)?)).ok()
+}
+
+/// Returns the env var for the const with `#[env("NAME")]` if it is set but cannot be parsed as
+/// the type of the const.
+pub fn invalid_env_override<T: de::Deserialize<'static>>(
+    env_name: &'static str,
+) -> Option<InvalidOverride> {
+    let text = env_var(env_name)?;
+    let err = T::deserialize(TextDeserializer(text)).err()?;
+    Some(InvalidOverride {
+        source: OverrideSource::Env(env_name),
+        text: text.to_string(),
+        error: err.to_string(),
+    })
+}
+
+// Values like `&'static str` borrow the text of the env var, so the text is leaked once per name
+// and leaked again only when the env var changes.
+fn env_var(name: &'static str) -> Option<&'static str> {
+    static TEXTS: OnceLock<Mutex<HashMap<&'static str, &'static str>>> = OnceLock::new();
+    let text = std::env::var(name).ok()?;
+    let mut texts = TEXTS.get_or_init(Default::default).lock().unwrap();
+    match texts.get(name) {
+        Some(cached) if *cached == text => Some(*cached),
+        _ => {
+            let leaked = &*text.leak();
+            texts.insert(name, leaked);
+            Some(leaked)
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use std::{
+    any::{Any, TypeId},
+    cell::RefCell,
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+type Values = Arc<Mutex<HashMap<TypeId, Box<dyn Any + Send>>>>;
+
+thread_local! {
+    static CURRENT: RefCell<Option<Values>> = const { RefCell::new(None) };
+}
+
+/// Returns the value of a `#[narrative::value]` static keyed by `K`, which is evaluated once in a
+/// story run and cloned after that.
+/// Outside story runs, like listing the steps of a story, the value is evaluated each time.
+pub fn story_value<K: 'static, T: Clone + Send + 'static>(evaluate: impl FnOnce() -> T) -> T {
+    let Some(values) = CURRENT.with_borrow(Clone::clone) else {
+        return evaluate();
+    };
+    let cached = values
+        .lock()
+        .unwrap()
+        .get(&TypeId::of::<K>())
+        .and_then(|value| value.downcast_ref::<T>())
+        .cloned();
+    if let Some(value) = cached {
+        return value;
+    }
+    // The lock is released while evaluating, since a value may refer to other values.
+    let value = evaluate();
+    values
+        .lock()
+        .unwrap()
+        .entry(TypeId::of::<K>())
+        .or_insert_with(|| Box::new(value))
+        .downcast_ref::<T>()
+        .expect("a story value is keyed by its own type")
+        .clone()
+}
+
+/// Runs `f` as a story run, which has its own story values.
+/// A nested story run shares the story values of the outermost run.
+pub fn with_story_values<R>(f: impl FnOnce() -> R) -> R {
+    match CURRENT.with_borrow(Clone::clone) {
+        Some(_) => f(),
+        None => enter(&Values::default(), f),
+    }
+}
+
+/// Runs the future as a story run, which has its own story values even if the future moves
+/// between threads.
+/// A nested story run shares the story values of the outermost run.
+pub fn with_story_values_async<F: Future>(future: F) -> impl Future<Output = F::Output> {
+    WithStoryValues {
+        values: None,
+        future: Box::pin(future),
+    }
+}
+
+fn enter<R>(values: &Values, f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<Values>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            CURRENT.set(self.0.take());
+        }
+    }
+
+    let _restore = Restore(CURRENT.replace(Some(values.clone())));
+    f()
+}
+
+struct WithStoryValues<F> {
+    /// Taken from the story run polling this future on the first poll, if any.
+    values: Option<Values>,
+    future: Pin<Box<F>>,
+}
+
+impl<F: Future> Future for WithStoryValues<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        let values = this
+            .values
+            .get_or_insert_with(|| CURRENT.with_borrow(Clone::clone).unwrap_or_default());
+        enter(values, || this.future.as_mut().poll(cx))
+    }
+}
@@ -0,0 +1,337 @@
+use std::str::FromStr;
+
+use serde::de::{DeserializeOwned, IntoDeserializer as _, Visitor};
+
+use crate::{
+    step::{Step, StepArg as _},
+    value::BoxedValue,
+};
+
+/// A segment of the text of a step, which tells the args apart from the literal text so reports
+/// can highlight the args.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StepTextSegment {
+    /// A literal part of the text.
+    Literal(String),
+    /// A formatted arg like `{user.name}`, whose name is `user.name`.
+    Arg { name: &'static str, text: String },
+}
+
+impl StepTextSegment {
+    /// Returns the text of the segment.
+    pub fn text(&self) -> &str {
+        match self {
+            StepTextSegment::Literal(text) => text,
+            StepTextSegment::Arg { text, .. } => text,
+        }
+    }
+}
+
+/// Decides the plural suffix like `{count:plural(s)}` in `{count} orange{count:plural(s)}`.
+pub trait Plural {
+    /// Returns true if the value is plural.
+    fn is_plural(&self) -> bool;
+}
+
+macro_rules! impl_plural {
+    ($($ty:ty),*) => {
+        $(
+            impl Plural for $ty {
+                fn is_plural(&self) -> bool {
+                    *self != 1 as $ty
+                }
+            }
+        )*
+    };
+}
+
+impl_plural!(
+    u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64
+);
+
+impl<T: Plural + ?Sized> Plural for &T {
+    fn is_plural(&self) -> bool {
+        (**self).is_plural()
+    }
+}
+
+#[doc(hidden)]
+pub fn plural_suffix<'a>(count: &impl Plural, suffix: &'a str) -> &'a str {
+    if count.is_plural() { suffix } else { "" }
+}
+
+/// A segment of the pattern of a step text, which finds the step from a text like
+/// `I have 5 oranges` by [`StoryContext::match_step`](crate::story::StoryContext::match_step).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatternSegment {
+    /// A literal part of the text, which must appear as is.
+    Literal(&'static str),
+    /// An arg like `{count}`, which matches any non-empty text.
+    Arg(&'static str),
+    /// A const like `{SHOP}` or `{USER.name}`, which must appear as its formatted value.
+    Const(&'static str),
+    /// A plural suffix like `s` in `{count:plural(s)}`, which may or may not appear.
+    Plural(&'static str),
+}
+
+/// A step found from a text, with the texts of its args.
+#[derive(Clone)]
+pub struct StepMatch<S> {
+    step: S,
+    args: Vec<(&'static str, String)>,
+}
+
+impl<S: Step> StepMatch<S> {
+    /// Matches the text to the pattern of the step, ignoring the leading and trailing whitespace.
+    pub fn new(step: S, text: &str) -> Option<Self> {
+        let pattern = step.text_pattern();
+        // Consts are compared to their values now, which may be overridden at run time.
+        let step_segments = if pattern
+            .iter()
+            .any(|segment| matches!(segment, PatternSegment::Const(_)))
+        {
+            step.step_segments()
+        } else {
+            Vec::new()
+        };
+        let const_text = |name: &str| {
+            step_segments.iter().find_map(|segment| match segment {
+                StepTextSegment::Arg { name: arg, text } if *arg == name => Some(text.as_str()),
+                _ => None,
+            })
+        };
+        let args = match_segments(pattern, text.trim(), const_text)?;
+        Some(Self { step, args })
+    }
+
+    /// Returns the matched step.
+    pub fn step(&self) -> &S {
+        &self.step
+    }
+
+    /// Returns the matched step, discarding the texts of the args.
+    pub fn into_step(self) -> S {
+        self.step
+    }
+
+    /// Returns the names and the texts of the matched args in the order of the step text.
+    pub fn args(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        self.args.iter().map(|(name, text)| (*name, text.as_str()))
+    }
+
+    /// Returns the text matched to the arg like `count` in `{count}`.
+    pub fn arg_text(&self, name: &str) -> Option<&str> {
+        self.args()
+            .find(|(arg_name, _)| *arg_name == name)
+            .map(|(_, text)| text)
+    }
+
+    /// Parses the text matched to the arg by `FromStr`.
+    pub fn parse_arg<T: FromStr>(&self, name: &str) -> Option<Result<T, T::Err>> {
+        self.arg_text(name).map(str::parse)
+    }
+
+    /// Deserializes the text matched to the arg, which is read as a number or a bool if possible
+    /// and as a string otherwise.
+    pub fn deserialize_arg<T: DeserializeOwned>(
+        &self,
+        name: &str,
+    ) -> Option<Result<T, serde::de::value::Error>> {
+        self.arg_text(name)
+            .map(|text| T::deserialize(TextDeserializer(text)))
+    }
+
+    /// Deserializes the matched args of the step into the types of the args, which is only
+    /// supported for stories with `deserialize` in the story attribute.
+    pub fn deserialize_arg_values(
+        &self,
+    ) -> Result<Vec<(&'static str, BoxedValue)>, serde::de::value::Error> {
+        self.step
+            .args()
+            .filter_map(|arg| {
+                let text = self.arg_text(arg.name())?;
+                Some(
+                    arg.deserialize_value(TextDeserializer(text))
+                        .map(|value| (arg.name(), value)),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Matches the segments in one pass, anchoring the args on the literal text after them. Adjacent
+/// args take one char each except the last, and an arg takes the text up to the first occurrence
+/// of the literal after it, or up to the end for the last segment.
+fn match_segments<'a>(
+    segments: &[PatternSegment],
+    text: &str,
+    const_text: impl Fn(&str) -> Option<&'a str>,
+) -> Option<Vec<(&'static str, String)>> {
+    let mut args = Vec::new();
+    let mut pending: Vec<&'static str> = Vec::new();
+    let mut pending_plural = None;
+    let mut rest = text;
+    for (index, segment) in segments.iter().enumerate() {
+        let literal = match segment {
+            PatternSegment::Arg(name) => {
+                pending.push(name);
+                continue;
+            }
+            PatternSegment::Plural(suffix) if pending.is_empty() => {
+                rest = rest.strip_prefix(suffix).unwrap_or(rest);
+                continue;
+            }
+            PatternSegment::Plural(suffix) => {
+                pending_plural = Some(*suffix);
+                continue;
+            }
+            PatternSegment::Literal(literal) => *literal,
+            PatternSegment::Const(name) => const_text(name)?,
+        };
+        if pending.is_empty() {
+            rest = rest.strip_prefix(literal)?;
+            continue;
+        }
+        // Each pending arg needs at least one char before the literal.
+        let min = rest
+            .char_indices()
+            .nth(pending.len() - 1)
+            .map(|(start, c)| start + c.len_utf8())?;
+        let end = if index == segments.len() - 1 {
+            rest.len()
+                .checked_sub(literal.len())
+                .filter(|end| *end >= min && rest[*end..] == *literal)?
+        } else {
+            min + rest[min..].find(literal)?
+        };
+        assign_args(&mut args, &mut pending, pending_plural.take(), &rest[..end])?;
+        rest = &rest[end + literal.len()..];
+    }
+    if pending.is_empty() {
+        return rest.is_empty().then_some(args);
+    }
+    assign_args(&mut args, &mut pending, pending_plural, rest)?;
+    Some(args)
+}
+
+/// Splits the text to the adjacent args, dropping the plural suffix after them if it appears.
+fn assign_args(
+    args: &mut Vec<(&'static str, String)>,
+    pending: &mut Vec<&'static str>,
+    plural: Option<&str>,
+    mut text: &str,
+) -> Option<()> {
+    if let Some(stripped) = plural.and_then(|suffix| text.strip_suffix(suffix))
+        && stripped.chars().count() >= pending.len()
+    {
+        text = stripped;
+    }
+    let last = pending.pop()?;
+    for name in pending.drain(..) {
+        let c = text.chars().next()?;
+        args.push((name, c.to_string()));
+        text = &text[c.len_utf8()..];
+    }
+    if text.is_empty() {
+        return None;
+    }
+    args.push((last, text.to_string()));
+    Some(())
+}
+
+/// Deserializes a text like the text of a matched arg or an env var, which is parsed for the
+/// numbers, bools, and chars, and guessed as a plain number like `8080` or a bool for untyped
+/// values.
+pub(crate) struct TextDeserializer<'a>(pub(crate) &'a str);
+
+/// Whether the text is a number written as is, like `42` or `-1.5`, rather than a text that only
+/// parses as a number like `inf`, `NaN`, `1e5`, or `007`. This is the same check as the numbers in
+/// the step texts of `include_feature!`.
+fn is_plain_number<T: std::str::FromStr + std::fmt::Display>(text: &str) -> bool {
+    text.chars()
+        .all(|c| c.is_ascii_digit() || c == '-' || c == '.')
+        && text
+            .parse::<T>()
+            .is_ok_and(|value| value.to_string() == text)
+}
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                visitor.$visit(self.0.parse().map_err(serde::de::Error::custom)?)
+            }
+        )*
+    };
+}
+
+impl<'a> serde::Deserializer<'a> for TextDeserializer<'a> {
+    type Error = serde::de::value::Error;
+
+    fn deserialize_any<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if is_plain_number::<u64>(self.0) {
+            visitor.visit_u64(self.0.parse().map_err(serde::de::Error::custom)?)
+        } else if is_plain_number::<i64>(self.0) {
+            visitor.visit_i64(self.0.parse().map_err(serde::de::Error::custom)?)
+        } else if is_plain_number::<f64>(self.0) {
+            visitor.visit_f64(self.0.parse().map_err(serde::de::Error::custom)?)
+        } else if let Ok(value) = self.0.parse::<bool>() {
+            visitor.visit_bool(value)
+        } else {
+            visitor.visit_borrowed_str(self.0)
+        }
+    }
+
+    deserialize_parsed! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_str<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_borrowed_str(self.0)
+    }
+
+    fn deserialize_string<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_borrowed_str(self.0)
+    }
+
+    fn deserialize_option<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'a>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'a>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.0
+            .into_deserializer()
+            .deserialize_enum(name, variants, visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        <V: Visitor<'a>>
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
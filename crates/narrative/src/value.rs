@@ -1,9 +1,56 @@
 pub trait Value: std::any::Any + std::fmt::Debug + serde::Serialize {}
 
-pub trait DynValue: std::any::Any + std::fmt::Debug + erased_serde::Serialize {}
+pub trait DynValue: std::any::Any + std::fmt::Debug + erased_serde::Serialize {
+    fn as_any(&self) -> &dyn std::any::Any;
+}
 
 impl<T: std::fmt::Debug + serde::Serialize + 'static> Value for T {}
-impl<T: std::fmt::Debug + serde::Serialize + 'static> DynValue for T {}
+impl<T: std::fmt::Debug + serde::Serialize + 'static> DynValue for T {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+/// A value that can be read back from a serialized form.
+/// Borrowed types like `&str` are not deserializable, so use owned types like `String` instead.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` cannot be deserialized as a step argument or a story const",
+    label = "this type must be owned and implement `Deserialize`",
+    note = "borrowed types like `&str` cannot be deserialized, use owned types like `String` instead"
+)]
+pub trait DeserializableValue: Value + serde::de::DeserializeOwned {}
+
+impl<T: Value + serde::de::DeserializeOwned> DeserializableValue for T {}
+
+/// A function that deserializes a value of a specific type.
+pub type DeserializeFn = for<'de> fn(
+    &mut dyn erased_serde::Deserializer<'de>,
+) -> Result<BoxedValue, erased_serde::Error>;
+
+/// Deserializes a value of `T`. This can be used as a [`DeserializeFn`].
+pub fn deserialize_boxed<T: DeserializableValue>(
+    deserializer: &mut dyn erased_serde::Deserializer<'_>,
+) -> Result<BoxedValue, erased_serde::Error> {
+    erased_serde::deserialize::<T>(deserializer).map(BoxedValue::new)
+}
+
+pub(crate) fn deserialize_with<'de, D: serde::Deserializer<'de>>(
+    deserialize: Option<DeserializeFn>,
+    name: &str,
+    deserializer: D,
+) -> Result<BoxedValue, D::Error> {
+    let Some(deserialize) = deserialize else {
+        return Err(not_deserializable(name));
+    };
+    let mut deserializer = <dyn erased_serde::Deserializer>::erase(deserializer);
+    deserialize(&mut deserializer).map_err(serde::de::Error::custom)
+}
+
+pub(crate) fn not_deserializable<E: serde::de::Error>(name: &str) -> E {
+    E::custom(format_args!(
+        "`{name}` is not deserializable, add `deserialize` to the story attribute to enable it"
+    ))
+}
 
 pub struct BoxedValue(Box<dyn DynValue>);
 
@@ -11,6 +58,11 @@ impl BoxedValue {
     pub fn new(value: impl DynValue) -> Self {
         Self(Box::new(value))
     }
+
+    /// Returns the value if it is of type `T`.
+    pub fn downcast_ref<T: 'static>(&self) -> Option<&T> {
+        (*self.0).as_any().downcast_ref()
+    }
 }
 
 impl std::fmt::Debug for BoxedValue {
@@ -0,0 +1,19 @@
+#[narrative::background]
+trait CommonSetup {
+    #[step("start the server")]
+    fn start_server();
+}
+
+#[narrative::story("Log in")]
+trait Login {
+    #[step("log in")]
+    fn log_in();
+}
+
+#[narrative::story("Check out", background = CommonSetup)]
+trait Checkout: Login {
+    #[step("check out")]
+    fn check_out();
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+mod other {
+    // Not a container of the standard library, though it is named like one
+    #[derive(Debug, Clone, serde::Serialize)]
+    pub struct Option<T>(pub T);
+}
+
+#[narrative::story("Container named type")]
+trait ContainerNamedType {
+    #[step("Given a type named like a container", arg = other::Option(1))]
+    fn use_container_named_type(arg: other::Option<u32>);
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+#[narrative::story("Borrowed types cannot be deserialized", deserialize)]
+trait DeserializeBorrowed {
+    const NAME: &str = "Ryo";
+    #[step("Hi, I'm {name}", name = NAME)]
+    fn greet(name: &str);
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+#[narrative::story("Log in")]
+trait Login {
+    #[step("log in")]
+    fn log_in();
+}
+
+#[narrative::story("Check out", error = std::io::Error)]
+trait Checkout: Login {
+    #[step("check out")]
+    fn check_out();
+}
+
+fn main() {}
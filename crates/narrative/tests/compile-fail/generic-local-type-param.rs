@@ -0,0 +1,16 @@
+#[derive(Debug, Clone, serde::Serialize)]
+struct NotLocalType;
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[narrative::local_type_for(Catalog)]
+struct Page<T> {
+    items: Vec<T>,
+}
+
+#[narrative::story("Browse the catalog")]
+trait Catalog {
+    #[step("see {page:?}", page = Page { items: vec![NotLocalType] })]
+    fn see_page(page: Page<NotLocalType>);
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+pub trait Backend {}
+
+#[narrative::story("Store users")]
+trait StoreUsers<T: Backend, const N: usize> {
+    #[step("store {count} users", count = N)]
+    fn store(backend: T, count: usize);
+}
+
+#[narrative::story("Check out")]
+trait Checkout<T: Backend>: StoreUsers<T, 1> {
+    #[step("check out")]
+    fn check_out();
+}
+
+fn main() {}
@@ -0,0 +1,23 @@
+#[derive(Debug, Clone, serde::Serialize)]
+#[narrative::local_type_for(SignUp, Checkout)]
+struct UserId(&'static str);
+
+#[narrative::story("Sign up")]
+trait SignUp {
+    #[step("sign up as {id:?}", id = UserId("ryo"))]
+    fn sign_up(id: UserId);
+}
+
+#[narrative::story("Checkout")]
+trait Checkout {
+    #[step("pay as {id:?}", id = UserId("ryo"))]
+    fn pay(id: UserId);
+}
+
+#[narrative::story("Admin")]
+trait Admin {
+    #[step("ban {id:?}", id = UserId("ryo"))]
+    fn ban(id: UserId);
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+#[narrative::story("Buy oranges")]
+trait BuyOranges {
+    #[step("I have {count} oranges", count = 3, text(ja = "オレンジを{amount}個持っている"))]
+    fn have_oranges(count: u32);
+}
+
+fn main() {}
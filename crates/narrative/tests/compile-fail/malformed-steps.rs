@@ -0,0 +1,21 @@
+#[narrative::story("Story with malformed steps")]
+trait MalformedStepsStory {
+    #[step("take self")]
+    fn take_self(self);
+    #[step("have a body")]
+    fn have_body() {}
+    #[step("be generic", value = 1)]
+    fn be_generic<T>(value: T);
+    #[step("take a pair", pair = (1, 2))]
+    fn take_pair((first, second): (u32, u32));
+    #[step("take the same args", id = 1)]
+    fn take_same_args(id: u32, id: u32);
+    #[step("give the same values", id = 1, id = 2)]
+    fn give_same_values(id: u32);
+
+    fn forget_step_attr();
+
+    println!("not an item");
+}
+
+fn main() {}
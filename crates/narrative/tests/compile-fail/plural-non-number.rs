@@ -0,0 +1,13 @@
+#[narrative::story("Call users")]
+trait CallUsers {
+    const GREETING: &str = "hello";
+
+    #[step("call {name} and {count} friend{count:plural(s)}", name = "ryo", count = 2)]
+    fn call(name: &str, count: u32);
+    #[step("call {name}{name:plural(s)}", name = "ryo")]
+    fn call_name(name: &str);
+    #[step("say {GREETING}{GREETING:plural(s)}")]
+    fn greet();
+}
+
+fn main() {}
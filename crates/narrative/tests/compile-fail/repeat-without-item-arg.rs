@@ -0,0 +1,9 @@
+#[narrative::story("Check out")]
+trait Checkout {
+    const ITEMS: &[&str] = &["apple", "banana"];
+
+    #[step(repeat = ITEMS, "add {item} to the cart", item = "apple")]
+    fn add_item(item: &str);
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+#[narrative::story("Log in")]
+trait Login {
+    const USER: &str = "guest";
+    #[step("log in as {USER}")]
+    fn log_in();
+}
+
+#[narrative::story("Log in as admin")]
+trait LogInAsAdmin {
+    #[step(story: Login { NAME = "admin" }, "log in as admin")]
+    fn log_in_as_admin();
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+#[narrative::story("Buy oranges")]
+trait BuyOranges {
+    const SHOP: &str = "Fruit Shop";
+
+    #[step("I have {cnt} oranges", count = 3)]
+    fn have_oranges(count: u32);
+    #[step("go to {SHOPS}", text(ja = "{shop}に行く"))]
+    fn go_to_shop();
+    #[step("pay {amount} yen")]
+    fn pay(amount: u32);
+}
+
+fn main() {}
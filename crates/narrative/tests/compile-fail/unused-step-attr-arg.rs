@@ -0,0 +1,9 @@
+#![deny(unused_variables)]
+
+#[narrative::story("Buy oranges")]
+trait BuyOranges {
+    #[step("I have {count} oranges from {shop}", count = 3, shop = "Fruit Shop", color = "orange")]
+    fn have_oranges(count: u32);
+}
+
+fn main() {}
@@ -0,0 +1,114 @@
+use std::convert::Infallible;
+
+#[narrative::story("Log in as an admin")]
+trait Login {
+    const USER: &str = "admin";
+    #[step("log in as {user}", user = USER)]
+    fn log_in(user: &str);
+    #[step("open the dashboard")]
+    fn open_dashboard();
+}
+
+#[narrative::story("Check out a cart")]
+trait Checkout: Login {
+    const ITEMS: u32 = 3;
+    #[step("add {count} items to the cart", count = ITEMS)]
+    fn add_items(count: u32);
+    #[step("pay")]
+    fn pay();
+}
+
+#[derive(Default)]
+struct Env {
+    log: Vec<String>,
+}
+
+impl Login for Env {
+    type Error = Infallible;
+
+    fn log_in(&mut self, user: &str) -> Result<(), Self::Error> {
+        self.log.push(format!("log_in: {user}"));
+        Ok(())
+    }
+
+    fn open_dashboard(&mut self) -> Result<(), Self::Error> {
+        self.log.push("open_dashboard".into());
+        Ok(())
+    }
+}
+
+// The error type is inherited from `Login`.
+impl Checkout for Env {
+    fn add_items(&mut self, count: u32) -> Result<(), Self::Error> {
+        self.log.push(format!("add_items: {count}"));
+        Ok(())
+    }
+
+    fn pay(&mut self) -> Result<(), Self::Error> {
+        self.log.push("pay".into());
+        Ok(())
+    }
+}
+
+#[test]
+fn test_run() {
+    use narrative::story::RunStory as _;
+    let mut env = Env::default();
+    CheckoutContext.run_story(&mut env).unwrap();
+    assert_eq!(
+        env.log,
+        vec!["log_in: admin", "open_dashboard", "add_items: 3", "pay"]
+    );
+}
+
+#[test]
+fn test_steps() {
+    use narrative::prelude::*;
+    let steps = CheckoutContext
+        .steps()
+        .map(|step| (step.step_id(), step.step_text()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        steps,
+        vec![
+            ("log_in", "log in as admin".to_string()),
+            ("open_dashboard", "open the dashboard".to_string()),
+            ("add_items", "add 3 items to the cart".to_string()),
+            ("pay", "pay".to_string()),
+        ]
+    );
+    let args = CheckoutContext
+        .steps()
+        .flat_map(|step| {
+            step.args()
+                .map(|arg| format!("{arg:?}"))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(args, vec!["user: &str = USER", "count: u32 = ITEMS"]);
+}
+
+#[test]
+fn test_consts() {
+    use narrative::prelude::*;
+    let consts = CheckoutContext
+        .consts()
+        .map(|c| c.name())
+        .collect::<Vec<_>>();
+    assert_eq!(consts, vec!["USER", "ITEMS"]);
+}
+
+#[test]
+fn test_dyn() {
+    use narrative::prelude::*;
+    let context = CheckoutContext.to_dyn();
+    let steps = context
+        .steps()
+        .map(|step| step.step_id())
+        .collect::<Vec<_>>();
+    assert_eq!(steps, vec!["log_in", "open_dashboard", "add_items", "pay"]);
+    let consts = context.consts().map(|c| c.name()).collect::<Vec<_>>();
+    assert_eq!(consts, vec!["USER", "ITEMS"]);
+    let arg = context.steps().next().unwrap().args().next().unwrap();
+    assert_eq!(arg.name(), "user");
+}
@@ -7,6 +7,7 @@ mod chain;
 mod consts;
 mod custom_data_type;
 mod empty_story;
+mod extends;
 mod hello_world;
 mod my_first_story;
 mod story_runner;
@@ -0,0 +1,27 @@
+@shop
+Feature: Shopping
+  People buy fruits at the shop.
+
+  Background:
+    Given I open the shop
+
+  Scenario: Buy apples
+    Given I have 5 apples
+    When I buy 3 apples for 1.5 dollars
+    Then I see "Thank you"
+    And the receipt is:
+      """
+      apples x3
+      """
+    And the cart has:
+      | name  | count |
+      | apple | 3     |
+
+  Scenario Outline: Log in
+    Given I log in as <user>
+    Then I see "<message>"
+
+    Examples:
+      | user  | message |
+      | admin | Welcome |
+      | guest | Hello   |
@@ -0,0 +1,127 @@
+use std::convert::Infallible;
+
+use narrative::{
+    step::Step as _,
+    story::{RunStory, RunStoryAsync, StoryContext as _},
+};
+
+use crate::TestRunner;
+
+mod setup {
+    #[narrative::background]
+    trait CommonSetup {
+        #[step("start the server")]
+        fn start_server();
+        #[step("open the browser")]
+        fn open_browser();
+    }
+}
+
+#[narrative::story("Sign up", background = setup::CommonSetup)]
+trait SignUp {
+    #[step("sign up")]
+    fn sign_up();
+}
+
+#[narrative::story("Sign in", background = setup::CommonSetup)]
+trait SignIn {
+    #[step("sign in")]
+    fn sign_in();
+}
+
+#[derive(Default)]
+struct Env(Vec<&'static str>);
+
+impl setup::CommonSetup for Env {
+    type Error = Infallible;
+
+    fn start_server(&mut self) -> Result<(), Self::Error> {
+        self.0.push("start");
+        Ok(())
+    }
+
+    fn open_browser(&mut self) -> Result<(), Self::Error> {
+        self.0.push("open");
+        Ok(())
+    }
+}
+
+impl setup::AsyncCommonSetup for Env {
+    type Error = Infallible;
+
+    async fn start_server(&mut self) -> Result<(), Self::Error> {
+        self.0.push("start");
+        Ok(())
+    }
+
+    async fn open_browser(&mut self) -> Result<(), Self::Error> {
+        self.0.push("open");
+        Ok(())
+    }
+}
+
+impl SignUp for Env {
+    fn sign_up(&mut self) -> Result<(), Self::Error> {
+        self.0.push("sign up");
+        Ok(())
+    }
+}
+
+impl AsyncSignUp for Env {
+    async fn sign_up(&mut self) -> Result<(), Self::Error> {
+        self.0.push("sign up");
+        Ok(())
+    }
+}
+
+impl SignIn for Env {
+    fn sign_in(&mut self) -> Result<(), Self::Error> {
+        self.0.push("sign in");
+        Ok(())
+    }
+}
+
+#[test]
+fn test_background_title() {
+    assert_eq!(
+        setup::CommonSetupContext::default().story_title(),
+        "CommonSetup"
+    );
+}
+
+#[test]
+fn test_background_steps() {
+    let step_texts: Vec<_> = SignUpContext.steps().map(|step| step.step_text()).collect();
+    assert_eq!(
+        step_texts,
+        vec!["start the server", "open the browser", "sign up"]
+    );
+    let step_ids: Vec<_> = SignInContext
+        .to_dyn()
+        .steps()
+        .map(|step| step.step_id())
+        .collect();
+    assert_eq!(step_ids, vec!["start_server", "open_browser", "sign_in"]);
+}
+
+#[test]
+fn test_run_stories_with_background() {
+    let mut env = Env::default();
+    SignUpContext
+        .run_story_with_runner(&mut env, &mut TestRunner::default())
+        .unwrap();
+    SignInContext
+        .run_story_with_runner(&mut env, &mut TestRunner::default())
+        .unwrap();
+    assert_eq!(
+        env.0,
+        vec!["start", "open", "sign up", "start", "open", "sign in"]
+    );
+}
+
+#[test]
+fn test_run_story_with_background_async() {
+    let mut env = Env::default();
+    futures::executor::block_on(SignUpContext.run_story_async(&mut env)).unwrap();
+    assert_eq!(env.0, vec!["start", "open", "sign up"]);
+}
@@ -0,0 +1,139 @@
+use std::{
+    convert::Infallible,
+    path::{Path, PathBuf},
+};
+
+use narrative::{
+    step::{Step as _, StepArg as _},
+    story::{RunStory, RunStoryAsync, StoryContext as _},
+};
+
+use crate::TestRunner;
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[narrative::local_type_for(ListUsers)]
+pub struct UserRecord {
+    name: String,
+}
+
+#[narrative::story("List users")]
+trait ListUsers {
+    const DATA_DIR: &str = "data";
+
+    #[step("load users from {path:?}", path = PathBuf::from(DATA_DIR).join("users.json"))]
+    fn load_users(path: &Path);
+    #[step("list {users:?}", users = vec![UserRecord { name: "ryo".to_string() }])]
+    fn list_users(users: &[UserRecord]);
+    #[step("greet {name}", name = format!("{}!", "ryo"))]
+    fn greet(name: &str);
+    #[step("skip {names:?}", names = &["alice", "bob"])]
+    fn skip(names: &[&str]);
+}
+
+#[derive(Default)]
+struct Env(Vec<String>);
+
+impl ListUsers for Env {
+    type Error = Infallible;
+
+    fn load_users(&mut self, path: &Path) -> Result<(), Self::Error> {
+        self.0.push(path.display().to_string());
+        Ok(())
+    }
+
+    fn list_users(&mut self, users: &[UserRecord]) -> Result<(), Self::Error> {
+        self.0.extend(users.iter().map(|user| user.name.clone()));
+        Ok(())
+    }
+
+    fn greet(&mut self, name: &str) -> Result<(), Self::Error> {
+        self.0.push(name.to_string());
+        Ok(())
+    }
+
+    fn skip(&mut self, names: &[&str]) -> Result<(), Self::Error> {
+        self.0.push(names.join(", "));
+        Ok(())
+    }
+}
+
+impl AsyncListUsers for Env {
+    type Error = Infallible;
+
+    async fn load_users(&mut self, path: &Path) -> Result<(), Self::Error> {
+        self.0.push(path.display().to_string());
+        Ok(())
+    }
+
+    async fn list_users(&mut self, users: &[UserRecord]) -> Result<(), Self::Error> {
+        self.0.extend(users.iter().map(|user| user.name.clone()));
+        Ok(())
+    }
+
+    async fn greet(&mut self, name: &str) -> Result<(), Self::Error> {
+        self.0.push(name.to_string());
+        Ok(())
+    }
+
+    async fn skip(&mut self, names: &[&str]) -> Result<(), Self::Error> {
+        self.0.push(names.join(", "));
+        Ok(())
+    }
+}
+
+#[test]
+fn test_run_with_owned_values() {
+    let mut env = Env::default();
+    ListUsersContext
+        .run_story_with_runner(&mut env, &mut TestRunner::default())
+        .unwrap();
+    assert_eq!(env.0, vec!["data/users.json", "ryo", "ryo!", "alice, bob"]);
+
+    let mut env = Env::default();
+    futures::executor::block_on(ListUsersContext.run_story_async(&mut env)).unwrap();
+    assert_eq!(env.0.len(), 4);
+}
+
+#[test]
+fn test_serialize_owned_values() {
+    let values: Vec<_> = ListUsersContext
+        .steps()
+        .map(|step| {
+            let arg = step.args().next().unwrap();
+            (arg.ty(), serde_json::to_value(arg.value()).unwrap())
+        })
+        .collect();
+    assert_eq!(
+        values,
+        vec![
+            ("&Path", serde_json::json!("data/users.json")),
+            ("&[UserRecord]", serde_json::json!([{ "name": "ryo" }])),
+            ("&str", serde_json::json!("ryo!")),
+            ("&[&str]", serde_json::json!(["alice", "bob"])),
+        ]
+    );
+
+    let dyn_values: Vec<_> = ListUsersContext
+        .to_dyn()
+        .steps()
+        .map(|step| format!("{:?}", step.args().next().unwrap().value()))
+        .collect();
+    assert_eq!(dyn_values[1], r#"[UserRecord { name: "ryo" }]"#);
+}
+
+#[test]
+fn test_step_texts() {
+    let step_texts: Vec<_> = ListUsersContext
+        .steps()
+        .map(|step| step.step_text())
+        .collect();
+    assert_eq!(
+        step_texts,
+        vec![
+            r#"load users from "data/users.json""#,
+            r#"list [UserRecord { name: "ryo" }]"#,
+            "greet ryo!",
+            r#"skip ["alice", "bob"]"#,
+        ]
+    );
+}
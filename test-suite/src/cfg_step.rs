@@ -0,0 +1,125 @@
+use std::convert::Infallible;
+
+use narrative::story::{RunStory, RunStoryAsync, StoryContext as _};
+
+use crate::TestRunner;
+
+#[narrative::story("Deploy the app")]
+trait Deploy {
+    #[step("build the app")]
+    fn build();
+    #[cfg(not(test))]
+    #[step("migrate to {version}", version = 2)]
+    fn migrate(version: u32);
+    #[cfg(not(test))]
+    #[step(story: Seed, "seed the database")]
+    fn seed();
+    #[cfg(test)]
+    #[step("start the app on {port}", port = 8080)]
+    fn start(port: u16);
+}
+
+#[narrative::story("Seed the database")]
+trait Seed {
+    const TABLES: &[&str] = &["users", "posts"];
+    const VERBOSE: bool = true;
+
+    #[cfg(not(test))]
+    #[step(repeat = TABLES, "drop {table}")]
+    fn drop_table(table: &str);
+    #[step(repeat = TABLES, "seed {table}")]
+    fn seed_table(table: &str);
+    #[cfg(not(test))]
+    #[step(when = VERBOSE, "log the seeds")]
+    fn log_seeds();
+}
+
+#[derive(Default)]
+struct Env {
+    steps: Vec<String>,
+}
+
+impl Deploy for Env {
+    type Error = Infallible;
+
+    fn build(&mut self) -> Result<(), Self::Error> {
+        self.steps.push("build".to_string());
+        Ok(())
+    }
+
+    fn start(&mut self, port: u16) -> Result<(), Self::Error> {
+        self.steps.push(format!("start {port}"));
+        Ok(())
+    }
+}
+
+impl AsyncDeploy for Env {
+    type Error = Infallible;
+
+    async fn build(&mut self) -> Result<(), Self::Error> {
+        Deploy::build(self)
+    }
+
+    async fn start(&mut self, port: u16) -> Result<(), Self::Error> {
+        Deploy::start(self, port)
+    }
+}
+
+impl Seed for Env {
+    type Error = Infallible;
+
+    fn seed_table(&mut self, table: &str) -> Result<(), Self::Error> {
+        self.steps.push(format!("seed {table}"));
+        Ok(())
+    }
+}
+
+#[test]
+fn test_steps() {
+    let texts = DeployContext
+        .steps()
+        .map(|step| step.step_text())
+        .collect::<Vec<_>>();
+    assert_eq!(texts, ["build the app", "start the app on 8080"]);
+}
+
+#[test]
+fn test_dyn_steps() {
+    let texts = DeployContext
+        .to_dyn()
+        .steps()
+        .map(|step| step.step_text())
+        .collect::<Vec<_>>();
+    assert_eq!(texts, ["build the app", "start the app on 8080"]);
+}
+
+#[test]
+fn test_run() {
+    let mut env = Env::default();
+    DeployContext
+        .run_story_with_runner(&mut env, &mut TestRunner::default())
+        .unwrap();
+    assert_eq!(env.steps, ["build", "start 8080"]);
+}
+
+#[test]
+fn test_run_async() {
+    let mut env = Env::default();
+    futures::executor::block_on(DeployContext.run_story_async(&mut env)).unwrap();
+    assert_eq!(env.steps, ["build", "start 8080"]);
+}
+
+#[test]
+fn test_dynamic_steps() {
+    let texts = SeedContext
+        .steps()
+        .map(|step| step.step_text())
+        .collect::<Vec<_>>();
+    assert_eq!(texts, ["seed users", "seed posts"]);
+
+    let mut env = Env::default();
+    SeedContext
+        .run_story_with_runner(&mut env, &mut TestRunner::default())
+        .unwrap();
+    assert_eq!(env.steps, ["seed users", "seed posts"]);
+}
@@ -0,0 +1,151 @@
+use std::{convert::Infallible, sync::Mutex};
+
+use narrative::{
+    environment::DummyEnvironment,
+    record::{RecordedEvent, RecordedOverride, RecordingRunner},
+    story::{OverrideSource, RunStory, StoryConst as _},
+};
+
+#[narrative::story("Visit the site")]
+trait VisitSite {
+    #[env("NARRATIVE_TEST_BASE_URL")]
+    const BASE_URL: &str = "http://localhost:8080";
+    #[env("NARRATIVE_TEST_RETRIES")]
+    const RETRIES: u32 = 3;
+    #[env("NARRATIVE_TEST_TIMEOUT")]
+    const TIMEOUT: u32 = 30;
+    #[env("NARRATIVE_TEST_PORT")]
+    const PORT: u16 = 8080;
+    const PATH: &str = "/users";
+
+    #[step("visit {BASE_URL}{PATH}", url = format!("{BASE_URL}{PATH}"))]
+    fn visit(url: String);
+    #[step("retry {RETRIES} times")]
+    fn retry(RETRIES: u32);
+    #[step("wait {TIMEOUT} seconds")]
+    fn wait(TIMEOUT: u32);
+    #[step("listen on {PORT}")]
+    fn listen(PORT: u16);
+}
+
+// Serializes the tests that set env vars, which are read by the other tests in this module.
+static ENV: Mutex<()> = Mutex::new(());
+
+#[test]
+fn test_overridden_by_env() {
+    let _guard = ENV.lock().unwrap();
+    // SAFETY: The env vars are only used by the tests in this module, which hold the guard.
+    unsafe {
+        std::env::set_var("NARRATIVE_TEST_BASE_URL", "https://staging.example.com");
+        std::env::set_var("NARRATIVE_TEST_RETRIES", "5");
+    }
+    let steps = VisitSiteContext.steps().collect::<Vec<_>>();
+    assert_eq!(
+        steps[0].step_text(),
+        "visit https://staging.example.com/users"
+    );
+    assert_eq!(
+        serde_json::to_value(steps[0].args().next().unwrap().value()).unwrap(),
+        "https://staging.example.com/users"
+    );
+    assert_eq!(steps[1].step_text(), "retry 5 times");
+    assert_eq!(
+        serde_json::to_value(steps[1].args().next().unwrap().value()).unwrap(),
+        5
+    );
+
+    let consts = VisitSiteContext.consts().collect::<Vec<_>>();
+    assert_eq!(
+        consts[0].overridden_by(),
+        Some(OverrideSource::Env("NARRATIVE_TEST_BASE_URL"))
+    );
+    assert_eq!(consts[1].expr(), "3");
+    assert_eq!(
+        serde_json::to_value(consts[1].value()).unwrap(),
+        serde_json::json!({"RETRIES": 5})
+    );
+    assert_eq!(consts[4].overridden_by(), None);
+
+    let mut runner = RecordingRunner::default();
+    VisitSiteContext
+        .run_story_with_runner(&mut DummyEnvironment::<Infallible>::default(), &mut runner)
+        .unwrap();
+    let RecordedEvent::StartStory { overrides, .. } = &runner.recording().events[0] else {
+        panic!("the recording starts with the story");
+    };
+    assert_eq!(
+        *overrides,
+        vec![
+            RecordedOverride {
+                name: "BASE_URL".to_string(),
+                source: "env NARRATIVE_TEST_BASE_URL".to_string(),
+                value: serde_json::json!({"BASE_URL": "https://staging.example.com"}),
+                error: None,
+            },
+            RecordedOverride {
+                name: "RETRIES".to_string(),
+                source: "env NARRATIVE_TEST_RETRIES".to_string(),
+                value: serde_json::json!({"RETRIES": 5}),
+                error: None,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_not_overridden() {
+    let _guard = ENV.lock().unwrap();
+    assert_eq!(VisitSiteContext.wait().step_text(), "wait 30 seconds");
+    let timeout = VisitSiteContext.consts().nth(2).unwrap();
+    assert_eq!(timeout.overridden_by(), None);
+    assert_eq!(timeout.invalid_override(), None);
+    assert_eq!(
+        serde_json::to_value(timeout.value()).unwrap(),
+        serde_json::json!({"TIMEOUT": 30})
+    );
+}
+
+#[test]
+fn test_invalid_env_falls_back_to_default() {
+    let _guard = ENV.lock().unwrap();
+    // SAFETY: The env vars are only used by the tests in this module, which hold the guard.
+    unsafe {
+        std::env::set_var("NARRATIVE_TEST_PORT", "not a port");
+    }
+    assert_eq!(VisitSiteContext.listen().step_text(), "listen on 8080");
+    let port = VisitSiteContext.consts().nth(3).unwrap();
+    assert_eq!(port.overridden_by(), None);
+    let invalid = port.invalid_override().unwrap();
+    assert_eq!(invalid.source, OverrideSource::Env("NARRATIVE_TEST_PORT"));
+    assert_eq!(invalid.text, "not a port");
+
+    let mut runner = RecordingRunner::default();
+    VisitSiteContext
+        .run_story_with_runner(&mut DummyEnvironment::<Infallible>::default(), &mut runner)
+        .unwrap();
+    let RecordedEvent::StartStory { overrides, .. } = &runner.recording().events[0] else {
+        panic!("the recording starts with the story");
+    };
+    let recorded = overrides
+        .iter()
+        .find(|recorded| recorded.name == "PORT")
+        .unwrap();
+    assert_eq!(recorded.source, "env NARRATIVE_TEST_PORT");
+    assert_eq!(recorded.value, serde_json::json!({"PORT": 8080}));
+    assert_eq!(recorded.error, Some(invalid.to_string()));
+
+    // SAFETY: The env vars are only used by the tests in this module, which hold the guard.
+    unsafe {
+        std::env::set_var("NARRATIVE_TEST_PORT", "9090");
+    }
+    assert_eq!(VisitSiteContext.listen().step_text(), "listen on 9090");
+    assert_eq!(
+        port.overridden_by(),
+        Some(OverrideSource::Env("NARRATIVE_TEST_PORT"))
+    );
+    assert_eq!(port.invalid_override(), None);
+    // SAFETY: The env vars are only used by the tests in this module, which hold the guard.
+    unsafe {
+        std::env::remove_var("NARRATIVE_TEST_PORT");
+    }
+}
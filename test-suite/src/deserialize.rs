@@ -0,0 +1,71 @@
+use narrative::{
+    step::{Step, StepArg},
+    story::{StoryConst, StoryContext},
+};
+use serde_json::json;
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[narrative::local_type_for(DeserializeStory)]
+pub struct UserName(String);
+
+#[narrative::story("Deserialize story", deserialize)]
+trait DeserializeStory {
+    const COUNT: u32 = 2;
+    #[step("{name:?} has {count} apples", name = UserName("Ryo".into()), count = COUNT)]
+    fn has_apples(name: UserName, count: u32);
+}
+
+#[narrative::story("Not deserializable story")]
+trait NotDeserializableStory {
+    const NAME: &str = "Ryo";
+    #[step("Hi, I'm {name}", name = NAME)]
+    fn greet(name: &str);
+}
+
+#[test]
+fn test_deserialize_step_args() {
+    let step = DeserializeStoryContext.steps().next().unwrap();
+    let mut args = step.args();
+    let name = args.next().unwrap();
+    let value = name.deserialize_value(json!("Alice")).unwrap();
+    assert_eq!(value.downcast_ref(), Some(&UserName("Alice".into())));
+    let count = args.next().unwrap();
+    let value = count.deserialize_value(json!(3)).unwrap();
+    assert_eq!(value.downcast_ref(), Some(&3u32));
+    assert!(count.deserialize_value(json!("three")).is_err());
+}
+
+#[test]
+fn test_deserialize_dyn_step_args() {
+    let step = DeserializeStoryContext.steps().next().unwrap();
+    let arg = step.to_dyn().args().next().unwrap();
+    let value = arg.deserialize_value(json!("Alice")).unwrap();
+    assert_eq!(value.downcast_ref(), Some(&UserName("Alice".into())));
+}
+
+#[test]
+fn test_deserialize_consts() {
+    let count = DeserializeStoryContext.consts().next().unwrap();
+    let value = count.deserialize_value(json!(5)).unwrap();
+    assert_eq!(value.downcast_ref(), Some(&5u32));
+    let count = DeserializeStoryContext.to_dyn().consts().next().unwrap();
+    let value = count.deserialize_value(json!(5)).unwrap();
+    assert_eq!(value.downcast_ref(), Some(&5u32));
+}
+
+#[test]
+fn test_not_deserializable() {
+    let step = NotDeserializableStoryContext.steps().next().unwrap();
+    let err = step
+        .args()
+        .next()
+        .unwrap()
+        .deserialize_value(json!("Alice"))
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "`name` is not deserializable, add `deserialize` to the story attribute to enable it"
+    );
+    let name = NotDeserializableStoryContext.consts().next().unwrap();
+    assert!(name.deserialize_value(json!("Alice")).is_err());
+}
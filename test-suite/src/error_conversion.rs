@@ -0,0 +1,182 @@
+use narrative::{
+    environment::DummyEnvironment,
+    story::{RunStory, RunStoryAsync},
+};
+
+mod auth {
+    #[derive(Debug, PartialEq)]
+    pub struct AuthError;
+
+    #[narrative::story("Log in")]
+    trait Login {
+        #[step("log in")]
+        fn log_in();
+        #[step("check the session")]
+        fn check_session();
+    }
+
+    pub struct AuthEnv {
+        pub session_expired: bool,
+    }
+
+    impl Login for AuthEnv {
+        type Error = AuthError;
+
+        fn log_in(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        fn check_session(&mut self) -> Result<(), Self::Error> {
+            if self.session_expired {
+                return Err(AuthError);
+            }
+            Ok(())
+        }
+    }
+
+    impl AsyncLogin for AuthEnv {
+        type Error = AuthError;
+
+        async fn log_in(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        async fn check_session(&mut self) -> Result<(), Self::Error> {
+            if self.session_expired {
+                return Err(AuthError);
+            }
+            Ok(())
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum CartError {
+    Auth(auth::AuthError),
+}
+
+impl From<auth::AuthError> for CartError {
+    fn from(error: auth::AuthError) -> Self {
+        CartError::Auth(error)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum AppError {
+    Cart(CartError),
+}
+
+impl From<CartError> for AppError {
+    fn from(error: CartError) -> Self {
+        AppError::Cart(error)
+    }
+}
+
+#[narrative::story("Check out", error_bound = std::fmt::Debug)]
+trait Checkout {
+    #[step(story: auth::Login, "log in")]
+    fn log_in();
+    #[step("pay")]
+    fn pay();
+}
+
+#[narrative::story("Purchase", error = AppError)]
+trait Purchase {
+    #[step(story: Checkout, "check out")]
+    fn check_out();
+}
+
+struct CheckoutEnv {
+    session_expired: bool,
+}
+
+impl Checkout for CheckoutEnv {
+    type Error = CartError;
+
+    fn log_in(&mut self) -> Result<impl auth::Login<Error: Into<Self::Error>>, Self::Error> {
+        Ok(auth::AuthEnv {
+            session_expired: self.session_expired,
+        })
+    }
+
+    fn pay(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl AsyncCheckout for CheckoutEnv {
+    type Error = CartError;
+
+    fn log_in(
+        &mut self,
+    ) -> Result<impl auth::AsyncLogin<Error: Into<Self::Error>> + Send, Self::Error> {
+        Ok(auth::AuthEnv {
+            session_expired: self.session_expired,
+        })
+    }
+
+    async fn pay(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+struct PurchaseEnv {
+    session_expired: bool,
+}
+
+impl Purchase for PurchaseEnv {
+    type Error = AppError;
+
+    fn check_out(&mut self) -> Result<impl Checkout<Error: Into<Self::Error>>, Self::Error> {
+        Ok(CheckoutEnv {
+            session_expired: self.session_expired,
+        })
+    }
+}
+
+#[test]
+fn test_convert_sub_story_error() {
+    let mut env = CheckoutEnv {
+        session_expired: false,
+    };
+    CheckoutContext.run_story(&mut env).unwrap();
+    let mut env = CheckoutEnv {
+        session_expired: true,
+    };
+    assert_eq!(
+        CheckoutContext.run_story(&mut env),
+        Err(CartError::Auth(auth::AuthError))
+    );
+}
+
+#[test]
+fn test_convert_sub_story_error_async() {
+    let mut env = CheckoutEnv {
+        session_expired: true,
+    };
+    assert_eq!(
+        futures::executor::block_on(CheckoutContext.run_story_async(&mut env)),
+        Err(CartError::Auth(auth::AuthError))
+    );
+}
+
+#[test]
+fn test_convert_nested_sub_story_error() {
+    let mut env = PurchaseEnv {
+        session_expired: false,
+    };
+    PurchaseContext.run_story(&mut env).unwrap();
+    let mut env = PurchaseEnv {
+        session_expired: true,
+    };
+    assert_eq!(
+        PurchaseContext.run_story(&mut env),
+        Err(AppError::Cart(CartError::Auth(auth::AuthError)))
+    );
+}
+
+#[test]
+fn test_dummy_environment() {
+    let mut env = DummyEnvironment::<AppError>::default();
+    PurchaseContext.run_story(&mut env).unwrap();
+}
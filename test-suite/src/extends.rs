@@ -0,0 +1,107 @@
+use std::convert::Infallible;
+
+use narrative::{
+    environment::DummyEnvironment,
+    story::{RunStory, RunStoryAsync},
+};
+
+use crate::TestRunner;
+
+mod parent {
+    #[narrative::story("Parent story from other module")]
+    trait ParentStory {
+        #[step("parent step")]
+        fn parent_step();
+    }
+}
+
+#[narrative::story("Child story")]
+trait ChildStory: parent::ParentStory {
+    #[step("child step")]
+    fn child_step();
+}
+
+#[narrative::story("Grandchild story")]
+trait GrandchildStory: ChildStory {
+    #[step("grandchild step")]
+    fn grandchild_step();
+}
+
+#[narrative::story("Story using an extended story")]
+trait UsingStory {
+    #[step(story: GrandchildStory, "run grandchild story")]
+    fn run_grandchild();
+}
+
+#[derive(Default)]
+struct Env(Vec<&'static str>);
+
+impl parent::ParentStory for Env {
+    type Error = Infallible;
+
+    fn parent_step(&mut self) -> Result<(), Self::Error> {
+        self.0.push("parent");
+        Ok(())
+    }
+}
+
+impl ChildStory for Env {
+    fn child_step(&mut self) -> Result<(), Self::Error> {
+        self.0.push("child");
+        Ok(())
+    }
+}
+
+impl GrandchildStory for Env {
+    fn grandchild_step(&mut self) -> Result<(), Self::Error> {
+        self.0.push("grandchild");
+        Ok(())
+    }
+}
+
+impl parent::AsyncParentStory for Env {
+    type Error = Infallible;
+
+    async fn parent_step(&mut self) -> Result<(), Self::Error> {
+        self.0.push("parent");
+        Ok(())
+    }
+}
+
+impl AsyncChildStory for Env {
+    async fn child_step(&mut self) -> Result<(), Self::Error> {
+        self.0.push("child");
+        Ok(())
+    }
+}
+
+impl AsyncGrandchildStory for Env {
+    async fn grandchild_step(&mut self) -> Result<(), Self::Error> {
+        self.0.push("grandchild");
+        Ok(())
+    }
+}
+
+#[test]
+fn test_extends_transitively() {
+    let mut env = Env::default();
+    GrandchildStoryContext
+        .run_story_with_runner(&mut env, &mut TestRunner::default())
+        .unwrap();
+    assert_eq!(env.0, vec!["parent", "child", "grandchild"]);
+}
+
+#[test]
+fn test_extends_async() {
+    let mut env = Env::default();
+    futures::executor::block_on(GrandchildStoryContext.run_story_async(&mut env)).unwrap();
+    assert_eq!(env.0, vec!["parent", "child", "grandchild"]);
+}
+
+#[test]
+fn test_extended_story_as_sub_story() {
+    let mut env = DummyEnvironment::<Infallible>::default();
+    UsingStoryContext
+        .run_story_with_runner(&mut env, &mut TestRunner::default())
+        .unwrap();
+}
@@ -0,0 +1,64 @@
+use narrative::{
+    step::{Step as _, StepArg as _},
+    story::StoryContext as _,
+};
+use serde_json::json;
+
+/// A type from another crate, which supports neither serde nor `#[local_type_for]`.
+mod other_crate {
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct StatusCode(pub u16);
+
+    impl std::fmt::Display for StatusCode {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    impl std::str::FromStr for StatusCode {
+        type Err = std::num::ParseIntError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            s.parse().map(StatusCode)
+        }
+    }
+}
+
+#[narrative::foreign_type_for(ApiStory, display, deserialize)]
+pub struct StatusCode(pub other_crate::StatusCode);
+
+#[narrative::foreign_type_for(ApiStory, deserialize)]
+pub struct Number(serde_json::Number);
+
+#[narrative::story("Call an API", deserialize)]
+trait ApiStory {
+    #[step("get {status:?}", status = StatusCode(other_crate::StatusCode(200)))]
+    fn get(status: StatusCode);
+    #[step("count {count:?}", count = Number(3.into()))]
+    fn count(count: Number);
+}
+
+#[test]
+fn test_foreign_type_args() {
+    let steps: Vec<_> = ApiStoryContext.steps().collect();
+    assert_eq!(steps[0].step_text(), "get StatusCode(200)");
+    assert_eq!(steps[1].step_text(), "count Number(3)");
+    let status = steps[0].args().next().unwrap();
+    assert_eq!(serde_json::to_value(status.value()).unwrap(), json!("200"));
+    let value = status.deserialize_value(json!("404")).unwrap();
+    let status = value.downcast_ref::<StatusCode>().unwrap();
+    assert_eq!(**status, other_crate::StatusCode(404));
+    assert!(
+        steps[0]
+            .args()
+            .next()
+            .unwrap()
+            .deserialize_value(json!("not found"))
+            .is_err()
+    );
+    let count = steps[1].args().next().unwrap();
+    assert_eq!(serde_json::to_value(count.value()).unwrap(), json!(3));
+    let value = count.deserialize_value(json!(5)).unwrap();
+    let count: serde_json::Number = value.downcast_ref::<Number>().unwrap().clone().into();
+    assert_eq!(count.as_u64(), Some(5));
+}
@@ -0,0 +1,79 @@
+use narrative::{
+    step::{Step as _, StepArg as _},
+    story::StoryContext as _,
+};
+use serde_json::json;
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[narrative::local_type_for(Catalog)]
+pub struct ItemId(u32);
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[narrative::local_type_for(Catalog)]
+#[serde(bound(serialize = "T: serde::Serialize"))]
+pub struct Page<'a, T, const N: usize>
+where
+    T: PartialEq,
+{
+    title: &'a str,
+    #[serde(with = "serde_arrays")]
+    items: [T; N],
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[narrative::local_type_for(Catalog)]
+pub enum Choice<T> {
+    One(T),
+    Many(Vec<T>),
+}
+
+/// serde doesn't implement `Serialize` for arrays of any length `N`.
+mod serde_arrays {
+    pub fn serialize<S: serde::Serializer, T: serde::Serialize, const N: usize>(
+        items: &[T; N],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(items.as_slice(), serializer)
+    }
+}
+
+#[narrative::story("Browse the catalog")]
+trait Catalog {
+    #[step("see {page:?}", page = Page { title: "New", items: [ItemId(1), ItemId(2)] })]
+    fn see_page(page: Page<'static, ItemId, 2>);
+    #[step("see counts {page:?}", page = Page { title: "Counts", items: [3u32] })]
+    fn see_counts(page: Page<'static, u32, 1>);
+    #[step("choose {choice:?}", choice = Choice::Many(vec![ItemId(3)]))]
+    fn choose(choice: Choice<ItemId>);
+    #[step("choose one {choice:?}", choice = Choice::One(4u32))]
+    fn choose_one(choice: Choice<u32>);
+    #[step("choose a pair {choice:?}", choice = Choice::One(Some((ItemId(5), 6u32))))]
+    fn choose_pair(choice: Choice<Option<(ItemId, u32)>>);
+}
+
+#[test]
+fn test_generic_local_types() {
+    let values: Vec<_> = CatalogContext
+        .steps()
+        .map(|step| {
+            let arg = step.args().next().unwrap();
+            (arg.ty(), serde_json::to_value(arg.value()).unwrap())
+        })
+        .collect();
+    assert_eq!(
+        values,
+        vec![
+            (
+                "Page<'static, ItemId, 2>",
+                json!({ "title": "New", "items": [1, 2] })
+            ),
+            (
+                "Page<'static, u32, 1>",
+                json!({ "title": "Counts", "items": [3] })
+            ),
+            ("Choice<ItemId>", json!({ "Many": [3] })),
+            ("Choice<u32>", json!({ "One": 4 })),
+            ("Choice<Option<(ItemId, u32)>>", json!({ "One": [5, 6] })),
+        ]
+    );
+}
@@ -0,0 +1,203 @@
+use std::convert::Infallible;
+
+use narrative::{
+    environment::DummyEnvironment,
+    step::Step as _,
+    story::{RunStory, RunStoryAsync, StoryContext as _},
+};
+
+use crate::TestRunner;
+
+pub trait Backend {
+    const NAME: &str;
+}
+
+pub struct Postgres;
+
+impl Backend for Postgres {
+    const NAME: &str = "postgres";
+}
+
+pub struct Sqlite;
+
+impl Backend for Sqlite {
+    const NAME: &str = "sqlite";
+}
+
+#[narrative::story("Store a user")]
+trait StoreUser<B: Backend> {
+    const USER: &str = "alice";
+
+    #[step("store {user}", user = USER)]
+    fn store(user: &str);
+}
+
+#[narrative::story("Store users in pages")]
+trait StorePages<T, const PAGES: usize = 2>
+where
+    T: Backend + Send,
+{
+    #[step(story: StoreUser<T> { USER = "bob" }, "store bob")]
+    fn store_bob();
+    #[step(story: StoreUser<T>, "store alice")]
+    fn store_alice();
+    #[step("check pages")]
+    fn check_pages();
+}
+
+#[narrative::story("Store a user in sqlite")]
+trait StoreInSqlite: StoreUser<Sqlite> {
+    #[step("check sqlite")]
+    fn check();
+}
+
+#[narrative::story("Borrow a name")]
+trait BorrowName<'a> {
+    #[step("borrow a name")]
+    fn borrow_name();
+    #[step(story: StoreUser<Postgres>, "store a user")]
+    fn store_user();
+}
+
+#[derive(Default)]
+struct Env(Vec<String>);
+
+impl<B: Backend> StoreUser<B> for Env {
+    type Error = Infallible;
+
+    fn store(&mut self, user: &str) -> Result<(), Self::Error> {
+        self.0.push(format!("{user} in {}", B::NAME));
+        Ok(())
+    }
+}
+
+impl<B: Backend> AsyncStoreUser<B> for Env {
+    type Error = Infallible;
+
+    async fn store(&mut self, user: &str) -> Result<(), Self::Error> {
+        self.0.push(format!("{user} in {}", B::NAME));
+        Ok(())
+    }
+}
+
+impl<T: Backend + Send, const PAGES: usize> StorePages<T, PAGES> for Env {
+    type Error = Infallible;
+
+    fn store_bob(&mut self) -> Result<impl StoreUser<T, Error = Infallible>, Infallible> {
+        Ok(Env::default())
+    }
+
+    fn store_alice(&mut self) -> Result<impl StoreUser<T, Error = Infallible>, Infallible> {
+        Ok(DummyEnvironment::default())
+    }
+
+    fn check_pages(&mut self) -> Result<(), Infallible> {
+        self.0.push(format!("{PAGES} pages in {}", T::NAME));
+        Ok(())
+    }
+}
+
+impl<'a> BorrowName<'a> for Env {
+    type Error = Infallible;
+
+    fn borrow_name(&mut self) -> Result<(), Self::Error> {
+        self.0.push("borrowed".to_string());
+        Ok(())
+    }
+
+    fn store_user(&mut self) -> Result<impl StoreUser<Postgres, Error = Infallible>, Infallible> {
+        Ok(Env::default())
+    }
+}
+
+impl<'a> AsyncBorrowName<'a> for Env {
+    type Error = Infallible;
+
+    async fn borrow_name(&mut self) -> Result<(), Self::Error> {
+        self.0.push("borrowed".to_string());
+        Ok(())
+    }
+
+    fn store_user(
+        &mut self,
+    ) -> Result<impl AsyncStoreUser<Postgres, Error = Infallible> + Send, Infallible> {
+        Ok(DummyEnvironment::default())
+    }
+}
+
+impl StoreInSqlite for Env {
+    fn check(&mut self) -> Result<(), Self::Error> {
+        self.0.push("checked".to_string());
+        Ok(())
+    }
+}
+
+#[test]
+fn test_run_generic_story() {
+    let mut env = Env::default();
+    StoreUserContext::<Postgres>::default()
+        .run_story_with_runner(&mut env, &mut TestRunner::default())
+        .unwrap();
+    StoreUserContext::<Sqlite>::default()
+        .run_story_with_runner(&mut env, &mut TestRunner::default())
+        .unwrap();
+    assert_eq!(env.0, vec!["alice in postgres", "alice in sqlite"]);
+}
+
+#[test]
+fn test_run_generic_story_async() {
+    let mut env = Env::default();
+    futures::executor::block_on(StoreUserContext::<Postgres>::default().run_story_async(&mut env))
+        .unwrap();
+    assert_eq!(env.0, vec!["alice in postgres"]);
+}
+
+#[test]
+fn test_generic_sub_story() {
+    let mut env = Env::default();
+    StorePagesContext::<Sqlite, 3>::default()
+        .run_story_with_runner(&mut env, &mut TestRunner::default())
+        .unwrap();
+    assert_eq!(env.0, vec!["3 pages in sqlite"]);
+
+    let context = StorePagesContext::<Postgres>::default();
+    let nested_story = context.store_bob().nested_story().unwrap();
+    let step = nested_story.steps().next().unwrap();
+    assert_eq!(step.step_text(), "store bob");
+    assert_eq!(
+        context
+            .store_alice()
+            .to_dyn()
+            .dyn_nested_story()
+            .unwrap()
+            .story_id(),
+        "StoreUser"
+    );
+}
+
+#[test]
+fn test_extend_instantiated_story() {
+    let mut env = Env::default();
+    StoreInSqliteContext
+        .run_story_with_runner(&mut env, &mut TestRunner::default())
+        .unwrap();
+    assert_eq!(env.0, vec!["alice in sqlite", "checked"]);
+}
+
+#[test]
+fn test_story_with_lifetime() {
+    let mut env = Env::default();
+    BorrowNameContext
+        .run_story_with_runner(&mut env, &mut TestRunner::default())
+        .unwrap();
+    futures::executor::block_on(BorrowNameContext.run_story_async(&mut env)).unwrap();
+    assert_eq!(env.0, vec!["borrowed", "borrowed"]);
+}
+
+#[test]
+fn test_dummy_environment() {
+    let mut env = DummyEnvironment::<Infallible>::default();
+    StorePagesContext::<Postgres>::default()
+        .run_story_with_runner(&mut env, &mut TestRunner::default())
+        .unwrap();
+}
@@ -0,0 +1,33 @@
+use narrative::{
+    http::{Method, StatusCode},
+    step::{Step as _, StepArg as _},
+    story::StoryContext as _,
+};
+use serde_json::json;
+
+#[narrative::story("Call an endpoint", deserialize)]
+trait CallEndpoint {
+    #[step("{method} /users returns {status}", method = Method(http::Method::POST), status = StatusCode(http::StatusCode::CREATED))]
+    fn call(method: Method, status: StatusCode);
+}
+
+#[test]
+fn test_serialize_by_display() {
+    let step = CallEndpointContext.steps().next().unwrap();
+    assert_eq!(step.step_text(), "POST /users returns 201 Created");
+    let values: Vec<_> = step
+        .args()
+        .map(|arg| serde_json::to_value(arg.value()).unwrap())
+        .collect();
+    assert_eq!(values, vec![json!("POST"), json!("201 Created")]);
+    assert_eq!(format!("{:?}", step.args().nth(1).unwrap().value()), "201");
+}
+
+#[test]
+fn test_deserialize_by_from_str() {
+    let status: StatusCode = serde_json::from_value(json!("404")).unwrap();
+    assert_eq!(*status, http::StatusCode::NOT_FOUND);
+    let method: Method = serde_json::from_value(json!("DELETE")).unwrap();
+    assert_eq!(http::Method::from(method), http::Method::DELETE);
+    assert!(serde_json::from_value::<StatusCode>(json!("teapot")).is_err());
+}
@@ -0,0 +1,139 @@
+use std::convert::Infallible;
+
+use narrative::{
+    step::Step as _,
+    story::{RunStory as _, StoryContext as _},
+};
+
+use crate::TestRunner;
+
+narrative::include_feature!("features/shopping.feature");
+
+#[derive(Default, Clone)]
+struct Env {
+    logs: Vec<String>,
+}
+
+impl ShoppingBackground for Env {
+    type Error = Infallible;
+
+    fn i_open_the_shop(&mut self) -> Result<(), Self::Error> {
+        self.logs.push("open".to_string());
+        Ok(())
+    }
+}
+
+impl BuyApples for Env {
+    fn i_have_apples(&mut self, apples: i64) -> Result<(), Self::Error> {
+        self.logs.push(format!("have {apples}"));
+        Ok(())
+    }
+
+    fn i_buy_apples_for_dollars(&mut self, apples: i64, dollars: f64) -> Result<(), Self::Error> {
+        self.logs.push(format!("buy {apples} for {dollars}"));
+        Ok(())
+    }
+
+    fn i_see(&mut self, text: &str) -> Result<(), Self::Error> {
+        self.logs.push(format!("see {text}"));
+        Ok(())
+    }
+
+    fn the_receipt_is(&mut self, doc_string: &str) -> Result<(), Self::Error> {
+        self.logs.push(format!("receipt {doc_string}"));
+        Ok(())
+    }
+
+    fn the_cart_has(&mut self, table: &[&[&str]]) -> Result<(), Self::Error> {
+        self.logs.push(format!("cart {table:?}"));
+        Ok(())
+    }
+}
+
+impl LogIn for Env {
+    fn i_log_in_as(&mut self, user: &str) -> Result<(), Self::Error> {
+        self.logs.push(format!("log in as {user}"));
+        Ok(())
+    }
+
+    fn i_see(&mut self, message: &str) -> Result<(), Self::Error> {
+        self.logs.push(format!("see {message}"));
+        Ok(())
+    }
+}
+
+impl LogInExamples for Env {
+    type Error = Infallible;
+
+    fn example_1(&mut self) -> Result<impl LogIn<Error = Self::Error>, Self::Error> {
+        Ok(self.clone())
+    }
+
+    fn example_2(&mut self) -> Result<impl LogIn<Error = Self::Error>, Self::Error> {
+        Ok(self.clone())
+    }
+}
+
+#[test]
+fn test_scenario_from_feature() {
+    assert_eq!(BuyApplesContext.story_title(), "Buy apples");
+    let step_texts: Vec<_> = BuyApplesContext
+        .steps()
+        .map(|step| step.step_text())
+        .collect();
+    assert_eq!(
+        step_texts,
+        vec![
+            "I open the shop",
+            "I have 5 apples",
+            "I buy 3 apples for 1.5 dollars",
+            "I see \"Thank you\"",
+            "the receipt is:",
+            "the cart has:",
+        ]
+    );
+    let mut env = Env::default();
+    BuyApplesContext
+        .run_story_with_runner(&mut env, &mut TestRunner::default())
+        .unwrap();
+    assert_eq!(
+        env.logs,
+        vec![
+            "open",
+            "have 5",
+            "buy 3 for 1.5",
+            "see Thank you",
+            "receipt apples x3",
+            r#"cart [["name", "count"], ["apple", "3"]]"#,
+        ]
+    );
+}
+
+#[test]
+fn test_scenario_outline_from_feature() {
+    let step_texts: Vec<_> = LogInExamplesContext
+        .steps()
+        .map(|step| step.step_text())
+        .collect();
+    assert_eq!(
+        step_texts,
+        vec![
+            "user = admin, message = Welcome",
+            "user = guest, message = Hello"
+        ]
+    );
+    let nested_steps: Vec<_> = LogInExamplesContext
+        .steps()
+        .nth(1)
+        .unwrap()
+        .to_dyn()
+        .dyn_nested_story()
+        .unwrap()
+        .steps()
+        .map(|step| step.step_text())
+        .collect();
+    assert_eq!(
+        nested_steps,
+        vec!["I open the shop", "I log in as guest", "I see \"Hello\""]
+    );
+}
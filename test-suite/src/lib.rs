@@ -3,9 +3,30 @@
 
 use narrative::{runner::StoryRunner, step::Run};
 
+mod background;
+mod borrowed_step_arg;
+mod cfg_step;
+mod const_overrides;
+mod deserialize;
+mod error_conversion;
+mod extends;
+mod foreign_type;
+mod generic_local_type;
+mod generic_story;
+mod http_types;
+mod include_feature;
+mod localization;
 mod multiple_stories_in_one_file;
+mod record;
+mod remote;
+mod repeat_and_when;
+mod shared_local_type;
 mod step_arg;
+mod step_match;
+mod step_text_template;
 mod story_consts;
+mod story_values;
+mod sub_story_bindings;
 mod sub_story_from_other_module;
 
 #[derive(Default)]
@@ -0,0 +1,144 @@
+use narrative::{
+    step::{DynStep, Step as _},
+    story::{DynStoryContext, StoryContext as _},
+};
+
+#[narrative::story("Buy oranges", ja = "オレンジを買う", zh_TW = "買橘子")]
+trait BuyOranges {
+    const SHOP: &str = "Fruit Shop";
+    const BAGS: &[u32] = &[1, 2];
+
+    #[step("go to {SHOP}", text(ja = "{SHOP}に行く"))]
+    fn go_to_shop();
+    #[step(
+        "I have {count} oranges",
+        count = 3,
+        text(ja = "オレンジを{count}個持っている")
+    )]
+    fn have_oranges(count: u32);
+    #[step(repeat = BAGS, "buy bag {bag} for {price} yen", price = 100, text(ja = "袋{bag}を買う"))]
+    fn buy_bag(bag: u32, price: u32);
+    #[step("pay")]
+    fn pay();
+}
+
+#[narrative::story("Visit the market", ja = "市場に行く")]
+trait VisitMarket {
+    #[step(story: BuyOranges { SHOP = "Market" }, "buy oranges", text(ja = "オレンジを買う"))]
+    fn buy_oranges();
+}
+
+#[test]
+fn test_story_title_in() {
+    assert_eq!(
+        BuyOrangesContext.story_title_in("ja"),
+        Some("オレンジを買う".to_string())
+    );
+    assert_eq!(
+        BuyOrangesContext.story_title_in("zh_TW"),
+        Some("買橘子".to_string())
+    );
+    assert_eq!(BuyOrangesContext.story_title_in("fr"), None);
+    assert_eq!(
+        BuyOrangesContext.to_dyn().story_title_in("ja"),
+        Some("オレンジを買う".to_string())
+    );
+}
+
+#[test]
+fn test_step_text_in() {
+    let texts: Vec<_> = BuyOrangesContext
+        .steps()
+        .map(|step| step.step_text_in("ja"))
+        .collect();
+    assert_eq!(
+        texts,
+        vec![
+            Some("Fruit Shopに行く".to_string()),
+            Some("オレンジを3個持っている".to_string()),
+            Some("袋1を買う".to_string()),
+            Some("袋2を買う".to_string()),
+            None,
+        ]
+    );
+    assert_eq!(BuyOrangesContext.go_to_shop().step_text_in("fr"), None);
+}
+
+#[test]
+fn test_dyn_step_text_in() {
+    let texts: Vec<_> = BuyOrangesContext
+        .to_dyn()
+        .steps()
+        .map(|step| step.step_text_in("ja"))
+        .collect();
+    assert_eq!(texts[0], Some("Fruit Shopに行く".to_string()));
+    assert_eq!(texts[3], Some("袋2を買う".to_string()));
+    assert_eq!(texts[4], None);
+}
+
+#[test]
+fn test_dyn_texts_by_builders() {
+    fn story() -> DynStoryContext {
+        DynStoryContext::new(
+            "Greet",
+            "Greet",
+            || Box::new(std::iter::empty()),
+            || Box::new(std::iter::once(step())),
+        )
+        .with_story_titles(&[("ja", "挨拶する")])
+    }
+    fn step() -> DynStep {
+        DynStep::new(
+            || "say hello".to_string(),
+            "say_hello",
+            || Box::new(std::iter::empty()),
+            story,
+            || None,
+        )
+        .with_step_text_in(|locale| (locale == "ja").then(|| "こんにちはと言う".to_string()))
+    }
+    assert_eq!(story().story_title_in("ja"), Some("挨拶する".to_string()));
+    let step = story().steps().next().unwrap();
+    assert_eq!(
+        step.step_text_in("ja"),
+        Some("こんにちはと言う".to_string())
+    );
+    assert_eq!(step.step_text_in("fr"), None);
+    assert_eq!(
+        step.step_segments(),
+        vec![narrative::text::StepTextSegment::Literal(
+            "say hello".to_string()
+        )]
+    );
+}
+
+#[test]
+fn test_localized_texts_of_bound_sub_story() {
+    let step = VisitMarketContext.buy_oranges();
+    assert_eq!(step.step_text_in("ja"), Some("オレンジを買う".to_string()));
+    let nested_story = step.nested_story().unwrap();
+    assert_eq!(
+        nested_story.story_title_in("ja"),
+        Some("オレンジを買う".to_string())
+    );
+    let texts: Vec<_> = nested_story
+        .steps()
+        .map(|step| step.step_text_in("ja"))
+        .collect();
+    assert_eq!(texts[0], Some("Marketに行く".to_string()));
+}
+
+#[narrative::story("Visit a page")]
+trait VisitPage {
+    #[step("visit {url} as {id}", url = "http://example.com", id = "ryo")]
+    fn visit();
+}
+
+#[test]
+fn test_args_named_like_locales() {
+    assert_eq!(
+        VisitPageContext.visit().step_text(),
+        "visit http://example.com as ryo"
+    );
+    assert_eq!(VisitPageContext.visit().step_text_in("id"), None);
+}
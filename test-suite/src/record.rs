@@ -0,0 +1,157 @@
+use std::convert::Infallible;
+
+use narrative::{
+    environment::DummyEnvironment,
+    record::{DiffingRunner, Outcome, RecordedEvent, Recording, RecordingRunner, StepDiff},
+    story::RunStory,
+};
+
+#[narrative::story("Record sub story")]
+trait RecordSubStory {
+    #[step("prepare")]
+    fn prepare();
+}
+
+#[narrative::story("Record story")]
+trait RecordStory {
+    #[step(story: RecordSubStory, "run sub story")]
+    fn run_sub_story();
+    #[step("I have {count} apples", count = 2)]
+    fn have_apples(count: u32);
+}
+
+#[derive(Debug, PartialEq)]
+struct NoApples(u32);
+
+struct Env;
+
+impl RecordSubStory for Env {
+    type Error = NoApples;
+
+    fn prepare(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl RecordStory for Env {
+    type Error = NoApples;
+
+    fn run_sub_story(&mut self) -> Result<impl RecordSubStory<Error = Self::Error>, Self::Error> {
+        Ok(Env)
+    }
+
+    fn have_apples(&mut self, count: u32) -> Result<(), Self::Error> {
+        Err(NoApples(count))
+    }
+}
+
+fn record() -> Recording {
+    let mut runner = RecordingRunner::default();
+    let err = RecordStoryContext
+        .run_story_with_runner(&mut Env, &mut runner)
+        .unwrap_err();
+    assert_eq!(err, NoApples(2));
+    runner.into_recording()
+}
+
+#[test]
+fn test_recording() {
+    let recording = record();
+    let events = recording
+        .events
+        .iter()
+        .map(|event| match event {
+            RecordedEvent::StartStory { story_id, .. } => format!("start {story_id}"),
+            RecordedEvent::Step(step) => format!("step {}: {:?}", step.step_text, step.outcome),
+            RecordedEvent::EndStory { story_id } => format!("end {story_id}"),
+        })
+        .collect::<Vec<_>>();
+    // The root story does not end because of the failure.
+    assert_eq!(
+        events,
+        vec![
+            "start RecordStory",
+            "step run sub story: Passed",
+            "start RecordSubStory",
+            "step prepare: Passed",
+            "end RecordSubStory",
+            "step I have 2 apples: Failed { error: \"NoApples(2)\" }",
+        ]
+    );
+    let step = recording.steps().last().unwrap();
+    assert_eq!(step.args[0].name, "count");
+    assert_eq!(step.args[0].value, serde_json::json!(2));
+}
+
+#[test]
+fn test_save_and_load() {
+    let recording = record();
+    let path = std::env::temp_dir().join(format!("narrative-record-{}.json", std::process::id()));
+    recording.save(&path).unwrap();
+    let loaded = Recording::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded, recording);
+}
+
+#[test]
+fn test_diff_same_run() {
+    let mut runner = DiffingRunner::new(record());
+    let _ = RecordStoryContext.run_story_with_runner(&mut Env, &mut runner);
+    assert_eq!(runner.diff(), vec![]);
+}
+
+#[test]
+fn test_diff_with_dummy_environment() {
+    let mut runner = DiffingRunner::new(record());
+    RecordStoryContext
+        .run_story_with_runner(&mut DummyEnvironment::<Infallible>::default(), &mut runner)
+        .unwrap();
+    let diffs = runner.diff();
+    assert_eq!(diffs.len(), 1);
+    let StepDiff::Changed {
+        index,
+        expected,
+        actual,
+    } = &diffs[0]
+    else {
+        panic!("unexpected diff: {diffs:?}");
+    };
+    assert_eq!(*index, 2);
+    assert_eq!(expected.step_id, "have_apples");
+    assert_eq!(actual.outcome, Outcome::Passed);
+}
+
+#[test]
+fn test_diff_missing_steps() {
+    let mut runner = DiffingRunner::new(record());
+    RecordSubStoryContext
+        .run_story_with_runner(&mut DummyEnvironment::<Infallible>::default(), &mut runner)
+        .unwrap();
+    // The step of the sub story is aligned with the recorded one, so it is not changed.
+    let diffs = runner.diff();
+    assert_eq!(diffs.len(), 2);
+    assert!(
+        matches!(&diffs[0], StepDiff::Missing { index: 0, expected } if expected.step_id == "run_sub_story")
+    );
+    assert!(
+        matches!(&diffs[1], StepDiff::Missing { index: 2, expected } if expected.step_id == "have_apples")
+    );
+}
+
+#[test]
+fn test_diff_inserted_step() {
+    // The recording without the step of the sub story, as if the step is inserted in the middle.
+    let mut recording = record();
+    recording
+        .events
+        .retain(|event| !matches!(event, RecordedEvent::Step(step) if step.step_id == "prepare"));
+    let mut runner = DiffingRunner::new(recording);
+    let _ = RecordStoryContext.run_story_with_runner(&mut Env, &mut runner);
+    let diffs = runner.diff();
+    assert_eq!(diffs.len(), 1, "unexpected diffs: {diffs:?}");
+    let StepDiff::Added { index, actual } = &diffs[0] else {
+        panic!("unexpected diff: {diffs:?}");
+    };
+    assert_eq!(*index, 1);
+    assert_eq!(actual.step_id, "prepare");
+}
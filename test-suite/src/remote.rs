@@ -0,0 +1,264 @@
+use std::{
+    io::{BufRead, BufReader, Cursor, Read, Write},
+    net::TcpListener,
+    process::Command,
+    thread::JoinHandle,
+};
+
+use narrative::{
+    remote::{
+        ChildProcess, HttpTransport, JsonLines, RemoteError, RemoteResult, RemoteRunner, Reply,
+    },
+    story::DynStoryContext,
+};
+use serde_json::json;
+
+#[narrative::story("Remote sub story")]
+trait RemoteSubStory {
+    #[step("sub step")]
+    fn sub_step();
+}
+
+#[narrative::story("Remote story")]
+trait RemoteStory {
+    const NAME: &str = "Ryo";
+    #[step("Hi, I'm {name}", name = NAME)]
+    fn greet(name: &str);
+    #[step(story: RemoteSubStory, "run sub story")]
+    fn run_sub_story();
+    #[step("I have {count} apples", count = 2)]
+    fn fail_step(count: u32);
+}
+
+#[test]
+fn test_requests() {
+    let replies = "{\"result\":\"ok\"}\n".repeat(8);
+    let mut runner = RemoteRunner::new(JsonLines::new(Cursor::new(replies), Vec::new()));
+    runner.run_story(RemoteStoryContext).unwrap();
+    let (_, written) = runner.into_inner().into_inner();
+    let requests = String::from_utf8(written)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        requests,
+        vec![
+            json!({"type": "start_story", "story_id": "RemoteStory", "story_title": "Remote story"}),
+            json!({
+                "type": "step",
+                "story_id": "RemoteStory",
+                "step_id": "greet",
+                "step_text": "Hi, I'm Ryo",
+                "args": [{"name": "name", "ty": "&str", "expr": "NAME", "value": "Ryo"}],
+                "nested_story": null,
+            }),
+            json!({
+                "type": "step",
+                "story_id": "RemoteStory",
+                "step_id": "run_sub_story",
+                "step_text": "run sub story",
+                "args": [],
+                "nested_story": {"story_id": "RemoteSubStory", "story_title": "Remote sub story"},
+            }),
+            json!({"type": "start_story", "story_id": "RemoteSubStory", "story_title": "Remote sub story"}),
+            json!({
+                "type": "step",
+                "story_id": "RemoteSubStory",
+                "step_id": "sub_step",
+                "step_text": "sub step",
+                "args": [],
+                "nested_story": null,
+            }),
+            json!({"type": "end_story", "story_id": "RemoteSubStory"}),
+            json!({
+                "type": "step",
+                "story_id": "RemoteStory",
+                "step_id": "fail_step",
+                "step_text": "I have 2 apples",
+                "args": [{"name": "count", "ty": "u32", "expr": "2", "value": 2}],
+                "nested_story": null,
+            }),
+            json!({"type": "end_story", "story_id": "RemoteStory"}),
+        ]
+    );
+}
+
+#[test]
+fn test_closed() {
+    let mut runner = RemoteRunner::new(JsonLines::new(Cursor::new(""), Vec::new()));
+    let err = runner.run_story(RemoteStoryContext).unwrap_err();
+    assert!(matches!(err, RemoteError::Closed));
+}
+
+// A stand-in for a step implementation written in another language.
+const ECHO_PROCESS: &str = r#"
+while IFS= read -r line; do
+    case "$line" in
+        *'"step_id":"fail_step"'*) echo '{"result":"error","message":"no apples"}' ;;
+        *) echo '{"result":"ok"}' ;;
+    esac
+done
+"#;
+
+#[test]
+fn test_child_process() {
+    let process = ChildProcess::spawn(Command::new("sh").args(["-c", ECHO_PROCESS])).unwrap();
+    let mut runner = RemoteRunner::new(process);
+    let err = runner.run_story(RemoteStoryContext).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "step RemoteStory::fail_step failed: no apples"
+    );
+    assert!(runner.into_inner().wait().unwrap().success());
+}
+
+#[test]
+fn test_run_dyn_stories() {
+    let stories: Vec<DynStoryContext> =
+        vec![RemoteSubStoryContext.to_dyn(), RemoteStoryContext.to_dyn()];
+    let replies = "{\"result\":\"ok\"}\n".repeat(11);
+    let mut runner = RemoteRunner::new(JsonLines::new(Cursor::new(replies), Vec::new()));
+    for story in stories {
+        runner.run_dyn_story(story).unwrap();
+    }
+    let ids: Vec<_> = runner
+        .results()
+        .iter()
+        .map(|result| (result.story_id, result.step_id))
+        .collect();
+    assert_eq!(
+        ids,
+        vec![
+            ("RemoteSubStory", None),
+            ("RemoteSubStory", Some("sub_step")),
+            ("RemoteSubStory", None),
+            ("RemoteStory", None),
+            ("RemoteStory", Some("greet")),
+            ("RemoteStory", Some("run_sub_story")),
+            ("RemoteSubStory", None),
+            ("RemoteSubStory", Some("sub_step")),
+            ("RemoteSubStory", None),
+            ("RemoteStory", Some("fail_step")),
+            ("RemoteStory", None),
+        ]
+    );
+}
+
+#[test]
+fn test_child_process_sub_story() {
+    let process = ChildProcess::spawn(Command::new("sh").args(["-c", ECHO_PROCESS])).unwrap();
+    let mut runner = RemoteRunner::new(process);
+    runner.run_story(RemoteSubStoryContext).unwrap();
+    assert!(runner.into_inner().wait().unwrap().success());
+}
+
+// A stub server that replies to each request with the reply for its step id, and returns the
+// received bodies when the given number of requests are handled.
+fn stub_server(
+    requests: usize,
+    reply: fn(&serde_json::Value) -> (u16, String),
+) -> (String, JoinHandle<Vec<serde_json::Value>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    let handle = std::thread::spawn(move || {
+        let mut bodies = Vec::new();
+        for _ in 0..requests {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            assert_eq!(request_line, "POST /steps HTTP/1.1\r\n");
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line == "\r\n" {
+                    break;
+                }
+                if let Some(value) = line.strip_prefix("Content-Length: ") {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            let (status, response) = reply(&body);
+            write!(
+                stream,
+                "HTTP/1.1 {status} OK\r\nContent-Length: {}\r\n\r\n{response}",
+                response.len()
+            )
+            .unwrap();
+            bodies.push(body);
+        }
+        bodies
+    });
+    (addr, handle)
+}
+
+fn reply_by_step_id(body: &serde_json::Value) -> (u16, String) {
+    let reply = match body["step_id"].as_str() {
+        Some("run_sub_story") => json!({"result": "skip", "message": "not ready"}),
+        Some("fail_step") => json!({"result": "fail", "message": "no apples"}),
+        _ => json!({"result": "pass"}),
+    };
+    (200, reply.to_string())
+}
+
+#[test]
+fn test_http() {
+    let (addr, server) = stub_server(4, reply_by_step_id);
+    let mut runner = RemoteRunner::new(HttpTransport::new(addr, "/steps"));
+    let err = runner.run_story(RemoteStoryContext).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "step RemoteStory::fail_step failed: no apples"
+    );
+    assert_eq!(
+        runner.results(),
+        &[
+            RemoteResult {
+                story_id: "RemoteStory",
+                step_id: None,
+                reply: Reply::Ok { message: None },
+            },
+            RemoteResult {
+                story_id: "RemoteStory",
+                step_id: Some("greet"),
+                reply: Reply::Ok { message: None },
+            },
+            RemoteResult {
+                story_id: "RemoteStory",
+                step_id: Some("run_sub_story"),
+                reply: Reply::Skip {
+                    message: Some("not ready".into()),
+                },
+            },
+            RemoteResult {
+                story_id: "RemoteStory",
+                step_id: Some("fail_step"),
+                reply: Reply::Error {
+                    message: "no apples".into(),
+                },
+            },
+        ]
+    );
+    let bodies = server.join().unwrap();
+    assert_eq!(bodies[1]["step_text"], "Hi, I'm Ryo");
+    assert_eq!(bodies[1]["args"][0]["value"], "Ryo");
+    // The nested story of the skipped step is not started.
+    assert_eq!(bodies[3]["step_id"], "fail_step");
+}
+
+#[test]
+fn test_http_error_status() {
+    let (addr, server) = stub_server(1, |_| (500, "internal error".into()));
+    let mut runner = RemoteRunner::new(HttpTransport::new(addr, "/steps"));
+    let err = runner.run_story(RemoteStoryContext).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "http error: unexpected status 500: internal error"
+    );
+    server.join().unwrap();
+}
@@ -0,0 +1,175 @@
+use std::convert::Infallible;
+
+use narrative::{
+    step::{Step as _, StepArg as _},
+    story::{RunStory, RunStoryAsync, StoryContext as _},
+};
+
+use crate::TestRunner;
+
+#[narrative::story("Check out a cart")]
+trait Checkout {
+    const ITEMS: &[&str] = &["apple", "banana", "cherry"];
+    const COUPON_ENABLED: bool = false;
+
+    #[step("open the cart")]
+    fn open_cart();
+    #[step(repeat = ITEMS, "add {item} to the cart", quantity = 2)]
+    fn add_item(item: &str, quantity: u32);
+    #[step(when = COUPON_ENABLED, "apply a coupon")]
+    fn apply_coupon();
+    #[step(when = ITEMS.len() > 2, "get free shipping")]
+    fn get_free_shipping();
+}
+
+#[narrative::story("Check out with a coupon")]
+trait CheckoutWithCoupon {
+    #[step(story: Checkout { ITEMS = &["durian"], COUPON_ENABLED = true }, "check out a durian")]
+    fn check_out();
+}
+
+#[derive(Default)]
+struct Env(Vec<String>);
+
+impl Checkout for Env {
+    type Error = Infallible;
+
+    fn open_cart(&mut self) -> Result<(), Self::Error> {
+        self.0.push("open".to_string());
+        Ok(())
+    }
+
+    fn add_item(&mut self, item: &str, quantity: u32) -> Result<(), Self::Error> {
+        self.0.push(format!("{quantity} {item}"));
+        Ok(())
+    }
+
+    fn apply_coupon(&mut self) -> Result<(), Self::Error> {
+        self.0.push("coupon".to_string());
+        Ok(())
+    }
+
+    fn get_free_shipping(&mut self) -> Result<(), Self::Error> {
+        self.0.push("free shipping".to_string());
+        Ok(())
+    }
+}
+
+impl AsyncCheckout for Env {
+    type Error = Infallible;
+
+    async fn open_cart(&mut self) -> Result<(), Self::Error> {
+        self.0.push("open".to_string());
+        Ok(())
+    }
+
+    async fn add_item(&mut self, item: &str, quantity: u32) -> Result<(), Self::Error> {
+        self.0.push(format!("{quantity} {item}"));
+        Ok(())
+    }
+
+    async fn apply_coupon(&mut self) -> Result<(), Self::Error> {
+        self.0.push("coupon".to_string());
+        Ok(())
+    }
+
+    async fn get_free_shipping(&mut self) -> Result<(), Self::Error> {
+        self.0.push("free shipping".to_string());
+        Ok(())
+    }
+}
+
+impl CheckoutWithCoupon for Env {
+    type Error = Infallible;
+
+    fn check_out(&mut self) -> Result<impl Checkout<Error = Self::Error>, Self::Error> {
+        Ok(Env::default())
+    }
+}
+
+#[test]
+fn test_steps() {
+    let step_texts: Vec<_> = CheckoutContext
+        .steps()
+        .map(|step| step.step_text())
+        .collect();
+    assert_eq!(
+        step_texts,
+        vec![
+            "open the cart",
+            "add apple to the cart",
+            "add banana to the cart",
+            "add cherry to the cart",
+            "get free shipping",
+        ]
+    );
+}
+
+#[test]
+fn test_repeated_step_args() {
+    let step = CheckoutContext.add_item(1);
+    let args: Vec<_> = step
+        .args()
+        .map(|arg| format!("{}: {} = {:?}", arg.name(), arg.expr(), arg.value()))
+        .collect();
+    assert_eq!(args, vec![r#"item: ITEMS = "banana""#, "quantity: 2 = 2"]);
+
+    let dyn_step = step.to_dyn();
+    assert_eq!(dyn_step.step_text(), "add banana to the cart");
+    let values: Vec<_> = dyn_step
+        .args()
+        .map(|arg| format!("{:?}", arg.value()))
+        .collect();
+    assert_eq!(values, vec![r#""banana""#, "2"]);
+}
+
+#[test]
+fn test_dyn_steps() {
+    let step_ids: Vec<_> = CheckoutContext
+        .to_dyn()
+        .steps()
+        .map(|step| step.step_id())
+        .collect();
+    assert_eq!(
+        step_ids,
+        vec![
+            "open_cart",
+            "add_item",
+            "add_item",
+            "add_item",
+            "get_free_shipping"
+        ]
+    );
+}
+
+#[test]
+fn test_run() {
+    let mut env = Env::default();
+    CheckoutContext
+        .run_story_with_runner(&mut env, &mut TestRunner::default())
+        .unwrap();
+    assert_eq!(
+        env.0,
+        vec!["open", "2 apple", "2 banana", "2 cherry", "free shipping"]
+    );
+}
+
+#[test]
+fn test_run_async() {
+    let mut env = Env::default();
+    futures::executor::block_on(CheckoutContext.run_story_async(&mut env)).unwrap();
+    assert_eq!(env.0.len(), 5);
+}
+
+#[test]
+fn test_bound_items_and_condition() {
+    let nested_story = CheckoutWithCouponContext
+        .check_out()
+        .nested_story()
+        .unwrap();
+    let step_texts: Vec<_> = nested_story.steps().map(|step| step.step_text()).collect();
+    assert_eq!(
+        step_texts,
+        vec!["open the cart", "add durian to the cart", "apply a coupon"]
+    );
+}
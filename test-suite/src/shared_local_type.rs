@@ -0,0 +1,40 @@
+use narrative::{
+    step::{Step as _, StepArg as _},
+    story::StoryContext as _,
+};
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[narrative::local_type_for(SignUp, Checkout)]
+pub struct UserId(&'static str);
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[narrative::local_type_for(SignUp, Checkout)]
+pub struct User {
+    id: UserId,
+    name: String,
+}
+
+#[narrative::story("Sign up")]
+trait SignUp {
+    #[step("sign up as {user:?}", user = User { id: UserId("ryo"), name: "Ryo".to_string() })]
+    fn sign_up(user: User);
+}
+
+#[narrative::story("Checkout")]
+trait Checkout {
+    #[step(story: SignUp, "sign up")]
+    fn sign_up();
+    #[step("pay as {user:?}", user = UserId("ryo"))]
+    fn pay(user: UserId);
+}
+
+#[test]
+fn test_local_type_shared_by_stories() {
+    let sign_up = SignUpContext.steps().next().unwrap();
+    let user = sign_up.args().next().unwrap();
+    assert_eq!(user.ty(), "User");
+    let pay = CheckoutContext.steps().nth(1).unwrap();
+    let user_id = pay.args().next().unwrap();
+    assert_eq!(user_id.ty(), "UserId");
+    assert_eq!(serde_json::to_value(user_id.value()).unwrap(), "ryo");
+}
@@ -1 +1,3 @@
 mod complex_type;
+mod container_types;
+mod std_types;
@@ -0,0 +1,78 @@
+use std::{
+    collections::BTreeMap,
+    ops::{Bound, Range},
+    sync::Arc,
+};
+
+use narrative::{
+    step::{Step as _, StepArg as _},
+    story::StoryContext as _,
+};
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[narrative::local_type_for(Containers)]
+pub struct UserId(&'static str);
+
+type Users = Vec<UserId>;
+
+mod custom {
+    use super::ContainersLocalType;
+
+    // Named like a std container, but asserted as a local type rather than by its element.
+    #[derive(Debug, Clone, serde::Serialize)]
+    #[narrative::local_type_for(Containers)]
+    pub struct Vec(pub u32);
+}
+
+#[narrative::story("Containers")]
+trait Containers {
+    #[step("invite {users:?}", users = vec![UserId("ryo")])]
+    fn invite(users: Users);
+    #[step("group {groups:?}", groups = BTreeMap::from([("admins".to_string(), vec![UserId("ryo")])]))]
+    fn group(groups: BTreeMap<String, Vec<UserId>>);
+    #[step("share {ids:?}", ids = Arc::<[UserId]>::from([UserId("ryo")]))]
+    fn share(ids: Arc<[UserId]>);
+    #[step("rank {ranks:?}", ranks = &[(UserId("ryo"), 1)])]
+    fn rank(ranks: &[(UserId, u32)]);
+    #[step("name {name}", name = Arc::<str>::from("ryo"))]
+    fn name(name: Arc<str>);
+    #[step("page {page:?}", page = custom::Vec(2))]
+    fn page(page: custom::Vec);
+    #[step("retry in {window:?}", window = 1..3)]
+    fn retry(window: Range<u32>);
+}
+
+#[test]
+fn test_container_args() {
+    let values: Vec<_> = ContainersContext
+        .steps()
+        .flat_map(|step| {
+            step.args()
+                .map(|arg| serde_json::to_value(arg.value()).unwrap())
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    assert_eq!(
+        values,
+        vec![
+            serde_json::json!(["ryo"]),
+            serde_json::json!({ "admins": ["ryo"] }),
+            serde_json::json!(["ryo"]),
+            serde_json::json!([["ryo", 1]]),
+            serde_json::json!("ryo"),
+            serde_json::json!(2),
+            serde_json::json!({ "start": 1, "end": 3 }),
+        ]
+    );
+}
+
+fn assert_independent<T: narrative::IndependentType>() {}
+
+#[test]
+fn test_composite_independent_types() {
+    assert_independent::<&str>();
+    assert_independent::<(u32, String)>();
+    assert_independent::<[u32; 3]>();
+    assert_independent::<Range<u32>>();
+    assert_independent::<Bound<u32>>();
+}
@@ -0,0 +1,53 @@
+use std::{borrow::Cow, collections::BTreeMap, num::Wrapping};
+
+use narrative::{
+    step::{Step as _, StepArg as _},
+    story::StoryContext as _,
+};
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[narrative::local_type_for(StdTypes)]
+pub struct Settings {
+    label: Cow<'static, str>,
+    retries: Wrapping<u8>,
+    tags: Vec<String>,
+    limit: Box<u32>,
+}
+
+#[narrative::story("Std types")]
+trait StdTypes {
+    #[step("configure {settings:?}", settings = Settings {
+        label: Cow::Borrowed("fast"),
+        retries: Wrapping(3),
+        tags: vec!["a".to_string()],
+        limit: Box::new(10),
+    })]
+    fn configure(settings: Settings);
+    #[step("set limits {limits:?}", limits = BTreeMap::from([("apples".to_string(), Some(3))]))]
+    fn set_limits(limits: BTreeMap<String, Option<u32>>);
+    #[step("name it {name}", name = Cow::Borrowed("orange"))]
+    fn name_it(name: Cow<'static, str>);
+    #[step("retry {retries} times", retries = Wrapping(2u8))]
+    fn retry(retries: Wrapping<u8>);
+}
+
+#[test]
+fn test_std_type_args() {
+    let values: Vec<_> = StdTypesContext
+        .steps()
+        .flat_map(|step| {
+            step.args()
+                .map(|arg| serde_json::to_value(arg.value()).unwrap())
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    assert_eq!(
+        values,
+        vec![
+            serde_json::json!({ "label": "fast", "retries": 3, "tags": ["a"], "limit": 10 }),
+            serde_json::json!({ "apples": 3 }),
+            serde_json::json!("orange"),
+            serde_json::json!(2),
+        ]
+    );
+}
@@ -0,0 +1,120 @@
+use narrative::{step::Step as _, story::StoryContext as _};
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[narrative::local_type_for(ShopStory)]
+pub enum Fruit {
+    Apple,
+    Orange,
+}
+
+#[narrative::story("Shopping", deserialize)]
+trait ShopStory {
+    const SHOP: u32 = 1;
+
+    #[step("I go to the shop #{SHOP}")]
+    fn go_to_shop();
+    #[step("I have {count} orange{count:plural(s)}", count = 1)]
+    fn have_oranges(count: u32);
+    #[step("I buy {count} {fruit:?} for {price} yen", count = 2, fruit = Fruit::Apple, price = 300)]
+    fn buy(count: u32, fruit: Fruit, price: u32);
+    #[step("I say {{hello}}")]
+    fn say_hello();
+    #[step("code {a}{b}-{c}-{d}", a = String::from("a"), b = String::from("b"), c = String::from("c"), d = String::from("d"))]
+    fn enter_code(a: String, b: String, c: String, d: String);
+}
+
+#[test]
+fn test_match_step() {
+    let matched = ShopStoryContext.match_step("I have 5 oranges").unwrap();
+    assert_eq!(matched.step().step_id(), "have_oranges");
+    assert_eq!(matched.arg_text("count"), Some("5"));
+    assert_eq!(matched.parse_arg::<u32>("count"), Some(Ok(5)));
+    let matched = ShopStoryContext.match_step("  I have 1 orange ").unwrap();
+    assert_eq!(matched.parse_arg::<u32>("count"), Some(Ok(1)));
+    assert!(matched.parse_arg::<u32>("price").is_none());
+
+    let matched = ShopStoryContext.match_step("I go to the shop #1").unwrap();
+    assert_eq!(matched.step().step_id(), "go_to_shop");
+    assert_eq!(matched.args().count(), 0);
+    assert!(ShopStoryContext.match_step("I go to the shop #2").is_none());
+
+    let matched = ShopStoryContext.match_step("I say {hello}").unwrap();
+    assert_eq!(matched.step().step_id(), "say_hello");
+    assert!(ShopStoryContext.match_step("I say hello").is_none());
+    assert!(ShopStoryContext.match_step("I have oranges").is_none());
+}
+
+#[test]
+fn test_match_adjacent_args() {
+    let matched = ShopStoryContext.match_step("code ab-cdef-g").unwrap();
+    assert_eq!(matched.step().step_id(), "enter_code");
+    assert_eq!(
+        matched.args().collect::<Vec<_>>(),
+        vec![("a", "a"), ("b", "b"), ("c", "cdef"), ("d", "g")]
+    );
+    assert!(ShopStoryContext.match_step("code a-b").is_none());
+
+    // Long texts are matched in one pass without backtracking.
+    let text = format!("code {}", "x".repeat(10_000));
+    assert!(ShopStoryContext.match_step(&text).is_none());
+}
+
+#[test]
+fn test_deserialize_matched_args() {
+    let matched = ShopStoryContext
+        .match_step("I buy 3 Orange for 120 yen")
+        .unwrap();
+    assert_eq!(matched.deserialize_arg::<u32>("count").unwrap(), Ok(3));
+    assert_eq!(
+        matched.deserialize_arg::<Fruit>("fruit").unwrap(),
+        Ok(Fruit::Orange)
+    );
+    assert_eq!(
+        matched.deserialize_arg::<String>("price").unwrap(),
+        Ok("120".to_string())
+    );
+    assert!(matched.deserialize_arg::<u32>("fruit").unwrap().is_err());
+
+    let values = matched.deserialize_arg_values().unwrap();
+    let names: Vec<_> = values.iter().map(|(name, _)| *name).collect();
+    assert_eq!(names, vec!["count", "fruit", "price"]);
+    assert_eq!(values[0].1.downcast_ref(), Some(&3u32));
+    assert_eq!(values[1].1.downcast_ref(), Some(&Fruit::Orange));
+    assert_eq!(values[2].1.downcast_ref(), Some(&120u32));
+}
+
+#[test]
+fn test_deserialize_untyped_matched_args() {
+    let matched = ShopStoryContext.match_step("code a1-inf-NaN").unwrap();
+    assert_eq!(
+        matched.deserialize_arg::<serde_json::Value>("b").unwrap(),
+        Ok(serde_json::json!(1))
+    );
+    assert_eq!(
+        matched.deserialize_arg::<serde_json::Value>("c").unwrap(),
+        Ok(serde_json::json!("inf"))
+    );
+    assert_eq!(
+        matched.deserialize_arg::<serde_json::Value>("d").unwrap(),
+        Ok(serde_json::json!("NaN"))
+    );
+    let matched = ShopStoryContext.match_step("code ab-1e5-1.5").unwrap();
+    assert_eq!(
+        matched.deserialize_arg::<serde_json::Value>("c").unwrap(),
+        Ok(serde_json::json!("1e5"))
+    );
+    assert_eq!(
+        matched.deserialize_arg::<serde_json::Value>("d").unwrap(),
+        Ok(serde_json::json!(1.5))
+    );
+}
+
+#[test]
+fn test_match_dyn_step() {
+    let context = ShopStoryContext.to_dyn();
+    let matched = context.match_step("I buy 1 Apple for 100 yen").unwrap();
+    assert_eq!(matched.step().step_id(), "buy");
+    assert_eq!(matched.parse_arg::<u32>("price"), Some(Ok(100)));
+    let values = matched.deserialize_arg_values().unwrap();
+    assert_eq!(values[1].1.downcast_ref(), Some(&Fruit::Apple));
+}
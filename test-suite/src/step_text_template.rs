@@ -0,0 +1,117 @@
+use narrative::{
+    step::Step as _,
+    story::{RunStory, StoryContext as _},
+    text::StepTextSegment,
+};
+
+use crate::TestRunner;
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct User {
+    name: &'static str,
+}
+
+fn currency(amount: &u32) -> String {
+    format!("¥{amount}")
+}
+
+#[narrative::story("Buy oranges")]
+trait BuyOranges {
+    const USER: User = User { name: "Ryo" };
+    const BAGS: &[u32] = &[1, 3];
+
+    #[step("{USER.name} has {count} orange{count:plural(s)}", count = 1)]
+    fn have_oranges(count: u32);
+    #[step(repeat = BAGS, "{USER.name} buys {bag} bag{bag:plural(s)} for {price:currency}", price = 300)]
+    fn buy_bags(bag: u32, price: u32);
+    #[step("{USER.name:?} pays {total:>6}", total = 900)]
+    fn pay(total: u32);
+    #[step("open the file(s)")]
+    fn open_files();
+    #[step("call {name}(s) and open {file}(rw)", name = "ryo", file = "a.txt")]
+    fn call_and_open(name: &str, file: &str);
+}
+
+impl BuyOrangesLocalType for User {}
+
+struct Env;
+
+impl BuyOranges for Env {
+    type Error = std::convert::Infallible;
+
+    fn have_oranges(&mut self, _count: u32) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn buy_bags(&mut self, _bag: u32, _price: u32) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn pay(&mut self, _total: u32) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn open_files(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn call_and_open(&mut self, _name: &str, _file: &str) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_step_texts() {
+    let step_texts: Vec<_> = BuyOrangesContext
+        .steps()
+        .map(|step| step.step_text())
+        .collect();
+    assert_eq!(
+        step_texts,
+        vec![
+            "Ryo has 1 orange",
+            "Ryo buys 1 bag for ¥300",
+            "Ryo buys 3 bags for ¥300",
+            r#""Ryo" pays    900"#,
+            "open the file(s)",
+            "call ryo(s) and open a.txt(rw)",
+        ]
+    );
+}
+
+#[test]
+fn test_step_segments() {
+    let segments = BuyOrangesContext.buy_bags(1).step_segments();
+    assert_eq!(
+        segments,
+        vec![
+            StepTextSegment::Arg {
+                name: "USER.name",
+                text: "Ryo".to_string()
+            },
+            StepTextSegment::Literal(" buys ".to_string()),
+            StepTextSegment::Arg {
+                name: "bag",
+                text: "3".to_string()
+            },
+            StepTextSegment::Literal(" bags for ".to_string()),
+            StepTextSegment::Arg {
+                name: "price",
+                text: "¥300".to_string()
+            },
+        ]
+    );
+    let dyn_segments = BuyOrangesContext.buy_bags(1).to_dyn().step_segments();
+    assert_eq!(dyn_segments, segments);
+    assert_eq!(
+        BuyOrangesContext.open_files().step_segments(),
+        vec![StepTextSegment::Literal("open the file(s)".to_string())]
+    );
+}
+
+#[test]
+fn test_run() {
+    BuyOrangesContext
+        .run_story_with_runner(&mut Env, &mut TestRunner::default())
+        .unwrap();
+}
@@ -0,0 +1,253 @@
+use std::{
+    convert::Infallible,
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use narrative::story::{RunStory, RunStoryAsync, StoryConst as _};
+
+use crate::TestRunner;
+
+static RUNS: AtomicU64 = AtomicU64::new(0);
+
+fn next_run_id() -> u64 {
+    RUNS.fetch_add(1, Ordering::Relaxed)
+}
+
+#[narrative::story("Register users")]
+trait RegisterUsers {
+    const DATA_DIR: &str = "data";
+    #[narrative::value]
+    const USERS: Vec<String> = vec!["alice".to_string(), "bob".to_string()];
+    #[narrative::value]
+    const USERS_FILE: PathBuf = PathBuf::from(DATA_DIR).join("users.json");
+    #[narrative::value]
+    const RUN_ID: u64 = next_run_id();
+
+    #[step("register {USERS:?} in run {RUN_ID}", users = USERS, run_id = RUN_ID)]
+    fn register(users: Vec<String>, run_id: u64);
+    #[step("save users to {path:?}", path = USERS_FILE)]
+    fn save(path: &Path);
+    #[step("check run {RUN_ID}")]
+    fn check(RUN_ID: u64);
+}
+
+#[narrative::story("Register admins")]
+trait RegisterAdmins {
+    #[step(story: RegisterUsers { USERS = vec!["admin".to_string()] }, "register admins")]
+    fn register_admins();
+}
+
+#[narrative::story("Check a run")]
+trait CheckRun {
+    const ID: u64 = 0;
+
+    #[step("check run {ID}")]
+    fn check_run(ID: u64);
+}
+
+#[narrative::story("Start a run")]
+trait StartRun {
+    #[narrative::value]
+    const RUN: u64 = next_run_id();
+
+    #[step("start run {RUN}")]
+    fn start_run(RUN: u64);
+    #[step(story: CheckRun { ID = RUN }, "check the run")]
+    fn check_the_run();
+}
+
+#[derive(Default)]
+struct Env {
+    users: Vec<String>,
+    paths: Vec<PathBuf>,
+    run_ids: Vec<u64>,
+}
+
+impl RegisterUsers for Env {
+    type Error = Infallible;
+
+    fn register(&mut self, users: Vec<String>, run_id: u64) -> Result<(), Self::Error> {
+        self.users.extend(users);
+        self.run_ids.push(run_id);
+        Ok(())
+    }
+
+    fn save(&mut self, path: &Path) -> Result<(), Self::Error> {
+        self.paths.push(path.to_path_buf());
+        Ok(())
+    }
+
+    fn check(&mut self, run_id: u64) -> Result<(), Self::Error> {
+        self.run_ids.push(run_id);
+        Ok(())
+    }
+}
+
+impl AsyncRegisterUsers for Env {
+    type Error = Infallible;
+
+    async fn register(&mut self, users: Vec<String>, run_id: u64) -> Result<(), Self::Error> {
+        RegisterUsers::register(self, users, run_id)
+    }
+
+    async fn save(&mut self, path: &Path) -> Result<(), Self::Error> {
+        RegisterUsers::save(self, path)
+    }
+
+    async fn check(&mut self, run_id: u64) -> Result<(), Self::Error> {
+        RegisterUsers::check(self, run_id)
+    }
+}
+
+impl RegisterAdmins for Env {
+    type Error = Infallible;
+
+    fn register_admins(&mut self) -> Result<impl RegisterUsers<Error = Self::Error>, Self::Error> {
+        Ok(Env::default())
+    }
+}
+
+#[derive(Default, Clone)]
+struct RunIds(Arc<Mutex<Vec<u64>>>);
+
+impl CheckRun for RunIds {
+    type Error = Infallible;
+
+    fn check_run(&mut self, id: u64) -> Result<(), Self::Error> {
+        self.0.lock().unwrap().push(id);
+        Ok(())
+    }
+}
+
+impl AsyncCheckRun for RunIds {
+    type Error = Infallible;
+
+    async fn check_run(&mut self, id: u64) -> Result<(), Self::Error> {
+        CheckRun::check_run(self, id)
+    }
+}
+
+impl StartRun for RunIds {
+    type Error = Infallible;
+
+    fn start_run(&mut self, run: u64) -> Result<(), Self::Error> {
+        self.0.lock().unwrap().push(run);
+        Ok(())
+    }
+
+    fn check_the_run(&mut self) -> Result<impl CheckRun<Error = Self::Error>, Self::Error> {
+        Ok(self.clone())
+    }
+}
+
+impl AsyncStartRun for RunIds {
+    type Error = Infallible;
+
+    async fn start_run(&mut self, run: u64) -> Result<(), Self::Error> {
+        StartRun::start_run(self, run)
+    }
+
+    fn check_the_run(&mut self) -> Result<impl AsyncCheckRun<Error = Self::Error>, Self::Error> {
+        Ok(self.clone())
+    }
+}
+
+#[test]
+fn test_evaluated_once_per_run() {
+    let mut env = Env::default();
+    RegisterUsersContext
+        .run_story_with_runner(&mut env, &mut TestRunner::default())
+        .unwrap();
+    RegisterUsersContext
+        .run_story_with_runner(&mut env, &mut TestRunner::default())
+        .unwrap();
+    assert_eq!(env.users, ["alice", "bob", "alice", "bob"]);
+    assert_eq!(env.paths, [Path::new("data/users.json"); 2]);
+    assert_eq!(env.run_ids[0], env.run_ids[1]);
+    assert_eq!(env.run_ids[2], env.run_ids[3]);
+    assert_ne!(env.run_ids[0], env.run_ids[2]);
+}
+
+#[test]
+fn test_evaluated_once_per_async_run() {
+    let mut env = Env::default();
+    futures::executor::block_on(RegisterUsersContext.run_story_async(&mut env)).unwrap();
+    futures::executor::block_on(RegisterUsersContext.run_story_async(&mut env)).unwrap();
+    assert_eq!(env.run_ids[0], env.run_ids[1]);
+    assert_ne!(env.run_ids[0], env.run_ids[2]);
+}
+
+#[test]
+fn test_step_text() {
+    assert!(
+        RegisterUsersContext
+            .register()
+            .step_text()
+            .starts_with(r#"register ["alice", "bob"] in run "#)
+    );
+    assert_eq!(
+        RegisterUsersContext.save().step_text(),
+        r#"save users to "data/users.json""#
+    );
+}
+
+#[test]
+fn test_listed_in_consts() {
+    let consts = RegisterUsersContext
+        .to_dyn()
+        .dyn_consts()
+        .collect::<Vec<_>>();
+    let names = consts.iter().map(|c| c.name()).collect::<Vec<_>>();
+    assert_eq!(names, ["DATA_DIR", "USERS", "USERS_FILE", "RUN_ID"]);
+    assert_eq!(consts[1].ty(), "Vec<String>");
+    assert_eq!(
+        consts[1].expr(),
+        r#"vec!["alice".to_string(), "bob".to_string()]"#
+    );
+    assert_eq!(
+        serde_json::to_value(consts[1].value()).unwrap(),
+        serde_json::json!(["alice", "bob"])
+    );
+    assert_eq!(
+        serde_json::to_value(consts[2].value()).unwrap(),
+        "data/users.json"
+    );
+}
+
+#[test]
+fn test_bound_by_parent_story() {
+    let nested_story = RegisterAdminsContext
+        .register_admins()
+        .to_dyn()
+        .dyn_nested_story()
+        .unwrap();
+    let step = nested_story.steps().next().unwrap();
+    assert!(
+        step.step_text()
+            .starts_with(r#"register ["admin"] in run "#)
+    );
+}
+
+#[test]
+fn test_shared_with_sub_story() {
+    let mut env = RunIds::default();
+    StartRunContext
+        .run_story_with_runner(&mut env, &mut TestRunner::default())
+        .unwrap();
+    let run_ids = env.0.lock().unwrap().clone();
+    assert_eq!(run_ids.len(), 2);
+    assert_eq!(run_ids[0], run_ids[1]);
+}
+
+#[test]
+fn test_shared_with_async_sub_story() {
+    let mut env = RunIds::default();
+    futures::executor::block_on(StartRunContext.run_story_async(&mut env)).unwrap();
+    let run_ids = env.0.lock().unwrap().clone();
+    assert_eq!(run_ids.len(), 2);
+    assert_eq!(run_ids[0], run_ids[1]);
+}
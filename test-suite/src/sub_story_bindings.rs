@@ -0,0 +1,166 @@
+use std::{
+    convert::Infallible,
+    sync::{Arc, Mutex},
+};
+
+use narrative::story::{RunStory, RunStoryAsync, StoryConst as _};
+
+use crate::TestRunner;
+
+mod login {
+    #[narrative::story("Log in")]
+    trait Login {
+        const USER: &str = "guest";
+
+        #[step("log in as {USER}", user = USER)]
+        fn log_in(user: &str);
+        #[step("see {message}", message = format!("Welcome, {USER}"))]
+        fn see_message(message: String);
+    }
+}
+
+#[narrative::story("Log in as admin and guest")]
+trait AdminAndGuest {
+    const ADMIN: &str = "admin";
+
+    #[step(story: login::Login { USER = ADMIN }, "log in as admin")]
+    fn log_in_as_admin();
+    #[step(story: login::Login, "log in as guest")]
+    fn log_in_as_guest();
+}
+
+#[narrative::story("Log in as root")]
+trait Root {
+    #[step(story: AdminAndGuest { ADMIN = "root" }, "log in as root and guest")]
+    fn log_in_as_root();
+}
+
+#[derive(Default, Clone)]
+struct Env(Arc<Mutex<Vec<String>>>);
+
+impl login::Login for Env {
+    type Error = Infallible;
+
+    fn log_in(&mut self, user: &str) -> Result<(), Self::Error> {
+        self.0.lock().unwrap().push(user.to_string());
+        Ok(())
+    }
+
+    fn see_message(&mut self, message: String) -> Result<(), Self::Error> {
+        self.0.lock().unwrap().push(message);
+        Ok(())
+    }
+}
+
+impl login::AsyncLogin for Env {
+    type Error = Infallible;
+
+    async fn log_in(&mut self, user: &str) -> Result<(), Self::Error> {
+        self.0.lock().unwrap().push(user.to_string());
+        Ok(())
+    }
+
+    async fn see_message(&mut self, message: String) -> Result<(), Self::Error> {
+        self.0.lock().unwrap().push(message);
+        Ok(())
+    }
+}
+
+impl AdminAndGuest for Env {
+    type Error = Infallible;
+
+    fn log_in_as_admin(&mut self) -> Result<impl login::Login<Error = Self::Error>, Self::Error> {
+        Ok(self.clone())
+    }
+
+    fn log_in_as_guest(&mut self) -> Result<impl login::Login<Error = Self::Error>, Self::Error> {
+        Ok(self.clone())
+    }
+}
+
+impl AsyncAdminAndGuest for Env {
+    type Error = Infallible;
+
+    fn log_in_as_admin(
+        &mut self,
+    ) -> Result<impl login::AsyncLogin<Error = Self::Error>, Self::Error> {
+        Ok(self.clone())
+    }
+
+    fn log_in_as_guest(
+        &mut self,
+    ) -> Result<impl login::AsyncLogin<Error = Self::Error>, Self::Error> {
+        Ok(self.clone())
+    }
+}
+
+impl Root for Env {
+    type Error = Infallible;
+
+    fn log_in_as_root(&mut self) -> Result<impl AdminAndGuest<Error = Self::Error>, Self::Error> {
+        Ok(self.clone())
+    }
+}
+
+#[test]
+fn test_run_with_bound_consts() {
+    let mut env = Env::default();
+    AdminAndGuestContext
+        .run_story_with_runner(&mut env, &mut TestRunner::default())
+        .unwrap();
+    assert_eq!(
+        *env.0.lock().unwrap(),
+        vec!["admin", "Welcome, admin", "guest", "Welcome, guest"]
+    );
+}
+
+#[test]
+fn test_run_async_with_bound_consts() {
+    let mut env = Env::default();
+    futures::executor::block_on(AdminAndGuestContext.run_story_async(&mut env)).unwrap();
+    assert_eq!(
+        *env.0.lock().unwrap(),
+        vec!["admin", "Welcome, admin", "guest", "Welcome, guest"]
+    );
+}
+
+#[test]
+fn test_nested_story_with_bound_consts() {
+    let nested_story = AdminAndGuestContext
+        .log_in_as_admin()
+        .to_dyn()
+        .dyn_nested_story()
+        .unwrap();
+    let steps = nested_story.steps().collect::<Vec<_>>();
+    assert_eq!(steps[0].step_text(), "log in as admin");
+    assert_eq!(steps[1].step_text(), "see Welcome, admin");
+    let args = steps[0].args().collect::<Vec<_>>();
+    assert_eq!(args[0].expr(), "USER");
+    assert_eq!(serde_json::to_value(args[0].value()).unwrap(), "admin");
+    let consts = nested_story.dyn_consts().collect::<Vec<_>>();
+    assert_eq!(serde_json::to_value(consts[0].value()).unwrap(), "admin");
+    assert_eq!(consts[0].expr(), "\"guest\"");
+
+    // The unbound sub story keeps the values in its definition.
+    let nested_story = AdminAndGuestContext
+        .log_in_as_guest()
+        .to_dyn()
+        .dyn_nested_story()
+        .unwrap();
+    assert_eq!(
+        nested_story.steps().next().unwrap().step_text(),
+        "log in as guest"
+    );
+}
+
+#[test]
+fn test_bound_consts_of_bound_story() {
+    let mut env = Env::default();
+    RootContext
+        .run_story_with_runner(&mut env, &mut TestRunner::default())
+        .unwrap();
+    assert_eq!(
+        *env.0.lock().unwrap(),
+        vec!["root", "Welcome, root", "guest", "Welcome, guest"]
+    );
+}